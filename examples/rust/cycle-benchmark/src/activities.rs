@@ -11,16 +11,11 @@ use feo_tracing::{instrument, tracing};
 pub struct DummyActivity {
     /// ID of the activity
     activity_id: ActivityId,
-    /// ID as string (only used for tracing)
-    _id_str: String,
 }
 
 impl DummyActivity {
     pub fn build(activity_id: ActivityId) -> Box<dyn Activity> {
-        Box::new(Self {
-            activity_id,
-            _id_str: u64::from(activity_id).to_string(),
-        })
+        Box::new(Self { activity_id })
     }
 }
 
@@ -34,7 +29,7 @@ impl Activity for DummyActivity {
 
     #[instrument(name = "Activity step")]
     fn step(&mut self) {
-        tracing::event!(tracing::Level::TRACE, id = self._id_str);
+        tracing::event!(tracing::Level::TRACE, id = u64::from(self.activity_id));
     }
 
     #[instrument(name = "Activity shutdown")]
@@ -150,6 +150,7 @@ mod direct_mpsc {
     use cycle_benchmark::config::ApplicationConfig;
     use feo::recording::recorder::RecordingRules;
     use feo::recording::registry::TypeRegistry;
+    use feo_com::interface::Namespace;
 
     pub(super) use feo::agent::direct::primary_mpsc::{Primary, PrimaryConfig};
     pub(super) use feo::agent::direct::recorder::{Recorder, RecorderConfig};
@@ -170,12 +171,14 @@ mod direct_mpsc {
 
         let agent_id = params.agent_id;
         PrimaryConfig {
+            id: agent_id,
             cycle_time: params.feo_cycle_time,
             activity_dependencies: app_config.activity_dependencies(),
             // With only one agent, we cannot attach a recorder
             recorder_ids: vec![],
             worker_assignments: app_config.worker_assignments().remove(&agent_id).unwrap(),
             timeout: Duration::from_secs(10),
+            namespace: Namespace::default(),
         }
     }
 
@@ -188,7 +191,7 @@ mod direct_mpsc {
         _: ApplicationConfig,
         _: &TypeRegistry,
         _: RecordingRules,
-    ) -> RecorderConfig {
+    ) -> RecorderConfig<'_> {
         panic!("direct mpsc signalling does not support recorders");
     }
 }
@@ -199,6 +202,8 @@ mod direct_sockets {
     use feo::agent::NodeAddress;
     use feo::recording::recorder::RecordingRules;
     use feo::recording::registry::TypeRegistry;
+    use feo::recording::rotation::Rotation;
+    use feo_com::interface::Namespace;
 
     pub(super) use feo::agent::direct::primary::{Primary, PrimaryConfig};
     pub(super) use feo::agent::direct::recorder::{Recorder, RecorderConfig};
@@ -219,12 +224,14 @@ mod direct_sockets {
     ) -> PrimaryConfig {
         let agent_id = params.agent_id;
         PrimaryConfig {
+            id: agent_id,
             cycle_time: params.feo_cycle_time,
             activity_dependencies: app_config.activity_dependencies(),
             recorder_ids: app_config.recorders(),
             worker_assignments: app_config.worker_assignments().remove(&agent_id).unwrap(),
             timeout: Duration::from_secs(10),
             endpoint: endpoint(&app_config, signalling),
+            namespace: Namespace::default(),
         }
     }
 
@@ -241,6 +248,7 @@ mod direct_sockets {
                 .unwrap(),
             timeout: Duration::from_secs(1),
             endpoint: endpoint(&app_config, signalling),
+            namespace: Namespace::default(),
         }
     }
 
@@ -250,15 +258,20 @@ mod direct_sockets {
         type_registry: &TypeRegistry,
         recording_rules: RecordingRules,
         signalling: SignallingType,
-    ) -> RecorderConfig {
+    ) -> RecorderConfig<'_> {
         let agent_id = params.agent_id;
         RecorderConfig {
             id: agent_id,
+            app_name: "cycle-benchmark",
             record_file: "./rec.bin",
+            rotation: Rotation::default(),
+            black_box: None,
+            decoupled: None,
             rules: recording_rules,
             registry: type_registry,
             receive_timeout: Duration::from_secs(10),
             endpoint: endpoint(&app_config, signalling),
+            namespace: Namespace::default(),
         }
     }
 }
@@ -269,6 +282,8 @@ mod relayed_sockets {
     use feo::agent::NodeAddress;
     use feo::recording::recorder::RecordingRules;
     use feo::recording::registry::TypeRegistry;
+    use feo::recording::rotation::Rotation;
+    use feo_com::interface::Namespace;
 
     pub(super) use feo::agent::relayed::primary::{Primary, PrimaryConfig};
     pub(super) use feo::agent::relayed::recorder::{Recorder, RecorderConfig};
@@ -310,6 +325,7 @@ mod relayed_sockets {
             id: agent_id,
             worker_agent_map: app_config.worker_agent_map(),
             activity_worker_map: app_config.activity_worker_map(),
+            namespace: Namespace::default(),
         }
     }
 
@@ -327,6 +343,7 @@ mod relayed_sockets {
             timeout: Duration::from_secs(10),
             bind_address_senders: endpoints.0,
             bind_address_receivers: endpoints.1,
+            namespace: Namespace::default(),
         }
     }
 
@@ -336,17 +353,22 @@ mod relayed_sockets {
         type_registry: &TypeRegistry,
         recording_rules: RecordingRules,
         signalling: SignallingType,
-    ) -> RecorderConfig {
+    ) -> RecorderConfig<'_> {
         let agent_id = params.agent_id;
         let endpoints = endpoints(&app_config, signalling);
         RecorderConfig {
             id: agent_id,
+            app_name: "cycle-benchmark",
             record_file: "./rec.bin",
+            rotation: Rotation::default(),
+            black_box: None,
+            decoupled: None,
             rules: recording_rules,
             registry: type_registry,
             receive_timeout: Duration::from_secs(10),
             bind_address_senders: endpoints.0,
             bind_address_receivers: endpoints.1,
+            namespace: Namespace::default(),
         }
     }
 }
@@ -11,8 +11,6 @@ use std::collections::{HashMap, HashSet};
 
 pub struct CompositeActivity {
     activity_id: ActivityId,
-    /// ID as string (only used for tracing)
-    _activity_id_str: String,
     activities: Vec<Box<dyn Activity>>,
 }
 
@@ -26,7 +24,6 @@ impl CompositeActivity {
 
         let composite = CompositeActivity {
             activity_id,
-            _activity_id_str: u64::from(activity_id).to_string(),
             activities,
         };
         Box::new(composite)
@@ -47,7 +44,7 @@ impl Activity for CompositeActivity {
 
     #[instrument(name = "Composite step")]
     fn step(&mut self) {
-        tracing::event!(tracing::Level::TRACE, id = self._activity_id_str);
+        tracing::event!(tracing::Level::TRACE, id = u64::from(self.activity_id));
         for activity in &mut self.activities {
             activity.step();
         }
@@ -5,10 +5,9 @@
 use crate::activities::messages::{BrakeInstruction, CameraImage, RadarScan, Scene, Steering};
 use core::fmt;
 use core::hash::{BuildHasher as _, Hasher as _};
-use core::mem::MaybeUninit;
-use core::ops::{Deref, DerefMut, Range};
+use core::ops::{Deref, Range};
 use core::time::Duration;
-use feo::activity::Activity;
+use feo::activity::{request_recording_trigger, Activity};
 use feo::ids::ActivityId;
 use feo_com::interface::{ActivityInput, ActivityOutput};
 #[cfg(feature = "com_iox2")]
@@ -187,7 +186,7 @@ impl NeuralNet {
         })
     }
 
-    fn infer(image: &CameraImage, radar: &RadarScan, scene: &mut MaybeUninit<Scene>) {
+    fn infer(image: &CameraImage, radar: &RadarScan, scene: &mut Scene) {
         let CameraImage {
             num_people,
             num_cars,
@@ -198,18 +197,11 @@ impl NeuralNet {
         let distance_left_lane = gen_random_in_range(5..10) as f64 / 10.0;
         let distance_right_lane = gen_random_in_range(5..10) as f64 / 10.0;
 
-        // Get raw pointer to payload within `MaybeUninit`.
-        let scene_ptr = scene.as_mut_ptr();
-
-        // Safety: `scene_ptr` was create from a `MaybeUninit` of the right type and size.
-        // The underlying type `Scene` has `repr(C)` and can be populated field by field.
-        unsafe {
-            (*scene_ptr).num_people = num_people;
-            (*scene_ptr).num_cars = num_cars;
-            (*scene_ptr).distance_obstacle = distance_obstacle;
-            (*scene_ptr).distance_left_lane = distance_left_lane;
-            (*scene_ptr).distance_right_lane = distance_right_lane;
-        }
+        scene.num_people = num_people;
+        scene.num_cars = num_cars;
+        scene.distance_obstacle = distance_obstacle;
+        scene.distance_left_lane = distance_left_lane;
+        scene.distance_right_lane = distance_right_lane;
     }
 }
 
@@ -230,12 +222,11 @@ impl Activity for NeuralNet {
         let radar = self.input_scan.read();
         let scene = self.output_scene.write_uninit();
 
-        if let (Ok(camera), Ok(radar), Ok(mut scene)) = (camera, radar, scene) {
+        if let (Ok(camera), Ok(radar), Ok(scene)) = (camera, radar, scene) {
             debug!("Inferring scene with neural network");
 
-            Self::infer(camera.deref(), radar.deref(), scene.deref_mut());
-            // Safety: `Scene` has `repr(C)` and was fully initialized by `Self::infer` above.
-            let scene = unsafe { scene.assume_init() };
+            // Fill the scene in place, field by field
+            let scene = scene.write_with(|scene| Self::infer(&camera, &radar, scene));
             debug!("Sending Scene {:?}", scene.deref());
             scene.send().unwrap();
         }
@@ -259,6 +250,8 @@ pub struct EmergencyBraking {
     input_scene: Box<dyn ActivityInput<Scene>>,
     /// Brake instruction output
     output_brake_instruction: Box<dyn ActivityOutput<BrakeInstruction>>,
+    /// Whether the brakes were fully engaged in the last step
+    full_braking: bool,
 }
 
 impl EmergencyBraking {
@@ -271,6 +264,7 @@ impl EmergencyBraking {
             activity_id,
             input_scene: activity_input(scene_topic),
             output_brake_instruction: activity_output(brake_instruction_topic),
+            full_braking: false,
         })
     }
 }
@@ -303,12 +297,20 @@ impl Activity for EmergencyBraking {
                         / (ENGAGE_DISTANCE - MAX_BRAKE_DISTANCE),
                 );
 
+                // Request a black-box recording whenever full braking starts
+                let full_braking = level >= 1.0;
+                if full_braking && !self.full_braking {
+                    request_recording_trigger();
+                }
+                self.full_braking = full_braking;
+
                 let brake_instruction = brake_instruction.write_payload(BrakeInstruction {
                     active: true,
                     level,
                 });
                 brake_instruction.send().unwrap();
             } else {
+                self.full_braking = false;
                 let brake_instruction = brake_instruction.write_payload(BrakeInstruction {
                     active: false,
                     level: 0.0,
@@ -9,6 +9,7 @@
 
 #[cfg(feature = "recording")]
 use feo::{recording::registry::TypeRegistry, register_type, register_types};
+use feo_com::interface::Zeroable;
 #[cfg(feature = "recording")]
 use postcard::experimental::max_size::MaxSize;
 #[cfg(feature = "recording")]
@@ -29,6 +30,9 @@ pub struct CameraImage {
     pub distance_obstacle: f64,
 }
 
+// Safety: all fields are zeroable
+unsafe impl Zeroable for CameraImage {}
+
 /// Radar scan
 ///
 /// With post-processing, we could detect the closest object
@@ -57,6 +61,9 @@ pub struct Scene {
     pub distance_right_lane: f64,
 }
 
+// Safety: all fields are zeroable
+unsafe impl Zeroable for Scene {}
+
 /// Brake instruction
 ///
 /// This is an instruction whether to engage the brakes and at which level.
@@ -82,13 +89,13 @@ pub struct Steering {
 #[cfg(feature = "recording")]
 pub fn type_registry() -> TypeRegistry {
     use core::fmt;
-    use feo_com::interface::ActivityInput;
+    use feo_com::interface::{ActivityInput, ActivityOutput};
 
     #[cfg(feature = "com_iox2")]
-    use feo_com::iox2::Iox2Input;
+    use feo_com::iox2::{Iox2Input, Iox2Output};
 
     #[cfg(feature = "com_linux_shm")]
-    use feo_com::linux_shm::LinuxShmInput;
+    use feo_com::linux_shm::{LinuxShmInput, LinuxShmOutput};
 
     fn activity_input<T>(topic: &str) -> Box<dyn ActivityInput<T>>
     where
@@ -101,14 +108,25 @@ pub fn type_registry() -> TypeRegistry {
         Box::new(LinuxShmInput::new(topic))
     }
 
+    fn activity_output<T>(topic: &str) -> Box<dyn ActivityOutput<T>>
+    where
+        T: fmt::Debug + 'static,
+    {
+        #[cfg(feature = "com_iox2")]
+        return Box::new(Iox2Output::new(topic));
+
+        #[cfg(feature = "com_linux_shm")]
+        Box::new(LinuxShmOutput::new(topic))
+    }
+
     let mut registry = TypeRegistry::default();
     register_types!(
         registry;
-        CameraImage, |topic: &str| activity_input(topic);
-        RadarScan, |topic: &str| activity_input(topic);
-        Scene, |topic: &str| activity_input(topic);
-        BrakeInstruction, |topic: &str| activity_input(topic);
-        Steering, |topic: &str| activity_input(topic)
+        CameraImage, |topic: &str| activity_input(topic), |topic: &str| activity_output(topic);
+        RadarScan, |topic: &str| activity_input(topic), |topic: &str| activity_output(topic);
+        Scene, |topic: &str| activity_input(topic), |topic: &str| activity_output(topic);
+        BrakeInstruction, |topic: &str| activity_input(topic), |topic: &str| activity_output(topic);
+        Steering, |topic: &str| activity_input(topic), |topic: &str| activity_output(topic)
     );
     registry
 }
@@ -2,72 +2,58 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use feo::recording::recorder::{DataDescriptionRecord, Record};
+use feo::recording::decoder::{write_records, OutputFormat};
+use feo::recording::reader::RecordingReader;
 use feo_log::info;
 use mini_adas::activities::messages;
-use serde::Deserialize;
-use std::io::Read;
+use std::io;
 
 fn main() {
     feo_logger::init(feo_log::LevelFilter::Trace, true, false);
 
-    let mut serialized_data = Vec::new();
-    std::fs::File::open("rec.bin")
-        .expect("failed to open recording")
-        // Reading to end for now, just for this simple tool
-        .read_to_end(&mut serialized_data)
-        .expect("failed to read recording");
-
-    info!("Read file with {} bytes", serialized_data.len());
-    let mut remaining_bytes = serialized_data.as_slice();
-    while !remaining_bytes.is_empty() {
-        let (record, remaining) =
-            postcard::take_from_bytes(remaining_bytes).expect("deserializing failed");
-        remaining_bytes = remaining;
+    let params = Params::from_args();
 
-        println!("{record:#?}");
-        if let Record::DataDescription(data_record) = record {
-            if let Some((image, remaining)) =
-                try_deserialization_as_a::<messages::CameraImage>(data_record, remaining_bytes)
-            {
-                remaining_bytes = remaining;
-                println!("{:#?}", image);
-            } else if let Some((radar, remaining)) =
-                try_deserialization_as_a::<messages::RadarScan>(data_record, remaining_bytes)
-            {
-                remaining_bytes = remaining;
-                println!("{:#?}", radar);
-            } else if let Some((scene, remaining)) =
-                try_deserialization_as_a::<messages::Scene>(data_record, remaining_bytes)
-            {
-                remaining_bytes = remaining;
-                println!("{:#?}", scene);
-            } else if let Some((brake, remaining)) =
-                try_deserialization_as_a::<messages::BrakeInstruction>(data_record, remaining_bytes)
-            {
-                remaining_bytes = remaining;
-                println!("{:#?}", brake);
-            } else if let Some((steering, remaining)) =
-                try_deserialization_as_a::<messages::Steering>(data_record, remaining_bytes)
-            {
-                remaining_bytes = remaining;
-                println!("{:#?}", steering);
-            } else {
-                // Skip data record
-                info!("Skipping deserialization of {}", data_record.type_name);
-                remaining_bytes = &remaining_bytes[data_record.data_size..];
-            }
-        }
+    let mut reader = RecordingReader::open(&params.record_file).expect("failed to open recording");
+    // Keep JSON output parseable line by line
+    if params.format == OutputFormat::Debug {
+        println!("{:#?}", reader.header());
+        let index = reader.index().expect("failed to read index");
+        info!("Read recording with {} cycles", index.len());
     }
+
+    let registry = messages::type_registry();
+    write_records(&mut reader, &registry, params.format, io::stdout().lock())
+        .expect("failed to read recording");
 }
 
-fn try_deserialization_as_a<'a, T: Deserialize<'a>>(
-    header: DataDescriptionRecord,
-    bytes: &'a [u8],
-) -> Option<(T, &'a [u8])> {
-    if header.type_name == std::any::type_name::<T>() {
-        Some(postcard::take_from_bytes(bytes).expect("failed to deserialize CameraImage"))
-    } else {
-        None
+/// Parameters of the deserializer
+struct Params {
+    /// Recording file to read
+    record_file: String,
+    /// Output format
+    format: OutputFormat,
+}
+
+impl Params {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+
+        // Optional flag `--json` to write one JSON object per record
+        let format = if args.iter().any(|arg| arg == "--json") {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Debug
+        };
+
+        // Optional argument is the recording file
+        let record_file = args
+            .into_iter()
+            .find(|arg| !arg.starts_with("--"))
+            .unwrap_or_else(|| "rec.bin".into());
+
+        Self {
+            record_file,
+            format,
+        }
     }
 }
@@ -0,0 +1,42 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use feo::recording::diff::{diff_recordings, DiffOptions};
+use feo::recording::reader::RecordingReader;
+use feo_log::LevelFilter;
+use mini_adas::activities::messages;
+use std::process::ExitCode;
+
+/// Compare two recordings, exiting with code 1 if they differ
+fn main() -> ExitCode {
+    feo_logger::init(LevelFilter::Warn, true, false);
+
+    let args: Vec<String> = std::env::args().collect();
+
+    // First and second argument are the recordings to compare
+    let (Some(left_file), Some(right_file)) = (args.get(1), args.get(2)) else {
+        eprintln!("usage: adas_diff <left> <right> [tolerance]");
+        return ExitCode::from(2);
+    };
+
+    // Optional third argument is the maximum absolute difference of numbers considered equal
+    let tolerance = args
+        .get(3)
+        .map(|x| x.parse::<f64>().expect("invalid tolerance"))
+        .unwrap_or_default();
+
+    let mut left = RecordingReader::open(left_file).expect("failed to open left recording");
+    let mut right = RecordingReader::open(right_file).expect("failed to open right recording");
+
+    let registry = messages::type_registry();
+    let report = diff_recordings(&mut left, &mut right, &registry, DiffOptions { tolerance })
+        .expect("failed to compare recordings");
+
+    print!("{report}");
+    if report.is_equal() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
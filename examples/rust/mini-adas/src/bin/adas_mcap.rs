@@ -0,0 +1,32 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use feo::recording::mcap::export_mcap;
+use feo::recording::reader::RecordingReader;
+use feo_log::{info, LevelFilter};
+use mini_adas::activities::messages;
+use std::fs;
+use std::io::BufWriter;
+
+fn main() {
+    feo_logger::init(LevelFilter::Info, true, false);
+
+    let args: Vec<String> = std::env::args().collect();
+
+    // First argument is the recording file, second argument the MCAP file to write
+    let record_file = args.get(1).map(String::as_str).unwrap_or("rec.bin");
+    let mcap_file = args.get(2).map(String::as_str).unwrap_or("rec.mcap");
+
+    let mut reader = RecordingReader::open(record_file).expect("failed to open recording");
+    let out = fs::File::create(mcap_file).expect("failed to create MCAP file");
+
+    let registry = messages::type_registry();
+    let stats = export_mcap(&mut reader, &registry, BufWriter::new(out))
+        .expect("failed to export recording");
+
+    info!(
+        "Exported {} data and {} signal messages to {mcap_file}, skipped {} data records",
+        stats.data, stats.signals, stats.skipped
+    );
+}
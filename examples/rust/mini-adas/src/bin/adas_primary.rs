@@ -27,6 +27,7 @@ fn main() {
     // Initialize topics. Do not drop.
     let _topic_guards = initialize_com_primary(
         COM_BACKEND,
+        &config,
         AGENT_ID,
         topic_dependencies(),
         &agent_assignments_ids(),
@@ -81,18 +82,20 @@ impl Params {
 #[cfg(feature = "signalling_direct_mpsc")]
 mod cfg {
     use super::{Duration, Params, AGENT_ID};
-    use mini_adas::config::{activity_dependencies, agent_assignments};
+    use mini_adas::config::{activity_dependencies, agent_assignments, com_namespace};
 
     pub(super) use feo::agent::direct::primary_mpsc::{Primary, PrimaryConfig};
 
     pub(super) fn make_config(params: Params) -> PrimaryConfig {
         PrimaryConfig {
+            id: AGENT_ID,
             cycle_time: params.feo_cycle_time,
             activity_dependencies: activity_dependencies(),
             // With only one agent, we cannot attach a recorder
             recorder_ids: vec![],
             worker_assignments: agent_assignments().remove(&AGENT_ID).unwrap(),
             timeout: Duration::from_secs(10),
+            namespace: com_namespace(),
         }
     }
 }
@@ -102,7 +105,7 @@ mod cfg {
     use super::{check_ids, Duration, Params, AGENT_ID};
     use feo::agent::NodeAddress;
     use feo::ids::AgentId;
-    use mini_adas::config::{activity_dependencies, agent_assignments, BIND_ADDR};
+    use mini_adas::config::{activity_dependencies, agent_assignments, com_namespace, BIND_ADDR};
     use std::collections::HashSet;
 
     pub(super) use feo::agent::direct::primary::{Primary, PrimaryConfig};
@@ -112,12 +115,14 @@ mod cfg {
         check_ids(&params.recorder_ids, &agent_ids);
 
         PrimaryConfig {
+            id: AGENT_ID,
             cycle_time: params.feo_cycle_time,
             activity_dependencies: activity_dependencies(),
             recorder_ids: params.recorder_ids,
             worker_assignments: agent_assignments().remove(&AGENT_ID).unwrap(),
             timeout: Duration::from_secs(10),
             endpoint: NodeAddress::Tcp(BIND_ADDR),
+            namespace: com_namespace(),
         }
     }
 }
@@ -127,7 +132,9 @@ mod cfg {
     use super::{check_ids, Duration, Params, AGENT_ID};
     use feo::agent::NodeAddress;
     use feo::ids::AgentId;
-    use mini_adas::config::{activity_dependencies, agent_assignments, socket_paths};
+    use mini_adas::config::{
+        activity_dependencies, agent_assignments, com_namespace, socket_paths,
+    };
     use std::collections::HashSet;
 
     pub(super) use feo::agent::direct::primary::{Primary, PrimaryConfig};
@@ -137,12 +144,14 @@ mod cfg {
         check_ids(&params.recorder_ids, &agent_ids);
 
         PrimaryConfig {
+            id: AGENT_ID,
             cycle_time: params.feo_cycle_time,
             activity_dependencies: activity_dependencies(),
             recorder_ids: params.recorder_ids,
             worker_assignments: agent_assignments().remove(&AGENT_ID).unwrap(),
             timeout: Duration::from_secs(10),
             endpoint: NodeAddress::UnixSocket(socket_paths().0),
+            namespace: com_namespace(),
         }
     }
 }
@@ -153,7 +162,8 @@ mod cfg {
     use feo::agent::NodeAddress;
     use feo::ids::{ActivityId, AgentId, WorkerId};
     use mini_adas::config::{
-        activity_dependencies, agent_assignments, worker_agent_map, BIND_ADDR, BIND_ADDR2,
+        activity_dependencies, agent_assignments, com_namespace, worker_agent_map, BIND_ADDR,
+        BIND_ADDR2,
     };
     use std::collections::{HashMap, HashSet};
 
@@ -182,6 +192,7 @@ mod cfg {
             id: AGENT_ID,
             worker_agent_map: worker_agent_map(),
             activity_worker_map,
+            namespace: com_namespace(),
         }
     }
 }
@@ -192,7 +203,7 @@ mod cfg {
     use feo::agent::NodeAddress;
     use feo::ids::{ActivityId, AgentId, WorkerId};
     use mini_adas::config::{
-        activity_dependencies, agent_assignments, socket_paths, worker_agent_map,
+        activity_dependencies, agent_assignments, com_namespace, socket_paths, worker_agent_map,
     };
     use std::collections::{HashMap, HashSet};
 
@@ -221,6 +232,7 @@ mod cfg {
             id: AGENT_ID,
             worker_agent_map: worker_agent_map(),
             activity_worker_map,
+            namespace: com_namespace(),
         }
     }
 }
@@ -3,51 +3,78 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use feo::ids::AgentId;
-use feo::recording::recorder::RecordingRules;
-use feo_log::{debug, LevelFilter};
-use mini_adas::activities::messages::{
-    self, BrakeInstruction, CameraImage, RadarScan, Scene, Steering,
-};
+use feo::recording::blackbox::{BlackBoxConfig, Window};
+use feo::recording::decoupled::DecoupledConfig;
+use feo::recording::format::Compression;
+use feo::recording::recorder::{RecordingRule, RecordingRules};
+use feo::recording::rotation::Rotation;
+use feo_log::{debug, info, LevelFilter};
+use mini_adas::activities::messages;
 
 use feo::agent::com_init::initialize_com_recorder;
 use feo::topicspec::TopicSpecification;
-use mini_adas::config::{
-    topic_dependencies, COM_BACKEND, TOPIC_CAMERA_FRONT, TOPIC_CONTROL_BRAKES,
-    TOPIC_CONTROL_STEERING, TOPIC_INFERRED_SCENE, TOPIC_RADAR_FRONT,
-};
-use std::collections::HashMap;
+use mini_adas::config::{topic_dependencies, COM_BACKEND, TOPIC_CAMERA_FRONT};
+use std::io::BufRead as _;
+use std::thread;
+
+/// Number of cycles recorded after a black-box trigger
+const POST_TRIGGER_CYCLES: u64 = 10;
 
 fn main() {
     feo_logger::init(LevelFilter::Trace, true, true);
+    feo_tracing::init(feo_tracing::LevelFilter::TRACE);
 
     let params = Params::from_args();
 
     let registry = &messages::type_registry();
-    let rules: RecordingRules = HashMap::from([
-        (TOPIC_CAMERA_FRONT, core::any::type_name::<CameraImage>()),
-        (
-            TOPIC_CONTROL_BRAKES,
-            core::any::type_name::<BrakeInstruction>(),
-        ),
-        (TOPIC_CONTROL_STEERING, core::any::type_name::<Steering>()),
-        (TOPIC_INFERRED_SCENE, core::any::type_name::<Scene>()),
-        (TOPIC_RADAR_FRONT, core::any::type_name::<RadarScan>()),
-    ]);
-
-    let config = cfg::make_config(params.agent_id, rules.clone(), registry);
+    // Record camera images compressed and only in every 5th cycle, all other vehicle topics
+    // on every change, taking the types of the topics from the topic specifications
+    let mut rules = RecordingRules::new();
+    rules
+        .add(
+            TOPIC_CAMERA_FRONT,
+            RecordingRule::default()
+                .every(5)
+                .compressed(Compression::Deflate(6)),
+        )
+        .add(
+            "feo/com/vehicle/*",
+            RecordingRule::default().only_on_change(),
+        );
+    let rules = rules.expand(&topic_dependencies());
+
+    let config = cfg::make_config(
+        params.agent_id,
+        params.rotation,
+        params.black_box,
+        params.decoupled,
+        rules.clone(),
+        registry,
+    );
 
     // initialize reading based on topic specs corresponding to recording rules
     let topic_specs: Vec<TopicSpecification> = topic_dependencies()
         .into_iter()
-        .filter(|s| rules.contains_key(s.topic))
+        .filter(|s| rules.contains(s.topic))
         .collect();
 
     // Initialize topics. Do not drop.
-    let _topic_guards = initialize_com_recorder(COM_BACKEND, topic_specs);
+    let _topic_guards = initialize_com_recorder(COM_BACKEND, &config, topic_specs);
 
     debug!("Creating recorder with agent id {}", params.agent_id);
     let mut recorder = cfg::Recorder::new(config);
 
+    // In black-box mode, trigger writing the black box on every line read from stdin
+    if params.black_box.is_some() {
+        info!("Press enter to write the black box");
+        let trigger = recorder.trigger_handle();
+        thread::spawn(move || {
+            for _ in std::io::stdin().lock().lines() {
+                trigger.trigger();
+            }
+        });
+    }
+
     debug!("Starting to record");
     recorder.run()
 }
@@ -56,6 +83,12 @@ fn main() {
 struct Params {
     /// Agent id of the recorder
     agent_id: AgentId,
+    /// Rotation of the recording file
+    rotation: Rotation,
+    /// Black-box configuration
+    black_box: Option<BlackBoxConfig>,
+    /// Decoupled mode configuration
+    decoupled: Option<DecoupledConfig>,
 }
 
 impl Params {
@@ -69,7 +102,56 @@ impl Params {
             .map(AgentId::new)
             .expect("missing or invalid agent id");
 
-        Self { agent_id }
+        // Optional second and third argument `--black-box <cycles>` switch to black-box mode,
+        // keeping the given number of cycles in memory
+        if args.get(2).is_some_and(|x| x == "--black-box") {
+            let cycles = args
+                .get(3)
+                .and_then(|x| x.parse::<usize>().ok())
+                .expect("missing or invalid number of black-box cycles");
+            let black_box = BlackBoxConfig {
+                window: Window::Cycles(cycles),
+                post_trigger_cycles: POST_TRIGGER_CYCLES,
+            };
+            return Self {
+                agent_id,
+                rotation: Rotation::default(),
+                black_box: Some(black_box),
+                decoupled: None,
+            };
+        }
+
+        // Optional arguments `--decoupled <cycles>` switch to decoupled mode,
+        // queueing up to the given number of cycles for writing
+        let (decoupled, args) = match args.get(2) {
+            Some(x) if x == "--decoupled" => {
+                let queue_capacity = args
+                    .get(3)
+                    .and_then(|x| x.parse::<usize>().ok())
+                    .expect("missing or invalid number of queued cycles");
+                (Some(DecoupledConfig { queue_capacity }), &args[4..])
+            }
+            _ => (None, &args[2..]),
+        };
+
+        // Next optional argument is the number of cycles per split file
+        let max_cycles = args.first().and_then(|x| x.parse::<u64>().ok());
+
+        // Next optional argument is the number of finished split files to keep
+        let max_files = args.get(1).and_then(|x| x.parse::<usize>().ok());
+
+        let rotation = Rotation {
+            max_cycles,
+            max_files,
+            ..Default::default()
+        };
+
+        Self {
+            agent_id,
+            rotation,
+            black_box: None,
+            decoupled,
+        }
     }
 }
 
@@ -77,25 +159,36 @@ impl Params {
 mod cfg {
     use feo::agent::NodeAddress;
     use feo::ids::AgentId;
+    use feo::recording::blackbox::BlackBoxConfig;
+    use feo::recording::decoupled::DecoupledConfig;
     use feo::recording::recorder::RecordingRules;
     use feo::recording::registry::TypeRegistry;
+    use feo::recording::rotation::Rotation;
     use feo_time::Duration;
-    use mini_adas::config::BIND_ADDR;
+    use mini_adas::config::{com_namespace, BIND_ADDR};
 
     pub(super) use feo::agent::direct::recorder::{Recorder, RecorderConfig};
 
     pub(super) fn make_config(
         agent_id: AgentId,
+        rotation: Rotation,
+        black_box: Option<BlackBoxConfig>,
+        decoupled: Option<DecoupledConfig>,
         rules: RecordingRules,
         registry: &TypeRegistry,
-    ) -> RecorderConfig {
+    ) -> RecorderConfig<'_> {
         RecorderConfig {
             id: agent_id,
+            app_name: "mini-adas",
             record_file: "./rec.bin",
+            rotation,
+            black_box,
+            decoupled,
             rules,
             registry,
             receive_timeout: Duration::from_secs(10),
             endpoint: NodeAddress::Tcp(BIND_ADDR),
+            namespace: com_namespace(),
         }
     }
 }
@@ -104,25 +197,36 @@ mod cfg {
 mod cfg {
     use feo::agent::NodeAddress;
     use feo::ids::AgentId;
+    use feo::recording::blackbox::BlackBoxConfig;
+    use feo::recording::decoupled::DecoupledConfig;
     use feo::recording::recorder::RecordingRules;
     use feo::recording::registry::TypeRegistry;
+    use feo::recording::rotation::Rotation;
     use feo_time::Duration;
-    use mini_adas::config::socket_paths;
+    use mini_adas::config::{com_namespace, socket_paths};
 
     pub(super) use feo::agent::direct::recorder::{Recorder, RecorderConfig};
 
     pub(super) fn make_config(
         agent_id: AgentId,
+        rotation: Rotation,
+        black_box: Option<BlackBoxConfig>,
+        decoupled: Option<DecoupledConfig>,
         rules: RecordingRules,
         registry: &TypeRegistry,
-    ) -> RecorderConfig {
+    ) -> RecorderConfig<'_> {
         RecorderConfig {
             id: agent_id,
+            app_name: "mini-adas",
             record_file: "./rec.bin",
+            rotation,
+            black_box,
+            decoupled,
             rules,
             registry,
             receive_timeout: Duration::from_secs(10),
             endpoint: NodeAddress::UnixSocket(socket_paths().0),
+            namespace: com_namespace(),
         }
     }
 }
@@ -131,26 +235,37 @@ mod cfg {
 mod cfg {
     use feo::agent::NodeAddress;
     use feo::ids::AgentId;
+    use feo::recording::blackbox::BlackBoxConfig;
+    use feo::recording::decoupled::DecoupledConfig;
     use feo::recording::recorder::RecordingRules;
     use feo::recording::registry::TypeRegistry;
+    use feo::recording::rotation::Rotation;
     use feo_time::Duration;
-    use mini_adas::config::{BIND_ADDR, BIND_ADDR2};
+    use mini_adas::config::{com_namespace, BIND_ADDR, BIND_ADDR2};
 
     pub(super) use feo::agent::relayed::recorder::{Recorder, RecorderConfig};
 
     pub(super) fn make_config(
         agent_id: AgentId,
+        rotation: Rotation,
+        black_box: Option<BlackBoxConfig>,
+        decoupled: Option<DecoupledConfig>,
         rules: RecordingRules,
         registry: &TypeRegistry,
-    ) -> RecorderConfig {
+    ) -> RecorderConfig<'_> {
         RecorderConfig {
             id: agent_id,
+            app_name: "mini-adas",
             record_file: "./rec.bin",
+            rotation,
+            black_box,
+            decoupled,
             rules,
             registry,
             receive_timeout: Duration::from_secs(10),
             bind_address_senders: NodeAddress::Tcp(BIND_ADDR),
             bind_address_receivers: NodeAddress::Tcp(BIND_ADDR2),
+            namespace: com_namespace(),
         }
     }
 }
@@ -159,26 +274,37 @@ mod cfg {
 mod cfg {
     use feo::agent::NodeAddress;
     use feo::ids::AgentId;
+    use feo::recording::blackbox::BlackBoxConfig;
+    use feo::recording::decoupled::DecoupledConfig;
     use feo::recording::recorder::RecordingRules;
     use feo::recording::registry::TypeRegistry;
+    use feo::recording::rotation::Rotation;
     use feo_time::Duration;
-    use mini_adas::config::socket_paths;
+    use mini_adas::config::{com_namespace, socket_paths};
 
     pub(super) use feo::agent::relayed::recorder::{Recorder, RecorderConfig};
 
     pub(super) fn make_config(
         agent_id: AgentId,
+        rotation: Rotation,
+        black_box: Option<BlackBoxConfig>,
+        decoupled: Option<DecoupledConfig>,
         rules: RecordingRules,
         registry: &TypeRegistry,
-    ) -> RecorderConfig {
+    ) -> RecorderConfig<'_> {
         RecorderConfig {
             id: agent_id,
+            app_name: "mini-adas",
             record_file: "./rec.bin",
+            rotation,
+            black_box,
+            decoupled,
             rules,
             registry,
             receive_timeout: Duration::from_secs(10),
             bind_address_senders: NodeAddress::UnixSocket(socket_paths().0),
             bind_address_receivers: NodeAddress::UnixSocket(socket_paths().1),
+            namespace: com_namespace(),
         }
     }
 }
@@ -0,0 +1,282 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use feo::activity::ActivityIdAndBuilder;
+use feo::agent::com_init::initialize_com_primary;
+use feo::ids::{ActivityId, AgentId, WorkerId};
+use feo::topicspec::Direction;
+use feo_log::{info, LevelFilter};
+use mini_adas::activities::messages;
+use mini_adas::config::{
+    agent_assignments, agent_assignments_ids, topic_dependencies, COM_BACKEND,
+    MAX_ADDITIONAL_SUBSCRIBERS,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// The replay agent takes the place of the primary agent
+const AGENT_ID: AgentId = AgentId::new(100);
+
+fn main() {
+    feo_logger::init(LevelFilter::Debug, true, true);
+
+    let params = Params::from_args();
+    info!(
+        "Replaying {} with activities {:?}",
+        params.record_file.display(),
+        params.activities
+    );
+
+    // Publish recorded data on all topics not written by a replayed activity
+    let topics: Vec<&'static str> = topic_dependencies()
+        .iter()
+        .filter(|spec| {
+            !spec
+                .peers
+                .iter()
+                .any(|(id, dir)| *dir == Direction::Outgoing && params.activities.contains(id))
+        })
+        .map(|spec| spec.topic)
+        .collect();
+
+    // Run all replayed activities in this agent, keeping their worker assignment
+    let worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)> = agent_assignments()
+        .into_values()
+        .flatten()
+        .map(|(worker_id, activities)| {
+            let activities: Vec<_> = activities
+                .into_iter()
+                .filter(|(id, _)| params.activities.contains(id))
+                .collect();
+            (worker_id, activities)
+        })
+        .filter(|(_, activities)| !activities.is_empty())
+        .collect();
+
+    let registry = &messages::type_registry();
+    let config = cfg::make_config(params, topics, worker_assignments, registry);
+
+    // Initialize all topics as local to this agent, so that recorded data can be written to them.
+    // Do not drop.
+    let com_assignments = HashMap::from([(
+        AGENT_ID,
+        agent_assignments_ids().into_values().flatten().collect(),
+    )]);
+    let _topic_guards = initialize_com_primary(
+        COM_BACKEND,
+        &config,
+        AGENT_ID,
+        topic_dependencies(),
+        &com_assignments,
+        MAX_ADDITIONAL_SUBSCRIBERS,
+    );
+
+    // Setup replay
+    let mut replay = cfg::Replay::new(config).expect("failed to open recording");
+
+    // Run replay until the end of the recording
+    replay.run().unwrap()
+}
+
+/// Parameters of the replay
+struct Params {
+    /// Recording file to replay
+    record_file: PathBuf,
+    /// Activities to re-run
+    activities: HashSet<ActivityId>,
+    /// Recorder IDs
+    recorder_ids: Vec<AgentId>,
+}
+
+impl Params {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+
+        // First argument is the recording file
+        let record_file = args
+            .get(1)
+            .map(PathBuf::from)
+            .expect("missing recording file");
+
+        // Second argument are the activity IDs to re-run as dot-separated list, e.g. 4.6
+        let activities = args
+            .get(2)
+            .and_then(|s| {
+                s.split('.')
+                    .map(|id| id.parse::<u64>().map(ActivityId::from))
+                    .collect::<Result<_, _>>()
+                    .ok()
+            })
+            .expect("missing or invalid activity ids");
+
+        // Third argument are the recorder IDs to wait for as dot-separated list, e.g. 900 or 900.901
+        let recorder_ids = args
+            .get(3)
+            .and_then(|s| {
+                s.split('.')
+                    .map(|id| id.parse::<u64>().map(AgentId::from))
+                    .collect::<Result<_, _>>()
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        Self {
+            record_file,
+            activities,
+            recorder_ids,
+        }
+    }
+}
+
+#[cfg(feature = "signalling_direct_tcp")]
+mod cfg {
+    use super::Params;
+    use feo::activity::ActivityIdAndBuilder;
+    use feo::agent::NodeAddress;
+    use feo::ids::WorkerId;
+    use feo::recording::registry::TypeRegistry;
+    use feo_time::Duration;
+    use mini_adas::config::{com_namespace, BIND_ADDR};
+
+    pub(super) use feo::agent::direct::replay::{Replay, ReplayConfig};
+
+    pub(super) fn make_config<'r>(
+        params: Params,
+        topics: Vec<&'static str>,
+        worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
+        registry: &'r TypeRegistry,
+    ) -> ReplayConfig<'r> {
+        ReplayConfig {
+            record_file: params.record_file,
+            topics,
+            registry,
+            activities: params.activities.into_iter().collect(),
+            recorder_ids: params.recorder_ids,
+            worker_assignments,
+            timeout: Duration::from_secs(10),
+            endpoint: NodeAddress::Tcp(BIND_ADDR),
+            namespace: com_namespace(),
+        }
+    }
+}
+
+#[cfg(feature = "signalling_direct_unix")]
+mod cfg {
+    use super::Params;
+    use feo::activity::ActivityIdAndBuilder;
+    use feo::agent::NodeAddress;
+    use feo::ids::WorkerId;
+    use feo::recording::registry::TypeRegistry;
+    use feo_time::Duration;
+    use mini_adas::config::{com_namespace, socket_paths};
+
+    pub(super) use feo::agent::direct::replay::{Replay, ReplayConfig};
+
+    pub(super) fn make_config<'r>(
+        params: Params,
+        topics: Vec<&'static str>,
+        worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
+        registry: &'r TypeRegistry,
+    ) -> ReplayConfig<'r> {
+        ReplayConfig {
+            record_file: params.record_file,
+            topics,
+            registry,
+            activities: params.activities.into_iter().collect(),
+            recorder_ids: params.recorder_ids,
+            worker_assignments,
+            timeout: Duration::from_secs(10),
+            endpoint: NodeAddress::UnixSocket(socket_paths().0),
+            namespace: com_namespace(),
+        }
+    }
+}
+
+#[cfg(feature = "signalling_relayed_tcp")]
+mod cfg {
+    use super::{Params, AGENT_ID};
+    use feo::activity::ActivityIdAndBuilder;
+    use feo::agent::NodeAddress;
+    use feo::ids::WorkerId;
+    use feo::recording::registry::TypeRegistry;
+    use feo_time::Duration;
+    use mini_adas::config::{com_namespace, BIND_ADDR, BIND_ADDR2};
+
+    pub(super) use feo::agent::relayed::replay::{Replay, ReplayConfig};
+
+    pub(super) fn make_config<'r>(
+        params: Params,
+        topics: Vec<&'static str>,
+        worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
+        registry: &'r TypeRegistry,
+    ) -> ReplayConfig<'r> {
+        let worker_agent_map = worker_assignments
+            .iter()
+            .map(|(wid, _)| (*wid, AGENT_ID))
+            .collect();
+        let activity_worker_map = worker_assignments
+            .iter()
+            .flat_map(|(wid, activities)| activities.iter().map(move |(aid, _)| (*aid, *wid)))
+            .collect();
+
+        ReplayConfig {
+            id: AGENT_ID,
+            record_file: params.record_file,
+            topics,
+            registry,
+            recorder_ids: params.recorder_ids,
+            worker_assignments,
+            timeout: Duration::from_secs(10),
+            bind_address_senders: NodeAddress::Tcp(BIND_ADDR),
+            bind_address_receivers: NodeAddress::Tcp(BIND_ADDR2),
+            worker_agent_map,
+            activity_worker_map,
+            namespace: com_namespace(),
+        }
+    }
+}
+
+#[cfg(feature = "signalling_relayed_unix")]
+mod cfg {
+    use super::{Params, AGENT_ID};
+    use feo::activity::ActivityIdAndBuilder;
+    use feo::agent::NodeAddress;
+    use feo::ids::WorkerId;
+    use feo::recording::registry::TypeRegistry;
+    use feo_time::Duration;
+    use mini_adas::config::{com_namespace, socket_paths};
+
+    pub(super) use feo::agent::relayed::replay::{Replay, ReplayConfig};
+
+    pub(super) fn make_config<'r>(
+        params: Params,
+        topics: Vec<&'static str>,
+        worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
+        registry: &'r TypeRegistry,
+    ) -> ReplayConfig<'r> {
+        let worker_agent_map = worker_assignments
+            .iter()
+            .map(|(wid, _)| (*wid, AGENT_ID))
+            .collect();
+        let activity_worker_map = worker_assignments
+            .iter()
+            .flat_map(|(wid, activities)| activities.iter().map(move |(aid, _)| (*aid, *wid)))
+            .collect();
+
+        ReplayConfig {
+            id: AGENT_ID,
+            record_file: params.record_file,
+            topics,
+            registry,
+            recorder_ids: params.recorder_ids,
+            worker_assignments,
+            timeout: Duration::from_secs(10),
+            bind_address_senders: NodeAddress::UnixSocket(socket_paths().0),
+            bind_address_receivers: NodeAddress::UnixSocket(socket_paths().1),
+            worker_agent_map,
+            activity_worker_map,
+            namespace: com_namespace(),
+        }
+    }
+}
@@ -15,7 +15,7 @@ fn main() {
     #[cfg(feature = "signalling_direct_tcp")]
     use mini_adas::config::BIND_ADDR;
     use mini_adas::config::{agent_assignments, topic_dependencies};
-    use mini_adas::config::{agent_assignments_ids, COM_BACKEND};
+    use mini_adas::config::{agent_assignments_ids, com_namespace, COM_BACKEND};
     use params::Params;
     use std::collections::HashSet;
 
@@ -33,6 +33,7 @@ fn main() {
         endpoint: NodeAddress::Tcp(BIND_ADDR),
         #[cfg(feature = "signalling_direct_unix")]
         endpoint: NodeAddress::UnixSocket(socket_paths().0),
+        namespace: com_namespace(),
     };
 
     // determine set of activity ids belonging to this agent
@@ -45,8 +46,12 @@ fn main() {
         .collect();
 
     // Initialize topics. Do not drop.
-    let _topic_guards =
-        initialize_com_secondary(COM_BACKEND, topic_dependencies(), &local_activities);
+    let _topic_guards = initialize_com_secondary(
+        COM_BACKEND,
+        &config,
+        topic_dependencies(),
+        &local_activities,
+    );
 
     let secondary = Secondary::new(config);
     secondary.run();
@@ -61,7 +66,7 @@ fn main() {
     use feo::ids::ActivityId;
     use feo_log::{info, LevelFilter};
     use mini_adas::config::{agent_assignments, topic_dependencies};
-    use mini_adas::config::{agent_assignments_ids, COM_BACKEND};
+    use mini_adas::config::{agent_assignments_ids, com_namespace, COM_BACKEND};
     use mini_adas::config::{BIND_ADDR, BIND_ADDR2};
     use params::Params;
     use std::collections::HashSet;
@@ -78,6 +83,7 @@ fn main() {
         timeout: Duration::from_secs(10),
         bind_address_senders: NodeAddress::Tcp(BIND_ADDR),
         bind_address_receivers: NodeAddress::Tcp(BIND_ADDR2),
+        namespace: com_namespace(),
     };
 
     // determine set of activity ids belonging to this agent
@@ -90,8 +96,12 @@ fn main() {
         .collect();
 
     // Initialize topics. Do not drop.
-    let _topic_guards =
-        initialize_com_secondary(COM_BACKEND, topic_dependencies(), &local_activities);
+    let _topic_guards = initialize_com_secondary(
+        COM_BACKEND,
+        &config,
+        topic_dependencies(),
+        &local_activities,
+    );
 
     let secondary = Secondary::new(config);
     secondary.run();
@@ -107,7 +117,7 @@ fn main() {
     use feo_log::{info, LevelFilter};
     use mini_adas::config::socket_paths;
     use mini_adas::config::{agent_assignments, topic_dependencies};
-    use mini_adas::config::{agent_assignments_ids, COM_BACKEND};
+    use mini_adas::config::{agent_assignments_ids, com_namespace, COM_BACKEND};
     use params::Params;
     use std::collections::HashSet;
 
@@ -123,6 +133,7 @@ fn main() {
         timeout: Duration::from_secs(10),
         bind_address_senders: NodeAddress::UnixSocket(socket_paths().0),
         bind_address_receivers: NodeAddress::UnixSocket(socket_paths().1),
+        namespace: com_namespace(),
     };
 
     // determine set of activity ids belonging to this agent
@@ -135,8 +146,12 @@ fn main() {
         .collect();
 
     // Initialize topics. Do not drop.
-    let _topic_guards =
-        initialize_com_secondary(COM_BACKEND, topic_dependencies(), &local_activities);
+    let _topic_guards = initialize_com_secondary(
+        COM_BACKEND,
+        &config,
+        topic_dependencies(),
+        &local_activities,
+    );
 
     let secondary = Secondary::new(config);
     secondary.run();
@@ -0,0 +1,90 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Introspection of the com layer topics of a running mini-adas deployment
+//! and cleanup of stale com resources
+
+use feo::recording::inspector::{dump_topic, format_topics, DumpFormat};
+use feo_com::interface::{cleanup_backend, configure_namespace};
+use feo_com::introspection::list_topics;
+use feo_log::LevelFilter;
+use feo_time::Duration;
+use mini_adas::activities::messages;
+use mini_adas::config::{com_namespace, COM_BACKEND};
+use std::process::ExitCode;
+
+/// Time to wait for a sample when dumping a topic
+const DUMP_TIMEOUT: Duration = Duration::from_secs(2);
+
+fn main() -> ExitCode {
+    feo_logger::init(LevelFilter::Warn, true, true);
+
+    let Some(command) = Command::from_args() else {
+        eprintln!("usage: adas_topics list | dump <topic> [--hex] | cleanup");
+        return ExitCode::FAILURE;
+    };
+    configure_namespace(&com_namespace());
+
+    match command {
+        Command::List => {
+            print!("{}", format_topics(&list_topics(COM_BACKEND)));
+            ExitCode::SUCCESS
+        }
+        Command::Dump { topic, format } => {
+            let registry = messages::type_registry();
+            match dump_topic(COM_BACKEND, &registry, &topic, format, DUMP_TIMEOUT) {
+                Ok(dump) => {
+                    println!("{}", dump.trim_end());
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("failed to dump topic: {e}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Cleanup => match cleanup_backend(COM_BACKEND) {
+            Ok(removed) => {
+                println!("removed {removed} stale resources");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("cleanup failed: {e}");
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+/// Subcommands of the tool
+enum Command {
+    /// List all topics with their metadata
+    List,
+    /// Dump the current sample of a topic
+    Dump { topic: String, format: DumpFormat },
+    /// Remove stale com resources left behind by crashed processes
+    Cleanup,
+}
+
+impl Command {
+    fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+
+        // First argument is the subcommand, followed by its arguments
+        match args.get(1)?.as_str() {
+            "list" => Some(Command::List),
+            "dump" => {
+                let topic = args.get(2)?.clone();
+                let format = match args.get(3).map(String::as_str) {
+                    None => DumpFormat::Debug,
+                    Some("--hex") => DumpFormat::Hex,
+                    Some(_) => return None,
+                };
+                Some(Command::Dump { topic, format })
+            }
+            "cleanup" => Some(Command::Cleanup),
+            _ => None,
+        }
+    }
+}
@@ -12,7 +12,7 @@ use core::net::{IpAddr, Ipv4Addr, SocketAddr};
 use feo::activity::{ActivityBuilder, ActivityIdAndBuilder};
 use feo::ids::{ActivityId, AgentId, WorkerId};
 use feo::topicspec::{Direction, TopicSpecification};
-use feo_com::interface::ComBackend;
+use feo_com::interface::{ComBackend, Namespace};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -26,6 +26,11 @@ pub const COM_BACKEND: ComBackend = ComBackend::Iox2;
 #[cfg(feature = "com_linux_shm")]
 pub const COM_BACKEND: ComBackend = ComBackend::LinuxShm;
 
+/// Com namespace isolating this application from other FEO applications on the same host
+pub fn com_namespace() -> Namespace {
+    Namespace::new("mini-adas").expect("invalid com namespace")
+}
+
 pub const BIND_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8081);
 pub const BIND_ADDR2: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8082);
 
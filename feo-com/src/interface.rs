@@ -25,14 +25,98 @@ use crate::linux_shm;
 use crate::linux_shm::shared_memory::{MappingMode, TopicInitializationAgentRole};
 #[cfg(feature = "ipc_linux_shm")]
 use crate::linux_shm::{LinuxShmInputGuard, LinuxShmOutputGuard, LinuxShmOutputUninitGuard};
+use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
+use alloc::string::String;
 use core::any::Any;
 use core::fmt;
 use core::mem::MaybeUninit;
 use core::ops::{Deref, DerefMut};
+use std::sync::OnceLock;
 
 pub type Topic<'a> = &'a str;
 
+/// Namespace of the com resources, unless configured otherwise
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Namespace of the com resources of this application instance
+static NAMESPACE: OnceLock<Namespace> = OnceLock::new();
+
+/// Namespace of the com resources of an application instance
+///
+/// Independent application instances on the same host must use different namespaces,
+/// all processes of one instance the same. The namespace applies to
+/// shared memory segments, runtime sockets and iceoryx2 resources.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Namespace(String);
+
+impl Namespace {
+    /// Create a namespace, which may only consist of ASCII alphanumeric characters and dashes
+    pub fn new(name: &str) -> Result<Self, InvalidNamespace> {
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if valid {
+            Ok(Self(name.to_owned()))
+        } else {
+            Err(InvalidNamespace(name.to_owned()))
+        }
+    }
+
+    /// Name of the namespace
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for Namespace {
+    fn default() -> Self {
+        Self(DEFAULT_NAMESPACE.to_owned())
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Error returned for names not allowed as [Namespace]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidNamespace(String);
+
+impl fmt::Display for InvalidNamespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid namespace '{}': only ASCII alphanumeric characters and '-' are allowed",
+            self.0
+        )
+    }
+}
+
+impl core::error::Error for InvalidNamespace {}
+
+/// Configure the namespace of the com resources of this application instance
+///
+/// The namespace must be configured before any topic is initialized.
+///
+/// # Panics
+///
+/// Panics if a different namespace has already been configured or used.
+pub fn configure_namespace(namespace: &Namespace) {
+    let configured = NAMESPACE.get_or_init(|| namespace.clone());
+    assert_eq!(
+        configured, namespace,
+        "com namespace already configured or used"
+    );
+}
+
+/// Namespace of the com resources of this application instance
+///
+/// Falls back to [DEFAULT_NAMESPACE] if not configured before.
+pub fn namespace() -> &'static str {
+    NAMESPACE.get_or_init(Namespace::default).as_str()
+}
+
 // COM backend runtime switch.
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub enum ComBackend {
@@ -49,6 +133,31 @@ pub enum Error {
     SendFailed,
 }
 
+/// Error type of [cleanup_backend]
+#[derive(Debug)]
+pub enum CleanupError {
+    /// Cleaning up resources of the given number of dead iceoryx2 nodes failed
+    #[cfg(feature = "ipc_iceoryx2")]
+    Iox2 { failed: usize },
+    #[cfg(feature = "ipc_linux_shm")]
+    LinuxShm(linux_shm::ownership::Error),
+}
+
+impl fmt::Display for CleanupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "ipc_iceoryx2")]
+            CleanupError::Iox2 { failed } => {
+                write!(f, "failed to clean up {failed} dead iceoryx2 nodes")
+            }
+            #[cfg(feature = "ipc_linux_shm")]
+            CleanupError::LinuxShm(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl core::error::Error for CleanupError {}
+
 /// A trait for structs which can provide handles to input buffers
 pub trait ActivityInput<T>: fmt::Debug
 where
@@ -73,6 +182,25 @@ where
     LinuxShm(LinuxShmInputGuard<T>),
 }
 
+impl<T> InputGuard<T>
+where
+    T: fmt::Debug,
+{
+    /// Sequence number of the sample, incremented on every publication on the topic
+    ///
+    /// Allows to detect whether a sample has changed since a previous read.
+    /// Returns `None` if the backend does not provide sequence numbers.
+    /// This is the case for iceoryx2, where every sample is only received once.
+    pub fn sequence(&self) -> Option<u64> {
+        match self {
+            #[cfg(feature = "ipc_iceoryx2")]
+            Self::Iox2(_) => None,
+            #[cfg(feature = "ipc_linux_shm")]
+            Self::LinuxShm(guard) => guard.sequence(),
+        }
+    }
+}
+
 impl<T> Deref for InputGuard<T>
 where
     T: fmt::Debug,
@@ -96,8 +224,43 @@ where
 {
     /// Get a handle to an uninitialized output buffer
     fn write_uninit(&mut self) -> Result<OutputUninitGuard<T>, Error>;
+
+    /// Get a handle to an output buffer initialized in place with zeros
+    fn write_zeroed(&mut self) -> Result<OutputGuard<T>, Error>
+    where
+        T: Zeroable,
+    {
+        self.write_uninit().map(OutputUninitGuard::zeroed)
+    }
 }
 
+/// Marker trait for types for which a value consisting of zero bytes only is valid
+///
+/// This allows to initialize large output buffers in place, without copying the value
+/// from the stack and without `unsafe` code in activities,
+/// see [OutputUninitGuard::zeroed] and [OutputUninitGuard::write_with].
+///
+/// # Safety
+///
+/// The all-zero bit pattern must be a valid value of the implementing type.
+/// For structs, this is the case if all fields implement [Zeroable].
+/// References, non-null pointers and most enums are not zeroable.
+pub unsafe trait Zeroable {}
+
+macro_rules! impl_zeroable {
+    ($($type:ty),+) => {
+        $(
+            // Safety: zero is a valid value of all primitive numeric types and bool
+            unsafe impl Zeroable for $type {}
+        )+
+    };
+}
+
+impl_zeroable!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool);
+
+// Safety: an array of zeroable elements consisting of zero bytes only consists of valid elements
+unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+
 /// A trait for structs which can provide handles to default-initialized output buffers
 pub trait ActivityOutputDefault<T>: fmt::Debug
 where
@@ -188,11 +351,13 @@ where
 ///
 /// For the buffer to be sendable, it has to be written/initialized
 /// to be turned into the initialized variant of the handle.
-/// There are three ways to achieve this:
+/// There are four ways to achieve this:
 /// - Writing a complete valid payload into the uninitialized buffer,
 ///   using [Self::write_payload]. This might be unefficient
 ///   for large `T` because the type has to exist somewhere in memory before.
 /// - Calling [Self::init] for types which have a [Default] implementation.
+/// - Calling [Self::zeroed] or [Self::write_with] for types implementing [Zeroable].
+///   This initializes the buffer in place, also for large `T`.
 /// - Writing directly to the uninitialized memory and call [Self::assume_init].
 ///   This is `unsafe` and the caller has to ensure that the buffer is initialized
 ///   to a valid value before calling [Self::assume_init].
@@ -233,6 +398,33 @@ where
     }
 }
 
+impl<T> OutputUninitGuard<T>
+where
+    T: fmt::Debug + Zeroable,
+{
+    /// Initialize the uninitialized buffer in place with zeros
+    pub fn zeroed(mut self) -> OutputGuard<T> {
+        let buffer: &mut MaybeUninit<T> = &mut self;
+        // Safety: the buffer has the size of exactly one value of `T`
+        unsafe { buffer.as_mut_ptr().write_bytes(0, 1) };
+        // Safety: the buffer consists of zero bytes only, which is a valid `T: Zeroable`
+        unsafe { self.assume_init() }
+    }
+
+    /// Initialize the uninitialized buffer in place with zeros and then with the given closure
+    ///
+    /// This allows to fill large types field by field, without creating them on the stack first.
+    pub fn write_with(mut self, init: impl FnOnce(&mut T)) -> OutputGuard<T> {
+        let buffer: &mut MaybeUninit<T> = &mut self;
+        // Safety: the buffer has the size of exactly one value of `T`
+        unsafe { buffer.as_mut_ptr().write_bytes(0, 1) };
+        // Safety: the buffer consists of zero bytes only, which is a valid `T: Zeroable`
+        init(unsafe { buffer.assume_init_mut() });
+        // Safety: the buffer was initialized above
+        unsafe { self.assume_init() }
+    }
+}
+
 impl<T> OutputUninitGuard<T>
 where
     T: fmt::Debug + Default,
@@ -376,6 +568,25 @@ pub fn init_topic_secondary<T: fmt::Debug + Default + 'static>(
     }
 }
 
+/// Initialize the given topic for read-only observation, e.g. by introspection tools
+///
+/// In contrast to [init_topic_secondary], this does not require a running primary agent
+/// and does not show up in the topic's reader count.
+/// The topic must already exist on this host.
+pub fn init_topic_observer<T: fmt::Debug + 'static>(
+    topic: Topic,
+    backend: ComBackend,
+) -> TopicHandle {
+    match backend {
+        // For iox2: do nothing and return dummy handle
+        #[cfg(feature = "ipc_iceoryx2")]
+        ComBackend::Iox2 => TopicHandle::from(Box::new(0u8)),
+
+        #[cfg(feature = "ipc_linux_shm")]
+        ComBackend::LinuxShm => linux_shm::init_topic_observer::<T>(topic),
+    }
+}
+
 #[must_use = "keep me alive until activities are created"]
 /// Opaque handle of a topic.
 ///
@@ -390,8 +601,25 @@ impl<T: 'static> From<Box<T>> for TopicHandle {
     }
 }
 
+/// Remove stale resources of the given backend left behind by crashed processes
+///
+/// Returns the number of removed resources (segments or nodes, depending on the backend).
+pub fn cleanup_backend(backend: ComBackend) -> Result<usize, CleanupError> {
+    match backend {
+        #[cfg(feature = "ipc_iceoryx2")]
+        ComBackend::Iox2 => iox2::cleanup(),
+        #[cfg(feature = "ipc_linux_shm")]
+        ComBackend::LinuxShm => linux_shm::cleanup().map_err(CleanupError::LinuxShm),
+    }
+}
+
 /// Start the given backend, if necessary
+///
+/// With the `topic_stats` feature, this also starts the periodic reporting of topic statistics.
 pub fn run_backend(backend: ComBackend, _local_requests: usize, _remote_requests: usize) {
+    #[cfg(feature = "topic_stats")]
+    crate::stats::spawn_reporter(backend);
+
     match backend {
         #[cfg(feature = "ipc_iceoryx2")]
         ComBackend::Iox2 => {}
@@ -401,3 +629,77 @@ pub fn run_backend(backend: ComBackend, _local_requests: usize, _remote_requests
         }
     }
 }
+
+/// Checks of the in-place initialization of output buffers, shared by the backend tests
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::*;
+    use alloc::format;
+    use std::process;
+
+    #[derive(Debug, Default)]
+    #[repr(C)]
+    pub(crate) struct Payload {
+        pub(crate) values: [u64; 32],
+        pub(crate) flag: bool,
+    }
+
+    // Safety: all fields are zeroable
+    unsafe impl Zeroable for Payload {}
+
+    /// Configure a namespace unique to the test process
+    pub(crate) fn configure_test_namespace() {
+        let name = format!("feo-com-test-{}", process::id());
+        configure_namespace(&Namespace::new(&name).unwrap());
+    }
+
+    // Publish a payload without any zero byte, so that stale contents would be detected
+    fn write_non_zero(output: &mut impl ActivityOutput<Payload>) {
+        output
+            .write_uninit()
+            .unwrap()
+            .write_payload(Payload {
+                values: [u64::MAX; 32],
+                flag: true,
+            })
+            .send()
+            .unwrap();
+    }
+
+    /// Check that `zeroed`, `write_zeroed` and `write_with` overwrite the previous contents
+    pub(crate) fn check_in_place_initialization(
+        output: &mut impl ActivityOutput<Payload>,
+        input: &impl ActivityInput<Payload>,
+    ) {
+        write_non_zero(output);
+        output.write_uninit().unwrap().zeroed().send().unwrap();
+        {
+            let payload = input.read().unwrap();
+            assert_eq!(payload.values, [0; 32]);
+            assert!(!payload.flag);
+        }
+
+        write_non_zero(output);
+        output.write_zeroed().unwrap().send().unwrap();
+        {
+            let payload = input.read().unwrap();
+            assert_eq!(payload.values, [0; 32]);
+            assert!(!payload.flag);
+        }
+
+        write_non_zero(output);
+        output
+            .write_uninit()
+            .unwrap()
+            .write_with(|payload| payload.values[7] = 42)
+            .send()
+            .unwrap();
+        {
+            let payload = input.read().unwrap();
+            let mut expected = [0; 32];
+            expected[7] = 42;
+            assert_eq!(payload.values, expected);
+            assert!(!payload.flag);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Introspection of the topics existing on this host
+//!
+//! The information available depends on the com backend.
+//! Fields which the backend cannot provide are `None`.
+
+use crate::interface::ComBackend;
+#[cfg(feature = "ipc_iceoryx2")]
+use crate::iox2;
+#[cfg(feature = "ipc_linux_shm")]
+use crate::linux_shm;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use std::time::SystemTime;
+
+/// Information about a single topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicInfo {
+    /// Name of the topic
+    pub topic: String,
+    /// Rust type name of the topic's payload, if known
+    pub type_name: Option<String>,
+    /// Size of the payload in bytes
+    pub size: usize,
+    /// Number of writers currently attached to the topic, if known
+    ///
+    /// Writers of processes killed without dropping them remain counted.
+    pub writers: Option<usize>,
+    /// Number of readers currently attached to the topic, if known
+    ///
+    /// Readers of processes killed without dropping them remain counted. With the `linux_shm`
+    /// backend, readers are only counted with the `topic_stats` feature.
+    pub readers: Option<usize>,
+    /// Time of the last publication on the topic, if known
+    pub last_publish: Option<SystemTime>,
+    /// Statistics of the topic, if supported by the backend and enabled by the `topic_stats` feature
+    pub stats: Option<TopicStats>,
+}
+
+/// Statistics of a single topic
+///
+/// The counters are only updated by processes built with the `topic_stats` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TopicStats {
+    /// Number of publications
+    pub publishes: u64,
+    /// Number of reads
+    pub reads: u64,
+    /// Maximal age of the sample observed at reads, if read after a publication
+    pub max_age: Option<Duration>,
+}
+
+/// List all topics of the given backend existing on this host, sorted by topic name
+pub fn list_topics(backend: ComBackend) -> Vec<TopicInfo> {
+    let mut topics = match backend {
+        #[cfg(feature = "ipc_iceoryx2")]
+        ComBackend::Iox2 => iox2::list_topics(),
+        #[cfg(feature = "ipc_linux_shm")]
+        ComBackend::LinuxShm => linux_shm::list_topics(),
+    };
+    topics.sort_by(|a, b| a.topic.cmp(&b.topic));
+    topics
+}
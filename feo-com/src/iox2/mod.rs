@@ -5,11 +5,18 @@
 //! iceoryx2 com backend
 
 use crate::interface::{
-    ActivityInput, ActivityOutput, ActivityOutputDefault, Error, InputGuard, OutputGuard,
-    OutputUninitGuard, Topic, TopicHandle,
+    namespace, ActivityInput, ActivityOutput, ActivityOutputDefault, CleanupError, Error,
+    InputGuard, OutputGuard, OutputUninitGuard, Topic, TopicHandle,
 };
+use crate::introspection::TopicInfo;
+#[cfg(feature = "runtime_tracing")]
+use crate::trace;
 use alloc::boxed::Box;
 use alloc::format;
+#[cfg(feature = "runtime_tracing")]
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::fmt;
 use core::mem::MaybeUninit;
 use core::ops::{Deref, DerefMut};
@@ -23,6 +30,8 @@ use iceoryx2::sample::Sample;
 use iceoryx2::sample_mut::SampleMut;
 use iceoryx2::sample_mut_uninit::SampleMutUninit;
 use iceoryx2::service::ipc;
+use iceoryx2::service::static_config::messaging_pattern::MessagingPattern;
+use iceoryx2::service::Service;
 use std::process;
 
 /// Initialize topic with the given number of writers (publishers) and readers (subscribers).
@@ -55,6 +64,8 @@ where
     T: fmt::Debug + 'static,
 {
     subscriber: Subscriber<ipc::Service, T, ()>,
+    #[cfg(feature = "runtime_tracing")]
+    topic: String,
 }
 
 impl<T> Iox2Input<T>
@@ -75,7 +86,11 @@ where
             .subscriber_builder()
             .create()
             .unwrap_or_else(|_| panic!("failed to create subscriber for topic {topic}"));
-        Self { subscriber }
+        Self {
+            subscriber,
+            #[cfg(feature = "runtime_tracing")]
+            topic: topic.to_string(),
+        }
     }
 }
 
@@ -84,6 +99,8 @@ where
     T: fmt::Debug + 'static,
 {
     fn read(&self) -> Result<InputGuard<T>, Error> {
+        #[cfg(feature = "runtime_tracing")]
+        trace::read(&self.topic);
         match self.subscriber.receive() {
             Ok(Some(sample)) => Ok(InputGuard::Iox2(Iox2InputGuard { sample })),
             Ok(None) | Err(_) => Err(Error::NoEmptyBuffer),
@@ -98,6 +115,8 @@ where
     T: fmt::Debug + 'static,
 {
     publisher: Publisher<ipc::Service, T, ()>,
+    #[cfg(feature = "runtime_tracing")]
+    topic: String,
 }
 
 impl<T> Iox2Output<T>
@@ -118,7 +137,11 @@ where
             .publisher_builder()
             .create()
             .unwrap_or_else(|_| panic!("failed to create subscriber for topic {topic}"));
-        Self { publisher }
+        Self {
+            publisher,
+            #[cfg(feature = "runtime_tracing")]
+            topic: topic.to_string(),
+        }
     }
 }
 
@@ -128,6 +151,8 @@ where
 {
     /// Get a handle to an uninitialized buffer
     fn write_uninit(&mut self) -> Result<OutputUninitGuard<T>, Error> {
+        #[cfg(feature = "runtime_tracing")]
+        trace::write(&self.topic);
         self.publisher
             .loan_uninit()
             .map(|sample| OutputUninitGuard::Iox2(Iox2OutputUninitGuard { sample }))
@@ -141,6 +166,8 @@ where
 {
     /// Get a handle to a buffer initialized with the [Default] trait
     fn write_init(&mut self) -> Result<OutputGuard<T>, Error> {
+        #[cfg(feature = "runtime_tracing")]
+        trace::write(&self.topic);
         self.publisher
             .loan()
             .map(|sample| OutputGuard::Iox2(Iox2OutputGuard { sample }))
@@ -244,15 +271,57 @@ impl<T: fmt::Debug> DerefMut for Iox2OutputUninitGuard<T> {
     }
 }
 
+fn ipc_config() -> Config {
+    let mut config = Config::default();
+    config.global.prefix = format!("feo_ipc_{}", namespace())
+        .as_str()
+        .try_into()
+        .expect("invalid namespace");
+    config
+}
+
+/// List all publish-subscribe services existing on this host
+///
+/// The numbers of attached publishers and subscribers are not available
+/// without knowing the payload type, so they are reported as unknown.
+pub fn list_topics() -> Vec<TopicInfo> {
+    let mut topics = Vec::new();
+    ipc::Service::list(&ipc_config(), |details| {
+        if let MessagingPattern::PublishSubscribe(cfg) = details.static_details.messaging_pattern()
+        {
+            let payload = &cfg.message_type_details().payload;
+            topics.push(TopicInfo {
+                topic: details.static_details.name().as_str().to_string(),
+                type_name: Some(payload.type_name.to_string()),
+                size: payload.size,
+                writers: None,
+                readers: None,
+                last_publish: None,
+                stats: None,
+            });
+        }
+        CallbackProgression::Continue
+    })
+    .expect("failed to list iceoryx2 services");
+    topics
+}
+
+/// Remove the stale resources of all dead nodes
+///
+/// Returns the number of cleaned up nodes.
+pub fn cleanup() -> Result<usize, CleanupError> {
+    let state = Node::<ipc::Service>::cleanup_dead_nodes(&ipc_config());
+    match state.failed_cleanups {
+        0 => Ok(state.cleanups),
+        failed => Err(CleanupError::Iox2 { failed }),
+    }
+}
+
 fn ipc_node() -> &'static Node<ipc::Service> {
     static ICEORYX_NODE: std::sync::OnceLock<Node<ipc::Service>> = std::sync::OnceLock::new();
 
     ICEORYX_NODE.get_or_init(|| {
-        let config = {
-            let mut config = Config::default();
-            config.global.prefix = "feo_ipc".try_into().unwrap();
-            config
-        };
+        let config = ipc_config();
 
         // Ensure there is no left-over state from dead nodes.
         Node::<ipc::Service>::cleanup_dead_nodes(&config);
@@ -276,3 +345,22 @@ fn ipc_node() -> &'static Node<ipc::Service> {
             .expect("failed to create ipc node")
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::interface::test_util::{
+        check_in_place_initialization, configure_test_namespace, Payload,
+    };
+
+    #[test]
+    fn test_in_place_initialization() {
+        configure_test_namespace();
+        let topic = "test/in_place_initialization";
+        let _handle = init_topic::<Payload>(topic, 1, 1);
+        let mut output = Iox2Output::<Payload>::new(topic);
+        let input = Iox2Input::<Payload>::new(topic);
+
+        check_in_place_initialization(&mut output, &input);
+    }
+}
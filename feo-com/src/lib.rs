@@ -15,7 +15,12 @@ extern crate alloc;
 extern crate std;
 
 pub mod interface;
+pub mod introspection;
 #[cfg(feature = "ipc_iceoryx2")]
 pub mod iox2;
 #[cfg(feature = "ipc_linux_shm")]
 pub mod linux_shm;
+#[cfg(feature = "topic_stats")]
+mod stats;
+#[cfg(feature = "runtime_tracing")]
+mod trace;
@@ -21,38 +21,53 @@
 //!   preceding call of [MappedPtrWriteGuard::send].
 //!
 
+pub mod ownership;
 pub(crate) mod shared_memory;
 
 use crate::interface::{
-    ActivityInput, ActivityOutput, ActivityOutputDefault, Error, InputGuard, OutputGuard,
-    OutputUninitGuard, Topic, TopicHandle,
+    namespace, ActivityInput, ActivityOutput, ActivityOutputDefault, Error, InputGuard,
+    OutputGuard, OutputUninitGuard, Topic, TopicHandle,
 };
+use crate::introspection::TopicInfo;
+#[cfg(feature = "topic_stats")]
+use crate::introspection::TopicStats;
+use crate::linux_shm::ownership::{namespace_prefix, OwnerLock};
 use crate::linux_shm::shared_memory::{
-    MappedPtrReadGuard, MappedPtrWriteGuard, MappingMode, ReadWriteAccessControlPtr,
+    MappedPtrReadGuard, MappedPtrWriteGuard, MappingMode, ReadWriteAccessControlPtr, TopicHeader,
     TopicInitializationAgentRole,
 };
+#[cfg(feature = "runtime_tracing")]
+use crate::trace;
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
 use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::any::type_name;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 use core::mem::{size_of, MaybeUninit};
+use core::num::NonZeroUsize;
 use core::ops::{Deref, DerefMut};
+use core::slice;
 use core::slice::from_raw_parts;
 use core::sync::atomic::Ordering;
+#[cfg(feature = "topic_stats")]
+use core::time::Duration;
 use feo_log::{debug, error, info};
 use nix::fcntl::OFlag;
 use nix::sys::mman::{mmap, shm_open, MapFlags, ProtFlags};
 use nix::sys::stat::Mode;
 use nix::unistd;
 use std::collections::HashMap;
-use std::io::{read_to_string, Write};
+use std::fs::{self, File};
+use std::io::{read_to_string, Read, Write};
 use std::net::Shutdown;
 use std::os::fd::OwnedFd;
 use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
 use std::sync::{LazyLock, Mutex, MutexGuard};
 use std::thread;
 use std::thread::JoinHandle;
@@ -61,9 +76,12 @@ use std::thread::JoinHandle;
 static RUNTIME: LazyLock<Mutex<ComRuntime>> = LazyLock::new(|| Mutex::new(ComRuntime::new()));
 
 impl ReadWriteAccessControlPtr {
-    fn map(&self, native_mapping: &OwnedFd) {
+    // Map the payload of the given native mapping and, if `track` is set, also its header
+    // in which the attached readers and writers are counted
+    fn map(&self, native_mapping: &OwnedFd, data_offset: usize, track: bool) {
         assert!(self.ptr.load(Ordering::Relaxed).is_null(), "already mapped");
-        let flags = if self.writable.load(Ordering::Relaxed) {
+        let writable = self.writable.load(Ordering::Relaxed);
+        let flags = if writable {
             ProtFlags::PROT_READ | ProtFlags::PROT_WRITE
         } else {
             ProtFlags::PROT_READ
@@ -78,7 +96,7 @@ impl ReadWriteAccessControlPtr {
                 flags,
                 MapFlags::MAP_SHARED,
                 native_mapping,
-                0,
+                data_offset as i64,
             )
         }
         .expect("mmap failed")
@@ -86,7 +104,114 @@ impl ReadWriteAccessControlPtr {
         let ptr = ptr as *mut ();
         assert!(!ptr.is_null());
         self.ptr.store(ptr, Ordering::Relaxed);
+
+        if track {
+            // Readers only update the header if statistics are kept
+            let header = map_header(native_mapping, writable || cfg!(feature = "topic_stats"));
+            self.header
+                .store((header as *const TopicHeader).cast_mut(), Ordering::Relaxed);
+        }
+    }
+}
+
+// Map the header of the given native mapping for the 'static lifetime
+//
+// The header must only be modified if mapped `writable`.
+fn map_header(native_mapping: &OwnedFd, writable: bool) -> &'static TopicHeader {
+    let flags = if writable {
+        ProtFlags::PROT_READ | ProtFlags::PROT_WRITE
+    } else {
+        ProtFlags::PROT_READ
+    };
+    // Safety: FFI call
+    let ptr = unsafe {
+        mmap(
+            None,
+            NonZeroUsize::new(size_of::<TopicHeader>()).unwrap(),
+            flags,
+            MapFlags::MAP_SHARED,
+            native_mapping,
+            0,
+        )
     }
+    .expect("mmap of topic header failed")
+    .as_ptr();
+    // Safety: the mapping is page-aligned, at least of the size of the header
+    // and never unmapped; the header is only accessed through atomics after initialization
+    let header = unsafe { &*(ptr as *const TopicHeader) };
+    assert!(header.is_valid(), "invalid topic header");
+    header
+}
+
+// Offset of the payload in the shared memory segment: the header size rounded up to the page size
+fn data_offset() -> usize {
+    // Safety: FFI call
+    let page_size = unsafe { nix::libc::sysconf(nix::libc::_SC_PAGESIZE) };
+    let page_size = usize::try_from(page_size).expect("failed to get page size");
+    size_of::<TopicHeader>().div_ceil(page_size) * page_size
+}
+
+// Read the header of the shared memory segment with the given mapping id
+fn read_header(mapping_id: &str) -> Option<TopicHeader> {
+    let mut file = File::open(Path::new(SHM_DIR).join(mapping_id)).ok()?;
+    let mut header = MaybeUninit::<TopicHeader>::uninit();
+    // Safety: the buffer covers exactly the uninitialized header
+    let buffer = unsafe {
+        slice::from_raw_parts_mut(header.as_mut_ptr() as *mut u8, size_of::<TopicHeader>())
+    };
+    file.read_exact(buffer).ok()?;
+    // Safety: all bit patterns are valid for the header fields
+    let header = unsafe { header.assume_init() };
+    header.is_valid().then_some(header)
+}
+
+// Return mapping ids and headers of all valid shared memory segments of the given namespace
+fn segments(namespace: &str) -> Vec<(String, TopicHeader)> {
+    let Ok(entries) = fs::read_dir(SHM_DIR) else {
+        return Vec::new();
+    };
+    let prefix = namespace_prefix(namespace);
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(&prefix))
+        .filter_map(|name| read_header(&name).map(|header| (name, header)))
+        .collect()
+}
+
+// Path of the runtime socket of the configured namespace
+fn socket_path() -> PathBuf {
+    Path::new(SOCKET_DIR).join(format!("score_feo.{}.socket", namespace()))
+}
+
+/// List all topics with a shared memory segment in the namespace of this application instance
+pub fn list_topics() -> Vec<TopicInfo> {
+    segments(namespace())
+        .into_iter()
+        .map(|(_, header)| TopicInfo {
+            topic: header.topic(),
+            type_name: Some(header.type_name()),
+            size: header.data_size as usize,
+            writers: Some(header.writers.load(Ordering::Relaxed) as usize),
+            #[cfg(feature = "topic_stats")]
+            readers: Some(header.readers.load(Ordering::Relaxed) as usize),
+            #[cfg(not(feature = "topic_stats"))]
+            readers: None,
+            last_publish: header.last_publish(),
+            // The statistics counters are only updated with the `topic_stats` feature
+            #[cfg(feature = "topic_stats")]
+            stats: Some(TopicStats {
+                publishes: header.publishes.load(Ordering::Relaxed),
+                reads: header.reads.load(Ordering::Relaxed),
+                max_age: match header.max_age.load(Ordering::Relaxed) {
+                    0 => None,
+                    nanos => Some(Duration::from_nanos(nanos)),
+                },
+            }),
+            #[cfg(not(feature = "topic_stats"))]
+            stats: None,
+        })
+        .collect()
 }
 
 struct TopicMapping {
@@ -98,10 +223,19 @@ struct TopicMapping {
 // COM runtime state
 pub struct ComRuntime {
     topics: HashMap<String, TopicMapping>,
+    // Ownership of the namespace, held by the primary
+    owner: Option<OwnerLock>,
     _thread: Option<JoinHandle<()>>,
 }
 
-const SOCKET: &str = "/tmp/score_feo.socket";
+/// Directory of the runtime socket
+const SOCKET_DIR: &str = "/tmp";
+
+/// Directory in which `shm_open` creates the shared memory segments
+const SHM_DIR: &str = "/dev/shm";
+
+/// Prefix of the names of all shared memory segments created by this backend
+const MAPPING_PREFIX: &str = "score_feo_";
 
 impl ComRuntime {
     pub fn run_service(requests_to_serve: usize) {
@@ -112,9 +246,10 @@ impl ComRuntime {
 
     /// Run COM runtime services
     fn service_main(mut requests_to_serve: usize) {
-        let _ = std::fs::remove_file(SOCKET);
-        let listener = UnixListener::bind(SOCKET)
-            .unwrap_or_else(|e| panic!("can't bind socket at {SOCKET}: {e}"));
+        let socket = socket_path();
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket)
+            .unwrap_or_else(|e| panic!("can't bind socket at {}: {e}", socket.display()));
         debug!("Listening for {requests_to_serve} topic mapping requests...");
         loop {
             if requests_to_serve < 1 {
@@ -165,12 +300,17 @@ impl ComRuntime {
     fn new() -> Self {
         Self {
             topics: HashMap::new(),
+            owner: None,
             _thread: None,
         }
     }
 
-    fn unique_mapping_id() -> String {
-        format!("score_feo_{:X}", rand::random::<u64>())
+    fn unique_mapping_id(&self) -> String {
+        format!(
+            "{}{:X}",
+            namespace_prefix(namespace()),
+            rand::random::<u64>()
+        )
     }
 
     /// Initialize the topic and register it in the COM runtime
@@ -198,23 +338,31 @@ impl ComRuntime {
     ) {
         let size = size_of::<T>();
         info!("Initializing topic {topic} (LinuxShm, {size} bytes)...");
-        let mapping_id = Self::unique_mapping_id();
+        if self.owner.is_none() {
+            self.owner = Some(OwnerLock::acquire(namespace()));
+        }
+        let mapping_id = self.unique_mapping_id();
         let native_mapping = shm_open(
             mapping_id.as_str(),
             OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_RDWR,
             Mode::S_IRUSR | Mode::S_IWUSR,
         )
         .unwrap_or_else(|e| panic!("can't create memory mapping for {topic}: {e}"));
-        assert_eq!(
-            size,
-            unistd::write(&native_mapping, unsafe {
-                from_raw_parts((&T::default() as *const T) as *const u8, size_of::<T>())
-            })
-            .expect("can't write shared memory init value")
-        );
+        let data_offset = data_offset();
+        let header = TopicHeader::new(topic, type_name::<T>(), data_offset, size);
+        let padding = vec![0u8; data_offset - size_of::<TopicHeader>()];
+        for bytes in [header.as_bytes(), &padding, unsafe {
+            from_raw_parts((&T::default() as *const T) as *const u8, size_of::<T>())
+        }] {
+            assert_eq!(
+                bytes.len(),
+                unistd::write(&native_mapping, bytes)
+                    .expect("can't write shared memory init value")
+            );
+        }
         let ptr = Arc::new(ReadWriteAccessControlPtr::new_unmapped::<T>(mapping_mode));
         if also_map {
-            ptr.map(&native_mapping);
+            ptr.map(&native_mapping, data_offset, true);
         }
         let mapping = TopicMapping { ptr, mapping_id };
         assert!(
@@ -230,18 +378,20 @@ impl ComRuntime {
     ) {
         let (size, mapping_id) = Self::request_primary(topic);
         assert_eq!(size_of::<T>(), size);
-        let native_mapping = shm_open(
-            &*mapping_id,
-            if matches!(mapping_mode, MappingMode::Write) {
-                OFlag::O_RDWR
-            } else {
-                OFlag::O_RDONLY
-            },
-            Mode::S_IRUSR,
-        )
-        .unwrap_or_else(|e| panic!("can't open mapping {mapping_id}: {e}"));
+        // Readers open for writing only to update the header with the `topic_stats` feature,
+        // access to the payload is restricted when mapping it
+        let flags = if matches!(mapping_mode, MappingMode::Write) || cfg!(feature = "topic_stats") {
+            OFlag::O_RDWR
+        } else {
+            OFlag::O_RDONLY
+        };
+        let native_mapping = shm_open(&*mapping_id, flags, Mode::S_IRUSR)
+            .unwrap_or_else(|e| panic!("can't open mapping {mapping_id}: {e}"));
+        let header = read_header(&mapping_id)
+            .unwrap_or_else(|| panic!("invalid header in mapping {mapping_id}"));
+        assert_eq!(header.type_name(), type_name::<T>(), "type mismatch");
         let ptr = Arc::new(ReadWriteAccessControlPtr::new_unmapped::<T>(mapping_mode));
-        ptr.map(&native_mapping);
+        ptr.map(&native_mapping, header.data_offset as usize, true);
         let mapping = TopicMapping {
             ptr,
             mapping_id: mapping_id.to_string(),
@@ -252,10 +402,33 @@ impl ComRuntime {
         );
     }
 
+    // Initialize the topic for read-only access, without handshake with the primary
+    fn init_topic_observer<T: Debug + 'static>(&mut self, topic: Topic) {
+        let (mapping_id, header) = segments(namespace())
+            .into_iter()
+            .find(|(_, header)| header.topic() == topic)
+            .unwrap_or_else(|| panic!("no shared memory segment found for topic {topic}"));
+        assert_eq!(header.data_size as usize, size_of::<T>(), "size mismatch");
+        assert_eq!(header.type_name(), type_name::<T>(), "type mismatch");
+        let native_mapping = shm_open(&*mapping_id, OFlag::O_RDONLY, Mode::S_IRUSR)
+            .unwrap_or_else(|e| panic!("can't open mapping {mapping_id}: {e}"));
+        // Observers do not show up in the header's counters
+        let ptr = Arc::new(ReadWriteAccessControlPtr::new_unmapped_checked::<T>(
+            MappingMode::Read,
+        ));
+        ptr.map(&native_mapping, header.data_offset as usize, false);
+        let mapping = TopicMapping { ptr, mapping_id };
+        assert!(
+            self.topics.insert(topic.to_owned(), mapping).is_none(),
+            "COM topic already initialized"
+        );
+    }
+
     // Make a request to primary
     fn request_primary(topic: Topic) -> (usize, String) {
-        let mut stream = UnixStream::connect(SOCKET)
-            .unwrap_or_else(|e| panic!("can't connect to socket {SOCKET}: {e}"));
+        let socket = socket_path();
+        let mut stream = UnixStream::connect(&socket)
+            .unwrap_or_else(|e| panic!("can't connect to socket {}: {e}", socket.display()));
         stream
             .write_all(topic.as_bytes())
             .expect("socket write failed");
@@ -318,8 +491,29 @@ pub fn init_topic<T: Debug + Default + 'static>(
     TopicHandle::from(Box::new(()))
 }
 
+/// Remove orphaned shared memory segments of this application instance
+///
+/// Returns the number of removed segments or an error,
+/// if the segments are still owned by a living primary process.
+pub fn cleanup() -> Result<usize, ownership::Error> {
+    ownership::cleanup(namespace())
+}
+
+// Initialize the topic for read-only observation and register it in the global COM runtime
+pub fn init_topic_observer<T: Debug + 'static>(topic: Topic) -> TopicHandle {
+    ComRuntime::global_runtime().init_topic_observer::<T>(topic);
+    TopicHandle::from(Box::new(()))
+}
+
 pub struct LinuxShmInputGuard<T: Debug>(MappedPtrReadGuard<T>);
 
+impl<T: Debug> LinuxShmInputGuard<T> {
+    /// Sequence number of the sample, incremented on every publication on the topic
+    pub(crate) fn sequence(&self) -> Option<u64> {
+        self.0.sequence()
+    }
+}
+
 impl<T: Debug> Deref for LinuxShmInputGuard<T> {
     type Target = T;
 
@@ -406,23 +600,37 @@ impl<T: Debug> DerefMut for LinuxShmOutputUninitGuard<T> {
 #[derive(Debug)]
 pub struct LinuxShmInput<T> {
     ptr: Arc<ReadWriteAccessControlPtr>,
+    #[cfg(feature = "runtime_tracing")]
+    topic: String,
     _type: PhantomData<T>,
 }
 
 impl<T: Debug + 'static> LinuxShmInput<T> {
     pub fn new(topic: Topic) -> Self {
+        let ptr = ComRuntime::global_runtime().topic_mapping::<T>(topic, MappingMode::Read);
+        ptr.attach(MappingMode::Read);
         Self {
-            ptr: ComRuntime::global_runtime().topic_mapping::<T>(topic, MappingMode::Read),
+            ptr,
+            #[cfg(feature = "runtime_tracing")]
+            topic: topic.to_owned(),
             _type: PhantomData,
         }
     }
 }
 
+impl<T> Drop for LinuxShmInput<T> {
+    fn drop(&mut self) {
+        self.ptr.detach(MappingMode::Read);
+    }
+}
+
 impl<T> ActivityInput<T> for LinuxShmInput<T>
 where
     T: Debug + 'static,
 {
     fn read(&self) -> Result<InputGuard<T>, Error> {
+        #[cfg(feature = "runtime_tracing")]
+        trace::read(&self.topic);
         Ok(InputGuard::LinuxShm(LinuxShmInputGuard(self.ptr.get())))
     }
 }
@@ -430,24 +638,38 @@ where
 #[derive(Debug)]
 pub struct LinuxShmOutput<T> {
     ptr: Arc<ReadWriteAccessControlPtr>,
+    #[cfg(feature = "runtime_tracing")]
+    topic: String,
     _type: PhantomData<T>,
 }
 
 impl<T: Debug + 'static> LinuxShmOutput<T> {
     pub fn new(topic: Topic) -> Self {
+        let ptr = ComRuntime::global_runtime().topic_mapping::<T>(topic, MappingMode::Write);
+        ptr.attach(MappingMode::Write);
         Self {
-            ptr: ComRuntime::global_runtime().topic_mapping::<T>(topic, MappingMode::Write),
+            ptr,
+            #[cfg(feature = "runtime_tracing")]
+            topic: topic.to_owned(),
             _type: PhantomData,
         }
     }
 }
 
+impl<T> Drop for LinuxShmOutput<T> {
+    fn drop(&mut self) {
+        self.ptr.detach(MappingMode::Write);
+    }
+}
+
 impl<T> ActivityOutput<T> for LinuxShmOutput<T>
 where
     T: Debug + 'static,
 {
     // Initialized when allocated
     fn write_uninit(&mut self) -> Result<OutputUninitGuard<T>, Error> {
+        #[cfg(feature = "runtime_tracing")]
+        trace::write(&self.topic);
         Ok(OutputUninitGuard::LinuxShm(LinuxShmOutputUninitGuard(
             self.ptr.get_mut(),
         )))
@@ -460,8 +682,81 @@ where
 {
     // Overwrites with [Default::default]
     fn write_init(&mut self) -> Result<OutputGuard<T>, Error> {
+        #[cfg(feature = "runtime_tracing")]
+        trace::write(&self.topic);
         let mut ptr = self.ptr.get_mut();
         *ptr = T::default();
         Ok(OutputGuard::LinuxShm(LinuxShmOutputGuard { ptr }))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::interface::test_util::{
+        check_in_place_initialization, configure_test_namespace, Payload,
+    };
+    use std::sync::PoisonError;
+
+    // Serializes the tests sharing the global runtime, each of which cleans up the namespace
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_in_place_initialization() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        configure_test_namespace();
+        let topic = "test/in_place_initialization";
+        let _handle = init_topic::<Payload>(
+            topic,
+            MappingMode::Write,
+            TopicInitializationAgentRole::Primary { also_map: true },
+        );
+        let mut output = LinuxShmOutput::<Payload>::new(topic);
+        let input = LinuxShmInput::<Payload>::new(topic);
+
+        check_in_place_initialization(&mut output, &input);
+
+        // Release the ownership of the namespace to remove its segment and lock file
+        ComRuntime::global_runtime().owner.take();
+        assert_eq!(cleanup().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_attached_counts() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        configure_test_namespace();
+        let topic = "test/attached_counts";
+        let _handle = init_topic::<Payload>(
+            topic,
+            MappingMode::Write,
+            TopicInitializationAgentRole::Primary { also_map: true },
+        );
+        let info = || {
+            list_topics()
+                .into_iter()
+                .find(|info| info.topic == topic)
+                .unwrap()
+        };
+        let counts = || (info().writers, info().readers);
+        // Statistics are only reported if kept
+        assert_eq!(info().stats.is_some(), cfg!(feature = "topic_stats"));
+        // Readers are only counted if statistics are kept
+        let readers = |count| cfg!(feature = "topic_stats").then_some(count);
+        assert_eq!(counts(), (Some(0), readers(0)));
+
+        let output = LinuxShmOutput::<Payload>::new(topic);
+        let input = LinuxShmInput::<Payload>::new(topic);
+        let other_input = LinuxShmInput::<Payload>::new(topic);
+        assert_eq!(counts(), (Some(1), readers(2)));
+
+        drop(input);
+        assert_eq!(counts(), (Some(1), readers(1)));
+        drop(output);
+        drop(other_input);
+        assert_eq!(counts(), (Some(0), readers(0)));
+
+        // Release the ownership of the namespace to remove its segment and lock file
+        ComRuntime::global_runtime().owner.take();
+        assert_eq!(cleanup().unwrap(), 1);
+    }
+}
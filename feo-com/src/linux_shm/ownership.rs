@@ -0,0 +1,219 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ownership tracking and cleanup of shared memory segments
+//!
+//! All segments of a namespace are owned by the primary process of that namespace.
+//! The primary holds an exclusive lock on a lock file containing its pid for its whole lifetime.
+//! If the lock can be acquired, the previous owner is dead and all remaining segments
+//! of the namespace are orphaned and can be removed.
+
+use crate::linux_shm::{MAPPING_PREFIX, SHM_DIR};
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use feo_log::{debug, info, warn};
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use nix::sys::mman::shm_unlink;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// Exclusive ownership of the segments of a namespace, held by the primary process
+pub(crate) struct OwnerLock {
+    _lock: Flock<File>,
+}
+
+impl OwnerLock {
+    /// Acquire ownership of the given namespace, removing orphaned segments of a previous owner
+    ///
+    /// # Panics
+    ///
+    /// Panics if the namespace is owned by another living process.
+    pub(crate) fn acquire(namespace: &str) -> Self {
+        let mut lock = match try_lock(namespace) {
+            Ok(lock) => lock,
+            Err(Error::Owned { pid }) => {
+                panic!("namespace {namespace} is already owned by primary process {pid}")
+            }
+            Err(e) => panic!("failed to lock namespace {namespace}: {e}"),
+        };
+
+        let removed = remove_segments(namespace);
+        if removed > 0 {
+            info!("Removed {removed} orphaned shared memory segments of namespace {namespace}");
+        }
+
+        lock.set_len(0)
+            .and_then(|_| lock.rewind())
+            .and_then(|_| write!(lock, "{}", process::id()))
+            .unwrap_or_else(|e| panic!("failed to write lock file of namespace {namespace}: {e}"));
+        debug!("Acquired ownership of namespace {namespace}");
+        Self { _lock: lock }
+    }
+}
+
+/// Error type of the cleanup of shared memory segments
+#[derive(Debug)]
+pub enum Error {
+    /// The namespace is owned by a living primary process with the given pid
+    Owned { pid: String },
+    /// Accessing the lock file failed
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Owned { pid } => write!(f, "namespace owned by living process {pid}"),
+            Error::Io(e) => write!(f, "failed to access lock file: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+/// Remove all orphaned segments of the given namespace and its lock file
+///
+/// Returns the number of removed segments or an error,
+/// if the namespace is still owned by a living primary process.
+pub fn cleanup(namespace: &str) -> Result<usize, Error> {
+    let _lock = try_lock(namespace)?;
+    let removed = remove_segments(namespace);
+    // Remove the lock file while still holding the lock
+    fs::remove_file(lock_file_path(namespace)).map_err(Error::Io)?;
+    Ok(removed)
+}
+
+/// Prefix of the names of all segments of the given namespace
+pub(crate) fn namespace_prefix(namespace: &str) -> String {
+    format!("{MAPPING_PREFIX}{namespace}_")
+}
+
+fn lock_file_path(namespace: &str) -> PathBuf {
+    PathBuf::from(SHM_DIR).join(format!("score_feo.{namespace}.lock"))
+}
+
+// Try to acquire the exclusive lock of the given namespace
+fn try_lock(namespace: &str) -> Result<Flock<File>, Error> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_file_path(namespace))
+        .map_err(Error::Io)?;
+    match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+        Ok(lock) => Ok(lock),
+        Err((mut file, Errno::EWOULDBLOCK)) => {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            Err(Error::Owned { pid })
+        }
+        Err((_, errno)) => Err(Error::Io(errno.into())),
+    }
+}
+
+// Remove all segments of the given namespace, returning the number of removed segments
+fn remove_segments(namespace: &str) -> usize {
+    let Ok(entries) = fs::read_dir(SHM_DIR) else {
+        return 0;
+    };
+    let prefix = namespace_prefix(namespace);
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(&prefix))
+        .filter(|name| match shm_unlink(name.as_str()) {
+            Ok(_) => {
+                debug!("Removed shared memory segment {name}");
+                true
+            }
+            Err(e) => {
+                warn!("Failed to remove shared memory segment {name}: {e}");
+                false
+            }
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use nix::fcntl::OFlag;
+    use nix::sys::mman::shm_open;
+    use nix::sys::stat::Mode;
+    use std::path::Path;
+
+    // Namespace unique to the test process and test
+    fn test_namespace(test: &str) -> String {
+        format!("feo-com-ownership-{}-{test}", process::id())
+    }
+
+    // Create a segment of the given namespace, returning its name
+    fn create_segment(namespace: &str, id: usize) -> String {
+        let name = format!("{}{id}", namespace_prefix(namespace));
+        shm_open(
+            name.as_str(),
+            OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_RDWR,
+            Mode::S_IRUSR | Mode::S_IWUSR,
+        )
+        .unwrap();
+        name
+    }
+
+    fn exists(segment: &str) -> bool {
+        Path::new(SHM_DIR).join(segment).exists()
+    }
+
+    #[test]
+    fn test_held_lock_blocks_cleanup() {
+        let namespace = test_namespace("held");
+        let owner = OwnerLock::acquire(&namespace);
+        let segment = create_segment(&namespace, 0);
+
+        match cleanup(&namespace) {
+            Err(Error::Owned { pid }) => assert_eq!(pid, process::id().to_string()),
+            result => panic!("unexpected cleanup result {result:?}"),
+        }
+        assert!(exists(&segment));
+
+        drop(owner);
+        assert_eq!(cleanup(&namespace).unwrap(), 1);
+        assert!(!exists(&segment));
+        assert!(!lock_file_path(&namespace).exists());
+    }
+
+    #[test]
+    fn test_stale_segments_removed() {
+        let namespace = test_namespace("stale");
+        let segments = [create_segment(&namespace, 0), create_segment(&namespace, 1)];
+
+        // Acquiring the ownership of a namespace without living owner removes its segments
+        let owner = OwnerLock::acquire(&namespace);
+        assert!(segments.iter().all(|segment| !exists(segment)));
+
+        drop(owner);
+        assert_eq!(cleanup(&namespace).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_other_namespace_untouched() {
+        let namespace = test_namespace("own");
+        // Shares the prefix of the names of the other namespace
+        let other_namespace = format!("{namespace}-other");
+        let segment = create_segment(&namespace, 0);
+        let other_segment = create_segment(&other_namespace, 0);
+
+        assert_eq!(cleanup(&namespace).unwrap(), 1);
+        assert!(!exists(&segment));
+        assert!(exists(&other_segment));
+
+        assert_eq!(cleanup(&other_namespace).unwrap(), 1);
+        assert!(!exists(&other_segment));
+    }
+}
@@ -2,13 +2,15 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use alloc::string::String;
 use alloc::sync::Arc;
 use core::any::TypeId;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
 use core::ptr;
-use core::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicU16, Ordering};
+use core::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicU16, AtomicU32, AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 // Mode of shared memory mapping
 #[derive(Debug, Clone, Copy)]
@@ -27,6 +29,163 @@ pub enum MappingMode {
     Write,
 }
 
+/// Magic number identifying FEO shared memory segments
+pub(crate) const TOPIC_HEADER_MAGIC: u64 = u64::from_le_bytes(*b"FEO_SHM\0");
+
+/// Version of the [TopicHeader] layout
+pub(crate) const TOPIC_HEADER_VERSION: u32 = 3;
+
+/// Maximum length of topic and type names stored in the [TopicHeader]
+pub(crate) const TOPIC_HEADER_NAME_SIZE: usize = 256;
+
+// Header at the start of every shared memory segment describing the topic
+//
+// The header occupies the first page(s) of the segment, the payload starts at `data_offset`
+// which is a multiple of the page size. This allows to map the header writable
+// while mapping the payload read-only for readers.
+//
+// The counters are updated by the peers with atomic operations and can be read
+// by other processes for introspection. Readers only get write access to the header,
+// and thus to the whole segment, if the `topic_stats` feature is enabled. Otherwise they
+// open the segment read-only and neither the readers nor the statistics are counted.
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct TopicHeader {
+    pub(crate) magic: u64,
+    pub(crate) version: u32,
+    pub(crate) topic_len: u32,
+    pub(crate) type_name_len: u32,
+    // Number of attached writers
+    pub(crate) writers: AtomicU32,
+    // Number of attached readers, only counted with the `topic_stats` feature
+    pub(crate) readers: AtomicU32,
+    _reserved: u32,
+    pub(crate) topic: [u8; TOPIC_HEADER_NAME_SIZE],
+    pub(crate) type_name: [u8; TOPIC_HEADER_NAME_SIZE],
+    pub(crate) data_offset: u64,
+    pub(crate) data_size: u64,
+    // Time of last publication in nanoseconds since the UNIX epoch, 0 if never published
+    pub(crate) last_publish: AtomicU64,
+    // Number of publications
+    pub(crate) publishes: AtomicU64,
+    // Number of reads
+    pub(crate) reads: AtomicU64,
+    // Maximal age of the sample at reads in nanoseconds
+    pub(crate) max_age: AtomicU64,
+    // Sequence number of the current sample, incremented on every publication
+    pub(crate) sequence: AtomicU64,
+}
+
+impl TopicHeader {
+    pub(crate) fn new(topic: &str, type_name: &str, data_offset: usize, data_size: usize) -> Self {
+        let mut header = Self {
+            magic: TOPIC_HEADER_MAGIC,
+            version: TOPIC_HEADER_VERSION,
+            topic_len: 0,
+            type_name_len: 0,
+            writers: AtomicU32::new(0),
+            readers: AtomicU32::new(0),
+            _reserved: 0,
+            topic: [0; TOPIC_HEADER_NAME_SIZE],
+            type_name: [0; TOPIC_HEADER_NAME_SIZE],
+            data_offset: data_offset as u64,
+            data_size: data_size as u64,
+            last_publish: AtomicU64::new(0),
+            publishes: AtomicU64::new(0),
+            reads: AtomicU64::new(0),
+            max_age: AtomicU64::new(0),
+            sequence: AtomicU64::new(0),
+        };
+        header.topic_len = copy_name(topic, &mut header.topic);
+        header.type_name_len = copy_name(type_name, &mut header.type_name);
+        header
+    }
+
+    /// Check if magic number and version match the current implementation
+    pub(crate) fn is_valid(&self) -> bool {
+        self.magic == TOPIC_HEADER_MAGIC && self.version == TOPIC_HEADER_VERSION
+    }
+
+    pub(crate) fn topic(&self) -> String {
+        name_from_bytes(&self.topic, self.topic_len)
+    }
+
+    pub(crate) fn type_name(&self) -> String {
+        name_from_bytes(&self.type_name, self.type_name_len)
+    }
+
+    /// Time of the last publication, if any
+    pub(crate) fn last_publish(&self) -> Option<SystemTime> {
+        match self.last_publish.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(UNIX_EPOCH + core::time::Duration::from_nanos(nanos)),
+        }
+    }
+
+    // Counter of the attached writers or readers, if counted
+    fn peers(&self, mode: MappingMode) -> Option<&AtomicU32> {
+        match mode {
+            #[cfg(feature = "topic_stats")]
+            MappingMode::Read => Some(&self.readers),
+            #[cfg(not(feature = "topic_stats"))]
+            MappingMode::Read => None,
+            MappingMode::Write => Some(&self.writers),
+        }
+    }
+
+    fn on_publish(&self) {
+        self.last_publish.store(now_nanos(), Ordering::Relaxed);
+        self.sequence.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "topic_stats")]
+        self.publishes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "topic_stats")]
+    fn on_read(&self) {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        let last_publish = self.last_publish.load(Ordering::Relaxed);
+        if last_publish != 0 {
+            let age = now_nanos().saturating_sub(last_publish);
+            self.max_age.fetch_max(age, Ordering::Relaxed);
+        }
+    }
+
+    /// Return the header as byte slice, e.g. for writing it to a file descriptor
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        // Safety: TopicHeader is repr(C) without padding bytes and lives as long as self
+        unsafe {
+            core::slice::from_raw_parts(
+                (self as *const Self) as *const u8,
+                size_of::<TopicHeader>(),
+            )
+        }
+    }
+}
+
+// Current time in nanoseconds since the UNIX epoch
+fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+// Copy the given name into the buffer, panicking if it does not fit
+fn copy_name(name: &str, buffer: &mut [u8; TOPIC_HEADER_NAME_SIZE]) -> u32 {
+    let bytes = name.as_bytes();
+    assert!(
+        bytes.len() <= TOPIC_HEADER_NAME_SIZE,
+        "name {name} exceeds maximal size of {TOPIC_HEADER_NAME_SIZE}"
+    );
+    buffer[..bytes.len()].copy_from_slice(bytes);
+    bytes.len() as u32
+}
+
+fn name_from_bytes(buffer: &[u8; TOPIC_HEADER_NAME_SIZE], len: u32) -> String {
+    let len = (len as usize).min(TOPIC_HEADER_NAME_SIZE);
+    String::from_utf8_lossy(&buffer[..len]).into_owned()
+}
+
 // Managed pointer to T with run-time type check and access control checks
 //
 // Provides lock-free access control, panics on multiple write or mixed read/write access
@@ -40,6 +199,9 @@ pub enum MappingMode {
 // 6. Runtime access check & type check prevent API misuse
 // 7. Runtime check for write call coupled with send (panics on missed send)
 //
+// If `header` is not null, it points to the [TopicHeader] of the mapping,
+// which is updated upon mapping and publication.
+//
 // Implementation details:
 // lock_state = u16::MAX - locked for writing
 // lock_state in (0; u16::MAX) - locked for reading (<lock_state> active readers)
@@ -51,6 +213,7 @@ pub(crate) struct ReadWriteAccessControlPtr {
     pub(crate) size: usize,
     pub(crate) lock_state: AtomicU16,
     pub(crate) ptr: AtomicPtr<()>,
+    pub(crate) header: AtomicPtr<TopicHeader>,
     pub(crate) writable: AtomicBool,
 }
 
@@ -63,11 +226,25 @@ impl ReadWriteAccessControlPtr {
                 "type size is too big"
             );
         }
+        Self::unmapped::<T>(mapping_mode)
+    }
+
+    // Same as [Self::new_unmapped], but checking the type size at runtime
+    //
+    // This is for generic code instantiated for types which are not necessarily mapped,
+    // e.g. all types of a type registry.
+    pub(crate) fn new_unmapped_checked<T: 'static>(mapping_mode: MappingMode) -> Self {
+        assert!(size_of::<T>() != 0, "zero-sized type is not allowed");
+        Self::unmapped::<T>(mapping_mode)
+    }
+
+    fn unmapped<T: 'static>(mapping_mode: MappingMode) -> Self {
         Self {
             type_id: TypeId::of::<T>(),
             size: size_of::<T>(),
             lock_state: AtomicU16::new(0),
             ptr: AtomicPtr::new(ptr::null_mut()),
+            header: AtomicPtr::new(ptr::null_mut()),
             writable: AtomicBool::new(matches!(mapping_mode, MappingMode::Write)),
         }
     }
@@ -76,6 +253,25 @@ impl ReadWriteAccessControlPtr {
         self.ptr.load(Ordering::Relaxed)
     }
 
+    fn header(&self) -> Option<&TopicHeader> {
+        // Safety: the header is either null or mapped for the 'static lifetime
+        unsafe { self.header.load(Ordering::Relaxed).as_ref() }
+    }
+
+    /// Count a reader or writer attached to the topic in its header, if any
+    pub(crate) fn attach(&self, mode: MappingMode) {
+        if let Some(peers) = self.header().and_then(|header| header.peers(mode)) {
+            peers.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Remove a reader or writer attached by [Self::attach] from the count in the header, if any
+    pub(crate) fn detach(&self, mode: MappingMode) {
+        if let Some(peers) = self.header().and_then(|header| header.peers(mode)) {
+            peers.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
     fn lock_read(&self) {
         let mut state = self.lock_state.load(Ordering::Relaxed);
         loop {
@@ -168,6 +364,10 @@ impl<T> MappedPtrReadGuard<T> {
     fn new(mapped_ptr: Arc<ReadWriteAccessControlPtr>) -> Self {
         mapped_ptr.lock_read();
         fence(Ordering::Acquire);
+        #[cfg(feature = "topic_stats")]
+        if let Some(header) = mapped_ptr.header() {
+            header.on_read();
+        }
         Self {
             mapped_ptr,
             _type: PhantomData,
@@ -175,6 +375,15 @@ impl<T> MappedPtrReadGuard<T> {
     }
 }
 
+impl<T> MappedPtrReadGuard<T> {
+    /// Sequence number of the sample, if the topic has a header
+    pub fn sequence(&self) -> Option<u64> {
+        self.mapped_ptr
+            .header()
+            .map(|header| header.sequence.load(Ordering::Relaxed))
+    }
+}
+
 impl<T> Drop for MappedPtrReadGuard<T> {
     fn drop(&mut self) {
         self.mapped_ptr.unlock_read();
@@ -207,6 +416,9 @@ impl<T> MappedPtrWriteGuard<T> {
 
     pub fn send(self) {
         fence(Ordering::Release);
+        if let Some(header) = self.mapped_ptr.header() {
+            header.on_publish();
+        }
         self.mapped_ptr.unlock_write();
     }
 }
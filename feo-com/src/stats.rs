@@ -0,0 +1,41 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Periodic reporting of topic statistics via feo-tracing
+//!
+//! For each topic, every statistics value is emitted as a counter named after the topic,
+//! e.g. `topic_stats feo/com/vehicle/speed publishes`.
+
+use crate::interface::ComBackend;
+use crate::introspection::list_topics;
+use alloc::format;
+use core::time::Duration;
+use feo_tracing::counter;
+use std::thread;
+
+/// Interval between two reports of the topic statistics
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Spawn a thread periodically reporting the statistics of all topics of the given backend
+pub(crate) fn spawn_reporter(backend: ComBackend) {
+    thread::spawn(move || loop {
+        thread::sleep(REPORT_INTERVAL);
+        report(backend);
+    });
+}
+
+// Emit the current statistics of all topics of the given backend
+fn report(backend: ComBackend) {
+    for info in list_topics(backend) {
+        let Some(stats) = info.stats else {
+            continue;
+        };
+        let name = |value: &str| format!("topic_stats {} {value}", info.topic);
+        counter!(name("publishes").as_str(), stats.publishes);
+        counter!(name("reads").as_str(), stats.reads);
+        if let Some(max_age) = stats.max_age {
+            counter!(name("max_age_us").as_str(), max_age.as_micros() as u64);
+        }
+    }
+}
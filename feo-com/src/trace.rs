@@ -0,0 +1,17 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracing of com reads and writes via feo-tracing
+
+use feo_tracing::{event, Level};
+
+/// Emit reading the given topic
+pub(crate) fn read(topic: &str) {
+    event!(name: "com read", Level::DEBUG, topic);
+}
+
+/// Emit acquiring a buffer to write to the given topic
+pub(crate) fn write(topic: &str) {
+    event!(name: "com write", Level::DEBUG, topic);
+}
@@ -0,0 +1,111 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Print duration statistics of the spans in a trace captured by feo-tracer
+
+use anyhow::Error;
+use argh::FromArgs;
+use feo_tracer::stats::{self, SpanStats, CYCLE_SPAN};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(FromArgs)]
+#[argh(help_triggers("-h", "--help", "help"))]
+/// Print the count, durations and start jitter of the spans in a Perfetto trace, per span name
+struct Args {
+    #[argh(positional, description = "perfetto trace written by feo-tracer")]
+    trace: PathBuf,
+
+    #[argh(
+        option,
+        short = 'c',
+        default = "CYCLE_SPAN.to_string()",
+        description = "name of the span marking the cycle start (default: task chain)"
+    )]
+    cycle_span: String,
+
+    #[argh(switch, short = 'j', description = "print JSON instead of a table")]
+    json: bool,
+}
+
+fn main() -> Result<(), Error> {
+    let Args {
+        trace,
+        cycle_span,
+        json,
+    } = argh::from_env();
+
+    let trace = stats::read_trace(&trace)?;
+    let stats = stats::analyze(&trace, &cycle_span);
+
+    if json {
+        print_json(&stats)?;
+    } else {
+        print_table(&stats);
+    }
+    Ok(())
+}
+
+/// Print one row per span name, durations in microseconds
+fn print_table(stats: &[SpanStats]) {
+    let width = stats
+        .iter()
+        .map(|span| span.name.chars().count())
+        .chain(["span".len()])
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{:<width$} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10} {:>12} {:>10}",
+        "span", "count", "min", "mean", "p50", "p99", "max", "start mean", "jitter"
+    );
+    for span in stats {
+        let (start_mean, jitter) = match &span.start {
+            Some(start) => (micros(start.mean), micros(start.jitter)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:<width$} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10} {:>12} {:>10}",
+            span.name,
+            span.count,
+            micros(span.min),
+            micros(span.mean),
+            micros(span.p50),
+            micros(span.p99),
+            micros(span.max),
+            start_mean,
+            jitter
+        );
+    }
+    println!("(durations in µs)");
+}
+
+/// Print an array with one object per span name, durations in microseconds
+fn print_json(stats: &[SpanStats]) -> Result<(), Error> {
+    let us = |duration: Duration| duration.as_nanos() as f64 / 1000.0;
+    let stats = stats
+        .iter()
+        .map(|span| {
+            json!({
+                "name": span.name,
+                "count": span.count,
+                "min_us": us(span.min),
+                "mean_us": us(span.mean),
+                "p50_us": us(span.p50),
+                "p99_us": us(span.p99),
+                "max_us": us(span.max),
+                "start_mean_us": span.start.as_ref().map(|start| us(start.mean)),
+                "start_jitter_us": span.start.as_ref().map(|start| us(start.jitter)),
+            })
+        })
+        .collect::<Vec<_>>();
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+    Ok(())
+}
+
+/// Format a duration in microseconds
+fn micros(duration: Duration) -> String {
+    format!("{:.1}", duration.as_nanos() as f64 / 1000.0)
+}
@@ -0,0 +1,385 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Writer of the Chrome trace event JSON format
+//!
+//! The output is a JSON array of trace events, loadable e.g. by
+//! [perfetto.dev](https://ui.perfetto.dev) or `chrome://tracing`.
+
+use crate::data::{
+    CounterValue, FieldValue, RecordData, RecordEventInfo, Thread, TraceRecord, DROPPED_MARKER,
+};
+use crate::TraceWriter;
+use anyhow::{bail, Error};
+use feo_log::{error, info};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::time::UNIX_EPOCH;
+
+/// Span
+#[derive(Debug)]
+struct Span {
+    /// Thread in which the span was created
+    tid: u32,
+    /// Name of the span
+    name: String,
+    /// Additional attributes
+    info: RecordEventInfo,
+}
+
+/// Chrome JSON writer
+pub struct Chrome<W: io::Write> {
+    writer: (W, u64),
+    spans: HashMap<(u32, u64), Span>,
+    /// Threads whose name has been written, per process and thread id
+    threads: HashSet<(u32, u32)>,
+}
+
+impl<W: io::Write> Drop for Chrome<W> {
+    fn drop(&mut self) {
+        // Terminate the array of events
+        let end = if self.writer.1 == 0 { "[]\n" } else { "\n]\n" };
+        if let Err(e) = self.write(end) {
+            error!("Failed to terminate JSON output: {e:?}");
+        }
+        info!(
+            "Dropping chrome writer. Wrote {} bytes",
+            human_bytes::human_bytes(self.writer.1 as f64)
+        );
+    }
+}
+
+impl<W: io::Write> Chrome<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: (writer, 0),
+            spans: HashMap::new(),
+            threads: HashSet::new(),
+        }
+    }
+}
+
+impl<W: io::Write> TraceWriter for Chrome<W> {
+    fn on_packet(&mut self, message: TraceRecord) -> Result<(), Error> {
+        let pid = message.process.id;
+        let tid = message.thread.as_ref().map(|thread| thread.id);
+        // Timestamps are given in microseconds
+        let ts = message.timestamp.duration_since(UNIX_EPOCH)?.as_nanos() as f64 / 1000.0;
+
+        if let Some(thread) = &message.thread {
+            self.describe_thread(pid, thread)?;
+        }
+
+        // Map record to event, as in the perfetto output
+        let data = match message.data {
+            RecordData::Record { span, info } => RecordData::Event {
+                parent_span: Some(span),
+                name: "".to_string(),
+                info,
+            },
+            data => data,
+        };
+
+        match data {
+            RecordData::Exec => {
+                if let Some(name) = message.process.name {
+                    self.append(json!({
+                        "ph": "M",
+                        "name": "process_name",
+                        "pid": pid,
+                        "tid": pid,
+                        "args": { "name": name },
+                    }))?;
+                }
+            }
+            RecordData::Exit => {
+                // Remove all spans and threads that belong to the process
+                self.spans.retain(|(span_pid, _), _| *span_pid != pid);
+                self.threads.retain(|(thread_pid, _)| *thread_pid != pid);
+            }
+            RecordData::NewSpan { id, name, info } => {
+                let Some(tid) = tid else {
+                    bail!("missing thread info in new span");
+                };
+                self.spans.insert((pid, id), Span { tid, name, info });
+            }
+            RecordData::EnterSpan { id } => {
+                let Some(span) = self.spans.get(&(pid, id)) else {
+                    return Ok(());
+                };
+                let event = json!({
+                    "ph": "B",
+                    "name": span.name,
+                    "pid": pid,
+                    "tid": span.tid,
+                    "ts": ts,
+                    "args": args(&span.info),
+                });
+                let event = with_flows(event, &span.info);
+                self.append(event)?;
+            }
+            RecordData::ExitSpan { id } => {
+                let Some(span) = self.spans.remove(&(pid, id)) else {
+                    return Ok(());
+                };
+                self.append(json!({
+                    "ph": "E",
+                    "name": span.name,
+                    "pid": pid,
+                    "tid": span.tid,
+                    "ts": ts,
+                }))?;
+            }
+            RecordData::Counter { name, value } => {
+                let value = match value {
+                    CounterValue::Int(value) => json!(value),
+                    CounterValue::Double(value) => json!(value),
+                };
+                self.append(json!({
+                    "ph": "C",
+                    "name": name,
+                    "pid": pid,
+                    "tid": pid,
+                    "ts": ts,
+                    "args": { "value": value },
+                }))?;
+            }
+            RecordData::Dropped { packets } => {
+                // Mark the loss on the thread, or on the process if the thread is unknown
+                let scope = if tid.is_some() { "t" } else { "p" };
+                self.append(json!({
+                    "ph": "i",
+                    "s": scope,
+                    "name": DROPPED_MARKER,
+                    "pid": pid,
+                    "tid": tid.unwrap_or(pid),
+                    "ts": ts,
+                    "args": { "packets": packets },
+                }))?;
+            }
+            RecordData::Record { .. } => unreachable!(),
+            RecordData::Event { name, info, .. } => {
+                let Some(tid) = tid else {
+                    bail!("missing thread info in event");
+                };
+                let event = json!({
+                    "ph": "i",
+                    "s": "t",
+                    "name": name,
+                    "pid": pid,
+                    "tid": tid,
+                    "ts": ts,
+                    "args": args(&info),
+                });
+                self.append(with_flows(event, &info))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: io::Write> Chrome<W> {
+    /// Write the name of a thread when it first appears
+    fn describe_thread(&mut self, pid: u32, thread: &Thread) -> Result<(), Error> {
+        let Some(name) = thread.name.as_deref() else {
+            return Ok(());
+        };
+        if !self.threads.insert((pid, thread.id)) {
+            return Ok(());
+        }
+        self.append(json!({
+            "ph": "M",
+            "name": "thread_name",
+            "pid": pid,
+            "tid": thread.id,
+            "args": { "name": name },
+        }))
+    }
+
+    /// Append an event to the array of events
+    fn append(&mut self, event: Value) -> Result<(), Error> {
+        let separator = if self.writer.1 == 0 { "[\n" } else { ",\n" };
+        self.write(separator)?;
+        self.write(&event.to_string())?;
+        Ok(())
+    }
+
+    fn write(&mut self, data: &str) -> io::Result<()> {
+        self.writer.0.write_all(data.as_bytes())?;
+        self.writer.1 += data.len() as u64;
+        Ok(())
+    }
+}
+
+/// Bind the event to the flow given by the flow fields of a span or event
+///
+/// A flow field continues the flow from the previous event with the same flow id
+/// and leads it on to the next one, a terminating flow field only continues it.
+/// Only the first flow of a span or event is represented.
+fn with_flows(mut event: Value, info: &RecordEventInfo) -> Value {
+    let flow = match info.flow_ids().next() {
+        Some(id) => Some((id, true)),
+        None => info.terminating_flow_ids().next().map(|id| (id, false)),
+    };
+    if let (Some((id, flow_out)), Value::Object(event)) = (flow, &mut event) {
+        event.insert("bind_id".to_string(), json!(id));
+        event.insert("flow_in".to_string(), json!(true));
+        event.insert("flow_out".to_string(), json!(flow_out));
+    }
+    event
+}
+
+/// Create an object with one member per field
+fn args(info: &RecordEventInfo) -> Value {
+    let args = info
+        .fields
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                FieldValue::Int(value) => json!(value),
+                FieldValue::UInt(value) => json!(value),
+                FieldValue::Double(value) => json!(value),
+                FieldValue::Bool(value) => json!(value),
+                FieldValue::String(value) => json!(value),
+            };
+            (name.clone(), value)
+        })
+        .collect::<Map<_, _>>();
+    Value::Object(args)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::Process;
+    use feo_tracing::{FLOW_FIELD, TERMINATING_FLOW_FIELD};
+    use std::time::Duration;
+
+    const PID: u32 = 7;
+    const TID: u32 = 8;
+
+    fn record(micros: u64, data: RecordData) -> TraceRecord {
+        TraceRecord::new(
+            UNIX_EPOCH + Duration::from_micros(micros),
+            Process {
+                id: PID,
+                name: Some("process".into()),
+            },
+            Some(Thread {
+                id: TID,
+                name: Some("thread".into()),
+            }),
+            data,
+        )
+    }
+
+    fn info(fields: &[(&str, FieldValue)]) -> RecordEventInfo {
+        RecordEventInfo {
+            fields: fields
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+        }
+    }
+
+    /// Write the given records and parse the output
+    fn write(records: impl IntoIterator<Item = TraceRecord>) -> Vec<Value> {
+        let mut output = Vec::new();
+        let mut chrome = Chrome::new(&mut output);
+        for record in records {
+            chrome.on_packet(record).unwrap();
+        }
+        drop(chrome);
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut output = Vec::new();
+        drop(Chrome::new(&mut output));
+        assert_eq!(output, b"[]\n");
+        assert!(write([]).is_empty());
+    }
+
+    #[test]
+    fn test_events() {
+        let events = write([
+            record(
+                1,
+                RecordData::NewSpan {
+                    id: 1,
+                    name: "step".into(),
+                    info: info(&[
+                        ("activity", FieldValue::UInt(3)),
+                        (FLOW_FIELD, FieldValue::UInt(42)),
+                    ]),
+                },
+            ),
+            record(2, RecordData::EnterSpan { id: 1 }),
+            record(
+                3,
+                RecordData::Counter {
+                    name: "queue".into(),
+                    value: CounterValue::Int(5),
+                },
+            ),
+            record(4, RecordData::Dropped { packets: 2 }),
+            record(5, RecordData::ExitSpan { id: 1 }),
+            record(
+                6,
+                RecordData::Event {
+                    parent_span: None,
+                    name: "step ready".into(),
+                    info: info(&[(TERMINATING_FLOW_FIELD, FieldValue::UInt(42))]),
+                },
+            ),
+        ]);
+
+        let phases: Vec<_> = events.iter().map(|event| event["ph"].clone()).collect();
+        assert_eq!(phases, ["M", "B", "C", "i", "E", "i"]);
+
+        // The thread is named on its first appearance
+        assert_eq!(events[0]["name"], "thread_name");
+        assert_eq!(events[0]["args"]["name"], "thread");
+
+        // The span starts the flow, the event terminates it
+        let begin = &events[1];
+        assert_eq!(begin["name"], "step");
+        assert_eq!((&begin["pid"], &begin["tid"]), (&json!(PID), &json!(TID)));
+        assert_eq!(begin["ts"], 2.0);
+        assert_eq!(begin["args"]["activity"], 3);
+        assert_eq!(begin["bind_id"], 42);
+        assert_eq!(begin["flow_in"], true);
+        assert_eq!(begin["flow_out"], true);
+        let ready = &events[5];
+        assert_eq!(ready["name"], "step ready");
+        assert_eq!(ready["bind_id"], 42);
+        assert_eq!(ready["flow_in"], true);
+        assert_eq!(ready["flow_out"], false);
+
+        assert_eq!(events[2]["name"], "queue");
+        assert_eq!(events[2]["args"]["value"], 5);
+
+        assert_eq!(events[3]["name"], DROPPED_MARKER);
+        assert_eq!(events[3]["s"], "t");
+        assert_eq!(events[3]["args"]["packets"], 2);
+
+        assert_eq!(events[4]["name"], "step");
+        assert_eq!(events[4]["ts"], 5.0);
+        assert!(events[4].get("bind_id").is_none());
+    }
+
+    #[test]
+    fn test_process_name() {
+        let mut exec = record(0, RecordData::Exec);
+        exec.thread = None;
+        let events = write([exec]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["ph"], "M");
+        assert_eq!(events[0]["name"], "process_name");
+        assert_eq!(events[0]["args"]["name"], "process");
+    }
+}
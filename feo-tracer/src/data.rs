@@ -6,6 +6,7 @@ use crate::io::ThreadNameCache;
 use anyhow::Error;
 use feo_tracing::protocol;
 use feo_tracing::protocol::EventInfo;
+use feo_tracing::{FLOW_FIELD, TERMINATING_FLOW_FIELD};
 use std::time;
 use std::time::SystemTime;
 
@@ -13,10 +14,13 @@ pub type ProcessId = u32;
 pub type ThreadId = u32;
 pub type Id = u64;
 
+/// Name of the marker inserted where trace packets were lost
+pub const DROPPED_MARKER: &str = "trace data lost";
+
 /// A process identifier
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Process {
-    /// Process ID
+    /// Process ID, or a key assigned by the tracer if the process id of the client is unknown
     pub id: ProcessId,
     /// Process name
     pub name: Option<String>,
@@ -75,7 +79,7 @@ pub enum RecordData {
         info: RecordEventInfo,
     },
     /// Record added to span
-    Record { span: Id },
+    Record { span: Id, info: RecordEventInfo },
     /// Event emitted
     Event {
         /// Parent span of the event
@@ -87,56 +91,116 @@ pub enum RecordData {
     EnterSpan { id: Id },
     /// Span exited
     ExitSpan { id: Id },
+    /// Counter value emitted
+    Counter { name: String, value: CounterValue },
+    /// Trace packets lost by the client or while decoding
+    Dropped { packets: u64 },
+}
+
+/// Value of a counter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CounterValue {
+    Int(i64),
+    Double(f64),
 }
 
 impl From<protocol::TraceData> for RecordData {
     fn from(trace_data: protocol::TraceData) -> Self {
         match trace_data {
-            protocol::TraceData::NewSpan {
+            protocol::TraceData::NewSpan { id, name, info } => RecordData::NewSpan {
                 id,
-                name,
-                name_len,
-                info,
-            } => {
-                let record_info: RecordEventInfo = info.into();
-                RecordData::NewSpan {
-                    id,
-                    name: String::from_utf8_lossy(&name[0..name_len]).to_string(),
-                    info: record_info,
-                }
-            }
-            protocol::TraceData::Record { span } => RecordData::Record { span },
+                name: name.as_str().to_string(),
+                info: info.into(),
+            },
+            protocol::TraceData::Record { span, info } => RecordData::Record {
+                span,
+                info: info.into(),
+            },
             protocol::TraceData::Event {
                 parent_span,
                 name,
-                name_len,
                 info,
-            } => {
-                let record_info: RecordEventInfo = info.into();
-                RecordData::Event {
-                    parent_span,
-                    name: String::from_utf8_lossy(&name[0..name_len]).to_string(),
-                    info: record_info,
-                }
-            }
+            } => RecordData::Event {
+                parent_span,
+                name: name.as_str().to_string(),
+                info: info.into(),
+            },
             protocol::TraceData::Enter { span } => RecordData::EnterSpan { id: span },
             protocol::TraceData::Exit { span } => RecordData::ExitSpan { id: span },
+            protocol::TraceData::Counter { name, value } => RecordData::Counter {
+                name: name.as_str().to_string(),
+                value: match value {
+                    protocol::CounterValue::I64(value) => CounterValue::Int(value),
+                    protocol::CounterValue::F64(value) => CounterValue::Double(value),
+                },
+            },
+            protocol::TraceData::Dropped { packets } => RecordData::Dropped { packets },
         }
     }
 }
 
+/// Fields attached to a span or event
 #[derive(Debug, Default)]
 pub struct RecordEventInfo {
-    pub name: Option<String>,
-    pub value: String,
+    /// Names and values of the fields
+    pub fields: Vec<(String, FieldValue)>,
+}
+
+impl RecordEventInfo {
+    /// Ids of the flows started or continued by the span or event
+    ///
+    /// A field named [`FLOW_FIELD`] starts or continues a flow.
+    pub fn flow_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.integer_fields(FLOW_FIELD)
+    }
+
+    /// Ids of the flows ended by the span or event, given by fields named [`TERMINATING_FLOW_FIELD`]
+    pub fn terminating_flow_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.integer_fields(TERMINATING_FLOW_FIELD)
+    }
+
+    /// Non-negative integer values of the fields with the given name
+    fn integer_fields(&self, name: &'static str) -> impl Iterator<Item = u64> + '_ {
+        self.fields
+            .iter()
+            .filter(move |(field, _)| field == name)
+            .filter_map(|(_, value)| match value {
+                FieldValue::UInt(id) => Some(*id),
+                FieldValue::Int(id) => u64::try_from(*id).ok(),
+                _ => None,
+            })
+    }
+}
+
+/// Typed value of a field
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Int(i64),
+    UInt(u64),
+    Double(f64),
+    Bool(bool),
+    /// String value or debug representation of a value
+    String(String),
 }
 
 impl From<EventInfo> for RecordEventInfo {
     fn from(info: EventInfo) -> Self {
-        let name_len = info.name_len;
-        let name = name_len.map(|len| String::from_utf8_lossy(&info.name[0..len]).to_string());
-        let value = String::from_utf8_lossy(&info.value[0..info.value_len]).to_string();
-        RecordEventInfo { name, value }
+        let fields = info
+            .fields()
+            .map(|field| {
+                let value = match field.value {
+                    protocol::FieldValue::I64(value) => FieldValue::Int(value),
+                    protocol::FieldValue::U64(value) => FieldValue::UInt(value),
+                    protocol::FieldValue::F64(value) => FieldValue::Double(value),
+                    protocol::FieldValue::Bool(value) => FieldValue::Bool(value),
+                    protocol::FieldValue::Str(value) | protocol::FieldValue::Debug(value) => {
+                        FieldValue::String(value.as_str().to_string())
+                    }
+                };
+                (field.name.as_str().to_string(), value)
+            })
+            .collect();
+        RecordEventInfo { fields }
     }
 }
 
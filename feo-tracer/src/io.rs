@@ -6,22 +6,89 @@
 
 use crate::data;
 use anyhow::{Context, Error};
+use core::future::pending;
 use feo_log::{debug, info, warn};
+use feo_tracing::endpoint::Endpoint;
 use feo_tracing::protocol;
 use postcard::accumulator::{CobsAccumulator, FeedResult};
 use std::collections::HashMap;
 use std::fs;
-use std::io::ErrorKind;
 use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::SystemTime;
-use tokio::net::{UnixListener, UnixStream};
-use tokio::sync::mpsc;
-use tokio::task;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::{mpsc, watch};
+use tokio::{select, task};
 
 /// Size of the buffer (bytes) used for deserializing incoming trace packets
 const READ_BUFFER_SIZE: usize = 32 * protocol::MAX_PACKET_SIZE;
 
-pub async fn listen(path: &Path, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
+/// Next key identifying a client whose process id is unknown to the tracer
+///
+/// Starts above the largest possible Linux process id, so that the keys do not collide
+/// with the process ids of local clients.
+static NEXT_CLIENT_KEY: AtomicU32 = AtomicU32::new(1 << 31);
+
+/// Listen for trace packets of clients connecting to the given endpoint
+///
+/// The current filter directives are sent to each client, initially if not empty and on every change.
+pub async fn listen(
+    endpoint: &Endpoint,
+    sink: mpsc::Sender<data::TraceRecord>,
+    filter: watch::Receiver<String>,
+) -> Result<(), Error> {
+    match endpoint {
+        Endpoint::Unix(path) => listen_unix(path, sink, filter).await,
+        Endpoint::Tcp(address) => {
+            // Bind
+            info!("Binding to {endpoint}");
+            let listener = TcpListener::bind(address)
+                .await
+                .with_context(|| format!("failed to bind to {endpoint}"))?;
+
+            // Listen
+            info!("Listening on {endpoint}");
+            loop {
+                let (socket, peer) = listener
+                    .accept()
+                    .await
+                    .context("failed to accept connection")?;
+
+                debug!("Accepted connection from {peer}");
+                // The process id of the peer is unknown and the one announced by the client may be
+                // ambiguous, e.g. in another container, so the client is assigned a unique key
+                let (socket, writer) = socket.into_split();
+                let control = Control::new(writer, filter.clone());
+                task::spawn(connection(socket, Some(control), None, sink.clone()));
+            }
+        }
+    }
+}
+
+/// Read a dump of the flight recorder of feo-tracing
+pub async fn read(path: &Path, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
+    info!("Reading {path:?}");
+    let file = File::open(path)
+        .await
+        .with_context(|| format!("failed to open {path:?}"))?;
+    // The dump may stem from another pid namespace or a process no longer running
+    connection(file, None::<Control<tokio::io::Sink>>, None, sink).await;
+    Ok(())
+}
+
+async fn listen_unix(
+    path: &Path,
+    sink: mpsc::Sender<data::TraceRecord>,
+    filter: watch::Receiver<String>,
+) -> Result<(), Error> {
+    // Check if socket is present and remove if necessary
+    if path.exists() {
+        debug!("Removing stale socket at {path:?}");
+        fs::remove_file(path).with_context(|| format!("failed to remove {path:?}"))?;
+    }
+
     // Bind
     info!("Binding to {path:?}");
     let listener = UnixListener::bind(path)?;
@@ -35,56 +102,145 @@ pub async fn listen(path: &Path, sink: mpsc::Sender<data::TraceRecord>) -> Resul
             .context("failed to accept connection")?;
 
         debug!("Accepted connection");
-        task::spawn(connection(socket, sink.clone()));
+        // Retrieve the PID of the peer
+        let pid = socket.peer_cred()?.pid().map(|pid| pid as u32);
+        let (socket, writer) = socket.into_split();
+        let control = Control::new(writer, filter.clone());
+        task::spawn(connection(socket, Some(control), pid, sink.clone()));
+    }
+}
+
+/// Sending side of a connection, forwarding the filter directives to the client
+struct Control<W> {
+    writer: W,
+    filter: watch::Receiver<String>,
+}
+
+impl<W: AsyncWrite + Unpin> Control<W> {
+    fn new(writer: W, filter: watch::Receiver<String>) -> Self {
+        Self { writer, filter }
+    }
+
+    /// Send the current filter directives to the client
+    async fn send_filter(&mut self) -> Result<(), Error> {
+        let directives = self.filter.borrow_and_update().clone();
+        let mut buffer = [0u8; protocol::MAX_CONTROL_SIZE];
+        let message =
+            postcard::to_slice_cobs(&protocol::Control::SetFilter(&directives), &mut buffer)
+                .context("filter directives too long")?;
+        self.writer.write_all(message).await?;
+        Ok(())
+    }
+}
+
+/// Wait for a change of the filter directives, forever if there is no control channel
+async fn filter_changed<W>(control: Option<&mut Control<W>>) {
+    if let Some(control) = control {
+        if control.filter.changed().await.is_ok() {
+            return;
+        }
+    }
+    pending().await
+}
+
+/// Connected client
+struct Client {
+    /// Key identifying the process in the trace
+    key: u32,
+    name: Option<String>,
+    thread_name_cache: ThreadNameCache,
+}
+
+impl Client {
+    /// Identify a client by the process id of the peer, if known, or by a unique key
+    ///
+    /// The process id announced by a client of unknown process id is only added to its name,
+    /// since clients in different pid namespaces may announce the same id.
+    fn new(peer_pid: Option<u32>, hello: protocol::Hello) -> Self {
+        match peer_pid {
+            Some(pid) => Self {
+                key: pid,
+                // Capture the process name for the peer
+                name: fs::read_to_string(format!("/proc/{pid}/comm"))
+                    .map(|name| name.trim_end().to_string())
+                    .ok(),
+                thread_name_cache: ThreadNameCache::new(pid),
+            },
+            None => {
+                let key = NEXT_CLIENT_KEY.fetch_add(1, Ordering::Relaxed);
+                let pid = hello.pid;
+                let name = match hello.name {
+                    Some(name) => format!("{} (pid {pid})", name.as_str()),
+                    None => format!("pid {pid}"),
+                };
+                Self {
+                    key,
+                    name: Some(name),
+                    // The process may live in another pid namespace, so procfs is not queried
+                    thread_name_cache: ThreadNameCache::disabled(key),
+                }
+            }
+        }
+    }
+
+    /// Create a record of this client without thread information
+    fn record(&self, data: data::RecordData) -> data::TraceRecord {
+        data::TraceRecord {
+            timestamp: SystemTime::now(),
+            process: data::Process {
+                id: self.key,
+                name: self.name.clone(),
+            },
+            thread: None,
+            data,
+        }
     }
 }
 
-async fn connection(socket: UnixStream, sink: mpsc::Sender<data::TraceRecord>) {
-    // Retrieve the PID of the peer
-    let pid = socket.peer_cred().unwrap().pid().unwrap() as u32;
-
-    // Capture the process name for the peer
-    let process_name = fs::read_to_string(format!("/proc/{}/comm", pid))
-        .map(|name| name.trim_end().to_string())
-        .ok();
-
-    info!(
-        "Processing messages from {pid:x} ({})",
-        process_name.as_deref().unwrap_or("")
-    );
-
-    // Send a process exec event
-    sink.send(data::TraceRecord {
-        timestamp: SystemTime::now(),
-        process: data::Process {
-            id: pid,
-            name: process_name.clone(),
-        },
-        thread: None,
-        data: data::RecordData::Exec,
-    })
-    .await
-    .expect("channel error");
-
-    // Create a cache for the thread names in order to avoid frequent reads of procfs entries
-    let mut thread_name_cache = ThreadNameCache::new(pid);
+async fn connection<S: AsyncRead + Unpin, W: AsyncWrite + Unpin>(
+    mut socket: S,
+    mut control: Option<Control<W>>,
+    peer_pid: Option<u32>,
+    sink: mpsc::Sender<data::TraceRecord>,
+) {
+    // The client is identified by the first packet
+    let mut client: Option<Client> = None;
+
+    // Send the filter directives given at startup
+    if let Some(control) = control.as_mut() {
+        if !control.filter.borrow().is_empty() {
+            if let Err(e) = control.send_filter().await {
+                warn!("Failed to send filter directives: {e:?}");
+            }
+        }
+    }
 
     // Buffers for incoming packets and postcard deserialization
     let mut read_buffer = [0u8; READ_BUFFER_SIZE];
     let mut cobs_buffer: CobsAccumulator<READ_BUFFER_SIZE> = CobsAccumulator::new();
 
     'deser: loop {
-        socket.readable().await.expect("socket error");
+        let pid = client.as_ref().map(|client| client.key).or(peer_pid);
+        let pid = pid.map(|pid| format!("{pid}")).unwrap_or_default();
+
+        let read = select! {
+            read = socket.read(&mut read_buffer) => read,
+            () = filter_changed(control.as_mut()) => {
+                if let Some(control) = control.as_mut() {
+                    if let Err(e) = control.send_filter().await {
+                        warn!("Failed to send filter directives to {pid}: {e:?}");
+                    }
+                }
+                continue;
+            }
+        };
 
-        let len = match socket.try_read(&mut read_buffer) {
+        let len = match read {
             Ok(0) => {
                 info!("Connection from {pid} closed");
                 break;
             }
             Ok(len) => len,
-            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                continue;
-            }
             Err(e) => {
                 warn!("Failed to receive data from {pid}: {e:?}. Closing connection");
                 break;
@@ -95,21 +251,54 @@ async fn connection(socket: UnixStream, sink: mpsc::Sender<data::TraceRecord>) {
         let mut remaining = buffer;
 
         while !remaining.is_empty() {
+            let Some(client) = client.as_mut() else {
+                remaining = match cobs_buffer.feed_ref::<protocol::Hello>(remaining) {
+                    FeedResult::Consumed => break,
+                    FeedResult::Success { data, remaining } => {
+                        let new_client = Client::new(peer_pid, data);
+                        info!(
+                            "Processing messages from {:x} ({})",
+                            new_client.key,
+                            new_client.name.as_deref().unwrap_or("")
+                        );
+
+                        // Send a process exec event
+                        sink.send(new_client.record(data::RecordData::Exec))
+                            .await
+                            .expect("channel error");
+                        client = Some(new_client);
+                        remaining
+                    }
+                    FeedResult::OverFull(_) | FeedResult::DeserError(_) => {
+                        warn!("Failed to identify client {pid}. Closing connection");
+                        break 'deser;
+                    }
+                };
+                continue;
+            };
+
             remaining = match cobs_buffer.feed_ref::<protocol::TracePacket>(remaining) {
                 FeedResult::Consumed => break,
                 FeedResult::OverFull(_) => {
                     warn!("Deserialization buffer overflow in {pid}. Closing connection");
                     break 'deser;
                 }
-                FeedResult::DeserError(remaining) => remaining,
+                FeedResult::DeserError(remaining) => {
+                    // Record the loss of the packet in the trace
+                    warn!("Failed to deserialize packet from {pid}");
+                    sink.send(client.record(data::RecordData::Dropped { packets: 1 }))
+                        .await
+                        .expect("channel error");
+                    remaining
+                }
                 FeedResult::Success { data, remaining } => {
                     // Data successfully decoded, add thread and process info
                     // and transmit to sink
                     let packet = match data::decode_packet(
-                        pid,
+                        client.key,
                         data,
-                        &mut thread_name_cache,
-                        process_name.clone(),
+                        &mut client.thread_name_cache,
+                        client.name.clone(),
                     ) {
                         Ok(packet) => packet,
                         Err(e) => {
@@ -125,17 +314,11 @@ async fn connection(socket: UnixStream, sink: mpsc::Sender<data::TraceRecord>) {
     }
 
     // Send a process exit event
-    sink.send(data::TraceRecord {
-        timestamp: SystemTime::now(),
-        process: data::Process {
-            id: pid,
-            name: None,
-        },
-        thread: None,
-        data: data::RecordData::Exit,
-    })
-    .await
-    .expect("channel error");
+    if let Some(client) = client {
+        sink.send(client.record(data::RecordData::Exit))
+            .await
+            .expect("channel error");
+    }
 }
 
 /// Cache for thread names in order to avoid frequent reads of procfs entries.
@@ -145,6 +328,8 @@ pub struct ThreadNameCache {
     pid: u32,
     /// Map of thread names indexed by their TID
     names: HashMap<u32, Option<String>>,
+    /// Query procfs for thread names not cached
+    lookup: bool,
 }
 
 impl<'a> ThreadNameCache {
@@ -153,6 +338,16 @@ impl<'a> ThreadNameCache {
         Self {
             pid,
             names: HashMap::new(),
+            lookup: true,
+        }
+    }
+
+    /// Create a cache for a process whose threads cannot be looked up in procfs
+    pub fn disabled(pid: u32) -> Self {
+        Self {
+            pid,
+            names: HashMap::new(),
+            lookup: false,
         }
     }
 
@@ -161,6 +356,9 @@ impl<'a> ThreadNameCache {
         self.names
             .entry(tid)
             .or_insert_with(|| {
+                if !self.lookup {
+                    return None;
+                }
                 let pid = self.pid;
                 fs::read_to_string(format!("/proc/{pid}/task/{tid}/comm"))
                     .map(|s| s.trim_end().to_string())
@@ -169,3 +367,30 @@ impl<'a> ThreadNameCache {
             .as_deref()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use feo_tracing::protocol::InfoString;
+    use std::process;
+
+    #[test]
+    fn test_client_keys() {
+        let hello = |name: Option<&str>| protocol::Hello {
+            pid: 1,
+            name: name.map(InfoString::new),
+        };
+
+        // Clients of unknown process id announcing the same pid are told apart
+        let first = Client::new(None, hello(Some("agent")));
+        let second = Client::new(None, hello(None));
+        assert_ne!(first.key, second.key);
+        assert!(first.key >= 1 << 31 && second.key >= 1 << 31);
+        assert_eq!(first.name.as_deref(), Some("agent (pid 1)"));
+        assert_eq!(second.name.as_deref(), Some("pid 1"));
+
+        // A local client is identified by its process id
+        let local = Client::new(Some(process::id()), hello(None));
+        assert_eq!(local.key, process::id());
+    }
+}
@@ -4,6 +4,16 @@
 
 //! Central trace collector
 
+use anyhow::Error;
+
+pub mod chrome;
 pub mod data;
 pub mod io;
 pub mod perfetto;
+pub mod stats;
+
+/// Writer of trace records into an output format
+pub trait TraceWriter {
+    /// Write a trace record
+    fn on_packet(&mut self, message: data::TraceRecord) -> Result<(), Error>;
+}
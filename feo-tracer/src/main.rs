@@ -7,22 +7,24 @@
 use anyhow::{bail, Context, Error};
 use argh::FromArgs;
 use core::future::pending;
-use feo_log::{debug, info, LevelFilter};
-use feo_tracer::io::listen;
-use feo_tracer::perfetto;
+use core::str::FromStr;
+use feo_log::{error, info, LevelFilter};
+use feo_tracer::io::{listen, read};
+use feo_tracer::{chrome, perfetto, TraceWriter};
+use feo_tracing::endpoint::Endpoint;
+use feo_tracing::Filter;
+use futures::future::BoxFuture;
 use futures::FutureExt;
 use indicatif_log_bridge::LogWrapper;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::{fs, io};
-use tokio::sync::mpsc;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{mpsc, watch};
 use tokio::{runtime, select, signal, task, time};
 
 /// Progress bar wrapper
 mod progress;
 
-/// Path to the Unix socket
-const UNIX_PACKET_PATH: &str = "/tmp/feo-tracer.sock";
-
 /// Size of the message channel (number of messages) for transmitting decoded trace
 /// packets to the file writer
 const MESSAGE_CHANNEL_SIZE: usize = 256;
@@ -48,6 +50,55 @@ struct Args {
     #[argh(description = "log level")]
     #[argh(option, short = 'l')]
     log_level: Option<LevelFilter>,
+
+    #[argh(
+        description = "endpoint to listen on, unix:<path> or tcp:<address>:<port>, may be repeated (default: $FEO_TRACER_ENDPOINT or unix:/tmp/feo-tracer.sock)"
+    )]
+    #[argh(option, short = 'e')]
+    endpoint: Vec<Endpoint>,
+
+    #[argh(
+        description = "dump of a feo-tracing flight recorder to convert instead of listening, may be repeated"
+    )]
+    #[argh(option, short = 'i')]
+    input: Vec<PathBuf>,
+
+    #[argh(description = "output format, perfetto (default) or json")]
+    #[argh(option, short = 'f', default = "Format::Perfetto")]
+    format: Format,
+
+    #[argh(
+        description = "trace filter directives target[name]=level sent to the traced processes"
+    )]
+    #[argh(option)]
+    filter: Option<String>,
+
+    #[argh(description = "replace the trace filter directives by each line read from stdin")]
+    #[argh(switch)]
+    filter_stdin: bool,
+}
+
+/// Output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Perfetto protobuf trace
+    Perfetto,
+    /// Chrome trace event JSON
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "perfetto" => Ok(Format::Perfetto),
+            "json" => Ok(Format::Json),
+            _ => Err(format!(
+                "unknown format {format}, expected perfetto or json"
+            )),
+        }
+    }
 }
 
 /// Tracer main entry point
@@ -56,6 +107,11 @@ fn main() -> Result<(), Error> {
         duration,
         out,
         log_level,
+        endpoint,
+        input,
+        format,
+        filter,
+        filter_stdin,
     } = argh::from_env();
 
     // Initialize logging
@@ -74,20 +130,41 @@ fn main() -> Result<(), Error> {
 
     let (message_sender, mut message_receiver) = mpsc::channel(MESSAGE_CHANNEL_SIZE);
 
-    // Listen for incoming connections on a socket
+    // Filter directives forwarded to the connected processes
+    let filter = filter.unwrap_or_default();
+    Filter::from_str(&filter)?;
+    let (filter_sender, filter_receiver) = watch::channel(filter);
+
+    // Read the given flight recorder dumps, or else listen for incoming connections on each endpoint.
     // Forward the messages to the message channel.
-    let fan_in_socket = {
+    let fan_in: Vec<BoxFuture<'static, Result<(), Error>>> = if !input.is_empty() {
         let message_sender = message_sender.clone();
-        async move {
-            let path = Path::new(UNIX_PACKET_PATH);
-            // Check if socket is present and remove if necessary
-            if path.exists() {
-                debug!("Removing stale socket at {path:?}");
-                fs::remove_file(path).with_context(|| format!("failed to remove {path:?}"))?;
+        vec![async move {
+            for path in input {
+                read(&path, message_sender.clone()).await?;
             }
-            listen(path, message_sender).await
+            // Close the message channel to complete the output
+            drop(message_sender);
+            pending().await
         }
+        .boxed()]
+    } else {
+        let endpoints = if endpoint.is_empty() {
+            vec![Endpoint::from_env()?]
+        } else {
+            endpoint
+        };
+        endpoints
+            .into_iter()
+            .map(|endpoint| {
+                let message_sender = message_sender.clone();
+                let filter_receiver = filter_receiver.clone();
+                async move { listen(&endpoint, message_sender, filter_receiver).await }.boxed()
+            })
+            .chain(filter_stdin.then(|| read_filters(filter_sender).boxed()))
+            .collect()
     };
+    drop(message_sender);
 
     // Handle incoming messages on the message channel. The channel yields
     // messages from all connected processes.
@@ -100,16 +177,23 @@ fn main() -> Result<(), Error> {
         );
 
         // Wrap writer in a progress bar
-        let writer = progress.add_writer(&format!("perfetto output ({})", out.display()), writer);
+        let name = match format {
+            Format::Perfetto => "perfetto",
+            Format::Json => "json",
+        };
+        let writer = progress.add_writer(&format!("{name} output ({})", out.display()), writer);
 
-        // Create a perfetto writer
-        let mut perfetto = perfetto::Perfetto::new(writer);
+        // Create a writer of the selected format
+        let mut trace_writer: Box<dyn TraceWriter + Send> = match format {
+            Format::Perfetto => Box::new(perfetto::Perfetto::new(writer)),
+            Format::Json => Box::new(chrome::Chrome::new(writer)),
+        };
 
         // Process messages as they arrive
         let process_packets = async move {
             while let Some(message) = message_receiver.recv().await {
                 progress.on_packet(&message);
-                perfetto.on_packet(message)?;
+                trace_writer.on_packet(message)?;
             }
             Ok(())
         };
@@ -136,7 +220,9 @@ fn main() -> Result<(), Error> {
 
     // Wait for all tasks to finish or error
     let run = async {
-        tasks.spawn(fan_in_socket);
+        for fan_in in fan_in {
+            tasks.spawn(fan_in);
+        }
         tasks.spawn(process_messages);
 
         match tasks.join_next().await.expect("no tasks to join") {
@@ -146,10 +232,39 @@ fn main() -> Result<(), Error> {
     };
 
     // Fire up runtime and wait
-    runtime::Builder::new_multi_thread()
+    let runtime = runtime::Builder::new_multi_thread()
         .worker_threads(NUM_THREADS)
         .enable_io()
         .enable_time()
-        .build()?
-        .block_on(run)
+        .build()?;
+    let result = runtime.block_on(run);
+    // Do not wait for the pending read of stdin
+    runtime.shutdown_background();
+    result
+}
+
+/// Read filter directives from stdin, one set per line, and forward them to the connected processes
+async fn read_filters(filter_sender: watch::Sender<String>) -> Result<(), Error> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                let directives = line.trim();
+                match Filter::from_str(directives) {
+                    Ok(_) => {
+                        info!("Setting filter directives '{directives}'");
+                        filter_sender.send_replace(directives.to_string());
+                    }
+                    Err(e) => error!("{e}"),
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                error!("Failed to read filter directives from stdin, ignoring it: {e}");
+                break;
+            }
+        }
+    }
+    // Keep the current directives after the end of the input
+    pending().await
 }
@@ -2,7 +2,10 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::data::{RecordData, RecordEventInfo, Thread, TraceRecord};
+use crate::data::{
+    CounterValue, FieldValue, RecordData, RecordEventInfo, Thread, TraceRecord, DROPPED_MARKER,
+};
+use crate::TraceWriter;
 use anyhow::{bail, Error};
 use feo_log::info;
 use perfetto_model as idl;
@@ -55,6 +58,8 @@ impl Span {
 pub struct Perfetto<W> {
     writer: (W, u64),
     spans: HashMap<(u32, u64), Span>,
+    /// Counter tracks per process and counter name
+    counters: HashMap<(u32, String), TrackUuid>,
     track_uuid: TrackUuid,
     sequence_id: SequenceId,
 }
@@ -77,12 +82,15 @@ impl<W: io::Write> Perfetto<W> {
         Self {
             writer: (writer, 0),
             spans,
+            counters: HashMap::new(),
             track_uuid,
             sequence_id,
         }
     }
+}
 
-    pub fn on_packet(&mut self, message: TraceRecord) -> Result<(), Error> {
+impl<W: io::Write> TraceWriter for Perfetto<W> {
+    fn on_packet(&mut self, message: TraceRecord) -> Result<(), Error> {
         let pid = message.process.id;
         let process = message.process;
         let thread = message.thread;
@@ -91,10 +99,10 @@ impl<W: io::Write> Perfetto<W> {
         // Map record to event. This is unfortunately not possible directly in the match
         // below because the types of the fields differ.
         let data = match message.data {
-            RecordData::Record { span } => RecordData::Event {
+            RecordData::Record { span, info } => RecordData::Event {
                 parent_span: Some(span),
                 name: "".to_string(),
-                info: RecordEventInfo::default(),
+                info,
             },
             data => data,
         };
@@ -102,8 +110,10 @@ impl<W: io::Write> Perfetto<W> {
         match data {
             RecordData::Exec => (),
             RecordData::Exit => {
-                // Remove all spans that belong to the process
+                // Remove all spans and counter tracks that belong to the process
                 self.spans.retain(|_, span| span.pid != pid);
+                self.counters
+                    .retain(|(counter_pid, _), _| *counter_pid != pid);
             }
             RecordData::NewSpan { id, name, info } => {
                 let key = (pid, id);
@@ -128,8 +138,7 @@ impl<W: io::Write> Perfetto<W> {
                     return Ok(());
                 };
 
-                let annotation = debug_annotation(span.info.name.clone(), span.info.value.clone());
-                let debug_annotations = debug_annotations(&[annotation]);
+                let debug_annotations = debug_annotations(&span.info);
                 let thread_track_uuid = span.thread.id;
                 let event = create_event(
                     thread_track_uuid as u64,
@@ -137,6 +146,7 @@ impl<W: io::Write> Perfetto<W> {
                     debug_annotations,
                     Some(idl::track_event::Type::SliceBegin),
                 );
+                let event = with_flows(event, &span.info);
                 let packet = idl::TracePacket {
                     data: Some(idl::trace_packet::Data::TrackEvent(event)),
                     timestamp: Some(timestamp_nanos),
@@ -176,6 +186,89 @@ impl<W: io::Write> Perfetto<W> {
                 self.append(&span.trace)?;
             }
 
+            RecordData::Counter { name, value } => {
+                let mut trace = idl::Trace::default();
+
+                // Describe the counter track on its first value
+                let key = (pid, name);
+                let track_uuid = match self.counters.get(&key) {
+                    Some(track_uuid) => *track_uuid,
+                    None => {
+                        let track_uuid = rand::random();
+                        trace
+                            .packet
+                            .push(self.process_descriptor(pid, process.name.as_deref()));
+                        trace
+                            .packet
+                            .push(self.counter_descriptor(pid, track_uuid, &key.1));
+                        self.counters.insert(key, track_uuid);
+                        track_uuid
+                    }
+                };
+
+                let counter_value_field = match value {
+                    CounterValue::Int(value) => {
+                        idl::track_event::CounterValueField::CounterValue(value)
+                    }
+                    CounterValue::Double(value) => {
+                        idl::track_event::CounterValueField::DoubleCounterValue(value)
+                    }
+                };
+                let track_event = idl::TrackEvent {
+                    counter_value_field: Some(counter_value_field),
+                    ..create_event(
+                        track_uuid,
+                        None,
+                        None,
+                        Some(idl::track_event::Type::Counter),
+                    )
+                };
+                trace.packet.push(idl::TracePacket {
+                    data: Some(idl::trace_packet::Data::TrackEvent(track_event)),
+                    trusted_pid: Some(pid as _),
+                    timestamp: Some(timestamp_nanos),
+                    optional_trusted_packet_sequence_id: Some(self.sequence_id()),
+                    ..Default::default()
+                });
+                self.append(&trace)?;
+            }
+
+            RecordData::Dropped { packets } => {
+                // Mark the loss on the track of the thread, or of the process if the thread is unknown
+                let mut trace = idl::Trace::default();
+                trace
+                    .packet
+                    .push(self.process_descriptor(pid, process.name.as_deref()));
+                let track_uuid = match thread {
+                    Some(thread) => {
+                        trace.packet.push(self.thread_descriptor(
+                            pid,
+                            thread.id,
+                            thread.name.as_deref(),
+                        ));
+                        thread.id as TrackUuid
+                    }
+                    None => self.process_track_uuid(pid),
+                };
+                let info = RecordEventInfo {
+                    fields: vec![("packets".to_string(), FieldValue::UInt(packets))],
+                };
+                let track_event = create_event(
+                    track_uuid,
+                    Some(DROPPED_MARKER),
+                    debug_annotations(&info),
+                    Some(idl::track_event::Type::Instant),
+                );
+                trace.packet.push(idl::TracePacket {
+                    data: Some(idl::trace_packet::Data::TrackEvent(track_event)),
+                    trusted_pid: Some(pid as _),
+                    timestamp: Some(timestamp_nanos),
+                    optional_trusted_packet_sequence_id: Some(self.sequence_id()),
+                    ..Default::default()
+                });
+                self.append(&trace)?;
+            }
+
             RecordData::Record { .. } => unreachable!(),
             RecordData::Event {
                 parent_span,
@@ -185,14 +278,14 @@ impl<W: io::Write> Perfetto<W> {
                 let Some(tid) = thread.as_ref().map(|t| t.id) else {
                     bail!("missing thread info in exit span");
                 };
-                let annotation = debug_annotation(info.name, info.value);
-                let debug_annotations = debug_annotations(&[annotation]);
+                let debug_annotations = debug_annotations(&info);
                 let track_event = create_event(
                     tid as u64,
                     Some(name.as_str()),
                     debug_annotations,
                     Some(idl::track_event::Type::Instant),
                 );
+                let track_event = with_flows(track_event, &info);
                 let packet = perfetto_model::TracePacket {
                     data: Some(idl::trace_packet::Data::TrackEvent(track_event)),
                     trusted_pid: Some(pid as _),
@@ -225,11 +318,14 @@ impl<W: io::Write> Perfetto<W> {
 
         Ok(())
     }
+}
 
+impl<W: io::Write> Perfetto<W> {
     fn process_descriptor(&self, id: u32, name: Option<&str>) -> idl::TracePacket {
         let mut packet = idl::TracePacket::default();
         let process = create_process_descriptor(id, name).into();
-        let track_desc = create_track_descriptor(Some(self.track_uuid), name, process, None);
+        let track_desc =
+            create_track_descriptor(Some(self.process_track_uuid(id)), name, process, None);
         packet.data = Some(idl::trace_packet::Data::TrackDescriptor(track_desc));
         packet
     }
@@ -242,6 +338,24 @@ impl<W: io::Write> Perfetto<W> {
         packet
     }
 
+    /// Describe a counter track of the given process
+    fn counter_descriptor(&self, pid: u32, uuid: TrackUuid, name: &str) -> idl::TracePacket {
+        let track_desc = idl::TrackDescriptor {
+            parent_uuid: Some(self.process_track_uuid(pid)),
+            counter: Some(idl::CounterDescriptor::default()),
+            ..create_track_descriptor(Some(uuid), Some(name), None, None)
+        };
+        idl::TracePacket {
+            data: Some(idl::trace_packet::Data::TrackDescriptor(track_desc)),
+            ..Default::default()
+        }
+    }
+
+    /// Uuid of the track of the given process
+    fn process_track_uuid(&self, pid: u32) -> TrackUuid {
+        self.track_uuid ^ pid as TrackUuid
+    }
+
     /// Append a trace packet to the writer. Serialized into proto and written to the writer.
     fn append(&mut self, packet: &idl::Trace) -> Result<(), Error> {
         let buf = packet.encode_to_vec();
@@ -305,23 +419,43 @@ fn create_event(
     }
 }
 
+/// Link the event to the flows given by the flow fields of a span or event
+fn with_flows(event: idl::TrackEvent, info: &RecordEventInfo) -> idl::TrackEvent {
+    idl::TrackEvent {
+        flow_ids: info.flow_ids().collect(),
+        terminating_flow_ids: info.terminating_flow_ids().collect(),
+        ..event
+    }
+}
+
 #[derive(Default)]
 struct DebugAnnotations {
     annotations: Vec<idl::DebugAnnotation>,
 }
 
-fn debug_annotations(annotations: &[perfetto_model::DebugAnnotation]) -> Option<DebugAnnotations> {
-    Some(DebugAnnotations {
-        annotations: annotations.to_vec(),
-    })
+/// Create one debug annotation per field
+fn debug_annotations(info: &RecordEventInfo) -> Option<DebugAnnotations> {
+    let annotations = info
+        .fields
+        .iter()
+        .map(|(name, value)| debug_annotation(name, value))
+        .collect();
+    Some(DebugAnnotations { annotations })
 }
 
-fn debug_annotation(name: Option<String>, value: String) -> idl::DebugAnnotation {
-    let name_field = name.map(idl::debug_annotation::NameField::Name);
+fn debug_annotation(name: &str, value: &FieldValue) -> idl::DebugAnnotation {
+    let name_field = Some(idl::debug_annotation::NameField::Name(name.to_string()));
+    let value = match value {
+        FieldValue::Int(value) => idl::debug_annotation::Value::IntValue(*value),
+        FieldValue::UInt(value) => idl::debug_annotation::Value::UintValue(*value),
+        FieldValue::Double(value) => idl::debug_annotation::Value::DoubleValue(*value),
+        FieldValue::Bool(value) => idl::debug_annotation::Value::BoolValue(*value),
+        FieldValue::String(value) => idl::debug_annotation::Value::StringValue(value.clone()),
+    };
 
     idl::DebugAnnotation {
         name_field,
-        value: Some(idl::debug_annotation::Value::StringValue(value)),
+        value: Some(value),
         ..Default::default()
     }
 }
@@ -0,0 +1,308 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Duration statistics of the spans in a Perfetto trace written by feo-tracer
+
+use anyhow::{Context, Error};
+use perfetto_model as idl;
+use prost::Message as ProstMessage;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Name of the span marking the start of each cycle, emitted by the scheduler
+pub const CYCLE_SPAN: &str = "task chain";
+
+/// A span of the trace, with start and end in nanoseconds since UNIX epoch
+#[derive(Debug, Clone)]
+struct Slice {
+    name: String,
+    start: u64,
+    end: u64,
+}
+
+/// Statistics of all spans with the same name
+#[derive(Debug, Clone)]
+pub struct SpanStats {
+    /// Name of the spans
+    pub name: String,
+    /// Number of spans
+    pub count: usize,
+    /// Minimal duration
+    pub min: Duration,
+    /// Mean duration
+    pub mean: Duration,
+    /// Median duration
+    pub p50: Duration,
+    /// 99th percentile of the duration
+    pub p99: Duration,
+    /// Maximal duration
+    pub max: Duration,
+    /// Start of the spans relative to the start of their cycle, if spans started within a cycle
+    pub start: Option<StartStats>,
+}
+
+/// Statistics of the start of spans relative to the start of the enclosing cycle
+#[derive(Debug, Clone)]
+pub struct StartStats {
+    /// Mean offset of the span start from the cycle start
+    pub mean: Duration,
+    /// Difference between maximal and minimal offset of the span start from the cycle start
+    pub jitter: Duration,
+}
+
+/// Read a Perfetto trace written by feo-tracer
+pub fn read_trace(path: &Path) -> Result<idl::Trace, Error> {
+    let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    // The trace is a sequence of trace messages, which decode as a single one
+    idl::Trace::decode(data.as_slice())
+        .with_context(|| format!("failed to decode {}", path.display()))
+}
+
+/// Compute the statistics of the spans in the trace, grouped by span name and sorted by name
+///
+/// The start of each span is related to the most recent start of a span named `cycle_span`,
+/// e.g. [`CYCLE_SPAN`].
+pub fn analyze(trace: &idl::Trace, cycle_span: &str) -> Vec<SpanStats> {
+    let slices = slices(trace);
+
+    let mut cycle_starts = slices
+        .iter()
+        .filter(|slice| slice.name == cycle_span)
+        .map(|slice| slice.start)
+        .collect::<Vec<_>>();
+    cycle_starts.sort_unstable();
+
+    // Durations and start offsets in nanoseconds per span name
+    let mut spans = BTreeMap::<&str, (Vec<u64>, Vec<u64>)>::new();
+    for slice in &slices {
+        let (durations, offsets) = spans.entry(&slice.name).or_default();
+        durations.push(slice.end.saturating_sub(slice.start));
+        // Index of the first cycle starting after the span
+        let next_cycle = cycle_starts.partition_point(|start| *start <= slice.start);
+        if next_cycle > 0 {
+            offsets.push(slice.start - cycle_starts[next_cycle - 1]);
+        }
+    }
+
+    spans
+        .into_iter()
+        .map(|(name, (mut durations, offsets))| {
+            durations.sort_unstable();
+            SpanStats {
+                name: name.to_string(),
+                count: durations.len(),
+                min: Duration::from_nanos(durations[0]),
+                mean: mean(&durations),
+                p50: percentile(&durations, 50),
+                p99: percentile(&durations, 99),
+                max: Duration::from_nanos(durations[durations.len() - 1]),
+                start: start_stats(&offsets),
+            }
+        })
+        .collect()
+}
+
+/// Collect the spans of the trace by matching the begin and end of slices on each track
+fn slices(trace: &idl::Trace) -> Vec<Slice> {
+    // Begun slices per process and track, innermost last
+    let mut open = HashMap::<(i32, u64), Vec<(String, u64)>>::new();
+    let mut slices = Vec::new();
+
+    for packet in &trace.packet {
+        let Some(idl::trace_packet::Data::TrackEvent(event)) = &packet.data else {
+            continue;
+        };
+        let (Some(timestamp), Some(track_uuid)) = (packet.timestamp, event.track_uuid) else {
+            continue;
+        };
+        let track = (packet.trusted_pid.unwrap_or_default(), track_uuid);
+        match event.r#type() {
+            idl::track_event::Type::SliceBegin => {
+                let name = match &event.name_field {
+                    Some(idl::track_event::NameField::Name(name)) => name.clone(),
+                    _ => String::new(),
+                };
+                open.entry(track).or_default().push((name, timestamp));
+            }
+            idl::track_event::Type::SliceEnd => {
+                if let Some((name, start)) = open.get_mut(&track).and_then(Vec::pop) {
+                    slices.push(Slice {
+                        name,
+                        start,
+                        end: timestamp,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+    slices
+}
+
+/// Statistics of the given nanosecond offsets of span starts, or None if there are none
+fn start_stats(offsets: &[u64]) -> Option<StartStats> {
+    let min = offsets.iter().min()?;
+    let max = offsets.iter().max()?;
+    Some(StartStats {
+        mean: mean(offsets),
+        jitter: Duration::from_nanos(max - min),
+    })
+}
+
+/// Mean of the given nanosecond values, or zero if there are none
+fn mean(values: &[u64]) -> Duration {
+    if values.is_empty() {
+        return Duration::ZERO;
+    }
+    let sum = values.iter().map(|value| *value as u128).sum::<u128>();
+    Duration::from_nanos((sum / values.len() as u128) as u64)
+}
+
+/// Nearest-rank percentile of the given sorted nanosecond values, or zero if there are none
+fn percentile(sorted: &[u64], percent: usize) -> Duration {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted
+        .get(rank - 1)
+        .map_or(Duration::ZERO, |value| Duration::from_nanos(*value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEDULER: u64 = 1;
+    const WORKER: u64 = 2;
+
+    /// Packet beginning a slice with the given name, or ending the innermost slice if there is none
+    fn slice_event(timestamp: u64, track: u64, name: Option<&str>) -> idl::TracePacket {
+        let r#type = match name {
+            Some(_) => idl::track_event::Type::SliceBegin,
+            None => idl::track_event::Type::SliceEnd,
+        };
+        let event = idl::TrackEvent {
+            r#type: Some(r#type.into()),
+            track_uuid: Some(track),
+            name_field: name.map(|name| idl::track_event::NameField::Name(name.to_string())),
+            ..Default::default()
+        };
+        idl::TracePacket {
+            timestamp: Some(timestamp),
+            data: Some(idl::trace_packet::Data::TrackEvent(event)),
+            ..Default::default()
+        }
+    }
+
+    fn nanos(nanos: u64) -> Duration {
+        Duration::from_nanos(nanos)
+    }
+
+    #[test]
+    fn test_analyze() {
+        // Two cycles on the scheduler track, nested steps on the worker track,
+        // the second step outlasting its cycle
+        let packet = vec![
+            idl::TracePacket::default(),
+            slice_event(5, WORKER, Some("init")),
+            slice_event(50, WORKER, None),
+            slice_event(100, SCHEDULER, Some(CYCLE_SPAN)),
+            slice_event(110, WORKER, Some("step")),
+            slice_event(115, WORKER, Some("read")),
+            slice_event(120, WORKER, None),
+            slice_event(140, WORKER, None),
+            slice_event(200, SCHEDULER, None),
+            slice_event(1000, SCHEDULER, Some(CYCLE_SPAN)),
+            slice_event(1030, WORKER, Some("step")),
+            slice_event(1035, WORKER, Some("read")),
+            slice_event(1045, WORKER, None),
+            slice_event(1150, SCHEDULER, None),
+            slice_event(1160, WORKER, None),
+        ];
+        let trace = idl::Trace { packet };
+
+        let slices = slices(&trace);
+        let step = slices.iter().find(|slice| slice.start == 1030).unwrap();
+        assert_eq!((step.name.as_str(), step.end), ("step", 1160));
+
+        let stats = analyze(&trace, CYCLE_SPAN);
+        let names = stats
+            .iter()
+            .map(|stats| stats.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["init", "read", "step", CYCLE_SPAN]);
+
+        // Started before the first cycle
+        let init = &stats[0];
+        assert_eq!((init.count, init.min, init.max), (1, nanos(45), nanos(45)));
+        assert!(init.start.is_none());
+
+        let read = &stats[1];
+        assert_eq!(read.count, 2);
+        assert_eq!(
+            (read.min, read.mean, read.max),
+            (nanos(5), nanos(7), nanos(10))
+        );
+        assert_eq!((read.p50, read.p99), (nanos(5), nanos(10)));
+        let start = read.start.as_ref().unwrap();
+        assert_eq!((start.mean, start.jitter), (nanos(25), nanos(20)));
+
+        let step = &stats[2];
+        assert_eq!(step.count, 2);
+        assert_eq!(
+            (step.min, step.mean, step.max),
+            (nanos(30), nanos(80), nanos(130))
+        );
+        let start = step.start.as_ref().unwrap();
+        assert_eq!((start.mean, start.jitter), (nanos(20), nanos(20)));
+
+        let cycle = &stats[3];
+        assert_eq!(cycle.count, 2);
+        assert_eq!(
+            (cycle.min, cycle.mean, cycle.max),
+            (nanos(100), nanos(125), nanos(150))
+        );
+        let start = cycle.start.as_ref().unwrap();
+        assert_eq!((start.mean, start.jitter), (Duration::ZERO, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(mean(&[]), Duration::ZERO);
+        assert_eq!(mean(&[7]), Duration::from_nanos(7));
+        assert_eq!(mean(&[1, 2, 3, 6]), Duration::from_nanos(3));
+        // The sum does not overflow
+        assert_eq!(mean(&[u64::MAX, u64::MAX]), Duration::from_nanos(u64::MAX));
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+        assert_eq!(percentile(&[7], 50), Duration::from_nanos(7));
+        assert_eq!(percentile(&[7], 99), Duration::from_nanos(7));
+
+        let values = (1..=100).collect::<Vec<u64>>();
+        assert_eq!(percentile(&values, 0), Duration::from_nanos(1));
+        assert_eq!(percentile(&values, 50), Duration::from_nanos(50));
+        assert_eq!(percentile(&values, 99), Duration::from_nanos(99));
+        assert_eq!(percentile(&values, 100), Duration::from_nanos(100));
+
+        // Nearest rank, rounded up
+        assert_eq!(percentile(&[1, 2, 3], 50), Duration::from_nanos(2));
+        assert_eq!(percentile(&[1, 2, 3], 99), Duration::from_nanos(3));
+    }
+
+    #[test]
+    fn test_start_stats() {
+        assert!(start_stats(&[]).is_none());
+
+        let single = start_stats(&[5]).unwrap();
+        assert_eq!(single.mean, Duration::from_nanos(5));
+        assert_eq!(single.jitter, Duration::ZERO);
+
+        let stats = start_stats(&[10, 4, 7]).unwrap();
+        assert_eq!(stats.mean, Duration::from_nanos(7));
+        assert_eq!(stats.jitter, Duration::from_nanos(6));
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use feo_tracer::data::RecordData;
+use feo_tracing::{span, LevelFilter};
+use std::{env, fs, process, thread};
+use tokio::sync::mpsc;
+
+/// A dump of the flight recorder is read by `feo-tracer --input`
+#[test]
+fn flight_recorder_dump_is_readable() {
+    let directory = env::temp_dir().join(format!("feo-tracer-dump-{}", process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    feo_tracing::init_flight_recorder(LevelFilter::TRACE, 1024, &directory);
+
+    span!(feo_tracing::Level::INFO, "dumped span").in_scope(|| ());
+
+    // The panic hook dumps the packets of all threads before the panicking thread is joined
+    thread::spawn(|| panic!("dump requested by test"))
+        .join()
+        .unwrap_err();
+    let dumps: Vec<_> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(dumps.len(), 1);
+
+    let (sink, mut receiver) = mpsc::channel(1024);
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime
+        .block_on(feo_tracer::io::read(&dumps[0], sink))
+        .unwrap();
+    let mut records = Vec::new();
+    while let Ok(record) = receiver.try_recv() {
+        records.push(record);
+    }
+    fs::remove_dir_all(&directory).unwrap();
+
+    // The dump is attributed to this process and contains the traced span and panic
+    assert!(matches!(records.first().unwrap().data, RecordData::Exec));
+    assert!(matches!(records.last().unwrap().data, RecordData::Exit));
+    let process = &records[0].process;
+    let label = format!("(pid {})", process::id());
+    assert!(process.name.as_ref().unwrap().ends_with(&label));
+    let names: Vec<_> = records
+        .iter()
+        .filter_map(|record| match &record.data {
+            RecordData::NewSpan { name, .. } | RecordData::Event { name, .. } => {
+                Some(name.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    assert!(names.contains(&"dumped span"));
+    assert!(names.contains(&"panic"));
+    let entered = records
+        .iter()
+        .filter(|record| matches!(record.data, RecordData::EnterSpan { .. }))
+        .count();
+    let exited = records
+        .iter()
+        .filter(|record| matches!(record.data, RecordData::ExitSpan { .. }))
+        .count();
+    assert_eq!((entered, exited), (1, 1));
+}
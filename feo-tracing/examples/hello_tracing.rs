@@ -5,7 +5,7 @@
 use core::hash::{BuildHasher, Hasher};
 use core::ops::Range;
 use core::time::Duration;
-use feo_tracing::{instrument, span, Level};
+use feo_tracing::{counter, instrument, span, Level};
 use std::collections::hash_map::RandomState;
 use std::thread;
 use tracing::level_filters::LevelFilter;
@@ -76,7 +76,8 @@ fn rand() -> u64 {
 
 #[instrument(level = "trace")]
 fn sleep_rand_millis(range: Range<u64>) {
-    thread::sleep(Duration::from_millis(
-        rand() % (range.end - range.start) + range.start,
-    ));
+    let millis = rand() % (range.end - range.start) + range.start;
+    // Emit a counter
+    counter!("sleep_ms", millis);
+    thread::sleep(Duration::from_millis(millis));
 }
@@ -0,0 +1,155 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Endpoint on which the feo-tracer receives trace packets
+
+use core::fmt;
+use core::str::FromStr;
+use std::env;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Environment variable selecting the endpoint, e.g. `unix:/tmp/feo-tracer.sock` or `tcp:127.0.0.1:7387`
+pub const ENDPOINT_ENV: &str = "FEO_TRACER_ENDPOINT";
+
+/// The unix socket path used by default by the tracing daemon to receive trace packets
+pub const UNIX_PACKET_PATH: &str = "/tmp/feo-tracer.sock";
+
+/// Endpoint of the feo-tracer
+///
+/// The textual representation is `unix:<path>` or `tcp:<address>:<port>`.
+/// A plain absolute path is accepted as a unix socket path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Unix stream socket at the given path
+    Unix(PathBuf),
+    /// TCP socket at the given address, e.g. a loopback address for traces from containers
+    Tcp(SocketAddr),
+}
+
+impl Endpoint {
+    /// Endpoint configured by [`ENDPOINT_ENV`], or the default endpoint if the variable is not set
+    pub fn from_env() -> Result<Self, InvalidEndpoint> {
+        match env::var(ENDPOINT_ENV) {
+            Ok(endpoint) => endpoint.parse(),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
+
+impl Default for Endpoint {
+    fn default() -> Self {
+        Self::Unix(PathBuf::from(UNIX_PACKET_PATH))
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = InvalidEndpoint;
+
+    fn from_str(endpoint: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = endpoint.strip_prefix("unix:") {
+            if !path.is_empty() {
+                return Ok(Self::Unix(PathBuf::from(path)));
+            }
+        } else if let Some(address) = endpoint.strip_prefix("tcp:") {
+            if let Ok(address) = address.parse() {
+                return Ok(Self::Tcp(address));
+            }
+        } else if endpoint.starts_with('/') {
+            return Ok(Self::Unix(PathBuf::from(endpoint)));
+        }
+        Err(InvalidEndpoint(endpoint.to_string()))
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+            Self::Tcp(address) => write!(f, "tcp:{address}"),
+        }
+    }
+}
+
+/// Error returned for an endpoint not matching `unix:<path>` or `tcp:<address>:<port>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidEndpoint(String);
+
+impl fmt::Display for InvalidEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid tracer endpoint '{}', expected unix:<path> or tcp:<address>:<port>",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidEndpoint {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_unix() {
+        assert_eq!(
+            "unix:/tmp/tracer.sock".parse(),
+            Ok(Endpoint::Unix(PathBuf::from("/tmp/tracer.sock")))
+        );
+        assert_eq!(
+            "unix:tracer.sock".parse(),
+            Ok(Endpoint::Unix(PathBuf::from("tracer.sock")))
+        );
+        // A bare absolute path is a unix socket
+        assert_eq!(
+            "/tmp/tracer.sock".parse(),
+            Ok(Endpoint::Unix(PathBuf::from("/tmp/tracer.sock")))
+        );
+    }
+
+    #[test]
+    fn test_parse_tcp() {
+        assert_eq!(
+            "tcp:127.0.0.1:7387".parse(),
+            Ok(Endpoint::Tcp(SocketAddr::from(([127, 0, 0, 1], 7387))))
+        );
+        assert_eq!(
+            "tcp:[::1]:7387".parse(),
+            Ok(Endpoint::Tcp(SocketAddr::from((
+                [0, 0, 0, 0, 0, 0, 0, 1],
+                7387
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for endpoint in [
+            "",
+            "unix:",
+            "tcp:",
+            "tcp:127.0.0.1",
+            "tcp:localhost:7387",
+            "tracer.sock",
+            "udp:127.0.0.1:7387",
+        ] {
+            assert_eq!(
+                endpoint.parse::<Endpoint>(),
+                Err(InvalidEndpoint(endpoint.to_string())),
+                "{endpoint}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for endpoint in [
+            Endpoint::default(),
+            Endpoint::Tcp(SocketAddr::from(([127, 0, 0, 1], 7387))),
+        ] {
+            assert_eq!(endpoint.to_string().parse(), Ok(endpoint));
+        }
+    }
+}
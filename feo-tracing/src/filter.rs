@@ -0,0 +1,284 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filter directives selecting the traced spans and events by target, name and level
+//!
+//! The syntax follows `RUST_LOG`: a comma-separated list of directives `target[name]=level`,
+//! where target and name are optional, e.g. `info,feo::scheduler=trace,feo_com=off,[step]=debug`.
+//! A directive without level enables the level `trace`, a level without target and name
+//! applies to everything not matched otherwise.
+//!
+//! A directive matches spans and events whose target starts with the directive's target and,
+//! unlike `RUST_LOG`, whose own name equals the directive's name. The most specific matching
+//! directive applies: directives with a name before those without, then those with the longest target.
+//! Of equally specific directives, the last one applies. Spans and events not matched are disabled.
+
+use core::fmt;
+use core::str::FromStr;
+use feo_log::error;
+use std::env;
+use std::sync::RwLock;
+use tracing::callsite::rebuild_interest_cache;
+use tracing::level_filters::LevelFilter;
+use tracing::Metadata;
+
+/// Environment variable with filter directives added to the filter passed to [`init`](crate::init)
+pub const FILTER_ENV: &str = "FEO_TRACING_FILTER";
+
+/// Filter of spans and events given by a list of directives
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    directives: Vec<Directive>,
+}
+
+/// Level of the spans and events matching target and name
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    target: Option<String>,
+    name: Option<String>,
+    level: LevelFilter,
+}
+
+impl Directive {
+    /// Check if the directive applies to the given target and name
+    fn matches(&self, target: &str, name: &str) -> bool {
+        let target_matches = match &self.target {
+            Some(prefix) => target.starts_with(prefix.as_str()),
+            None => true,
+        };
+        let name_matches = match &self.name {
+            Some(directive) => directive == name,
+            None => true,
+        };
+        target_matches && name_matches
+    }
+
+    /// Rank of the directive among others matching the same span or event
+    fn specificity(&self) -> (bool, usize) {
+        (
+            self.name.is_some(),
+            self.target.as_ref().map_or(0, String::len),
+        )
+    }
+}
+
+impl Filter {
+    /// Check if a span or event is enabled
+    pub fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= &self.level(metadata.target(), metadata.name())
+    }
+
+    /// Maximal level enabled by any directive
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|directive| directive.level)
+            .max()
+            .unwrap_or(LevelFilter::OFF)
+    }
+
+    /// Add the directives of another filter, taking precedence over equally specific ones
+    pub fn extend(&mut self, other: Filter) {
+        self.directives.extend(other.directives);
+    }
+
+    /// Level of the most specific directive matching the given target and name
+    fn level(&self, target: &str, name: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .enumerate()
+            .filter(|(_, directive)| directive.matches(target, name))
+            .max_by_key(|(index, directive)| (directive.specificity(), *index))
+            .map_or(LevelFilter::OFF, |(_, directive)| directive.level)
+    }
+}
+
+impl From<LevelFilter> for Filter {
+    fn from(level: LevelFilter) -> Self {
+        Self {
+            directives: vec![Directive {
+                target: None,
+                name: None,
+                level,
+            }],
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = InvalidFilter;
+
+    fn from_str(directives: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidFilter(directives.to_string());
+        let directives = directives
+            .split(',')
+            .map(str::trim)
+            .filter(|directive| !directive.is_empty())
+            .map(|directive| {
+                let (selector, level) = match directive.split_once('=') {
+                    Some((selector, level)) => (selector, level.parse().map_err(|_| invalid())?),
+                    None => match directive.parse() {
+                        // A plain level applies to everything
+                        Ok(level) => ("", level),
+                        // A plain selector enables everything it matches
+                        Err(_) => (directive, LevelFilter::TRACE),
+                    },
+                };
+                let (target, name) = match selector.split_once('[') {
+                    Some((target, name)) => {
+                        let name = name.strip_suffix(']').ok_or_else(invalid)?;
+                        (target, Some(name.to_string()))
+                    }
+                    None => (selector, None),
+                };
+                let target = (!target.is_empty()).then(|| target.to_string());
+                Ok(Directive {
+                    target,
+                    name,
+                    level,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { directives })
+    }
+}
+
+/// Error returned for filter directives not matching `target[name]=level`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidFilter(String);
+
+impl fmt::Display for InvalidFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid trace filter '{}', expected directives target[name]=level",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidFilter {}
+
+/// Filter given at initialization, extended by directives set at runtime via the feo-tracer
+pub(crate) struct DynamicFilter {
+    initial: Filter,
+    current: RwLock<Filter>,
+}
+
+impl DynamicFilter {
+    /// Create the filter of the subscriber, adding the directives of [`FILTER_ENV`] to the given ones
+    pub(crate) fn new(filter: Filter) -> Self {
+        let mut initial = filter;
+        if let Ok(directives) = env::var(FILTER_ENV) {
+            match directives.parse() {
+                Ok(directives) => initial.extend(directives),
+                Err(e) => error!("{e}, ignoring {FILTER_ENV}"),
+            }
+        }
+        Self {
+            current: RwLock::new(initial.clone()),
+            initial,
+        }
+    }
+
+    /// Check if a span or event is enabled
+    pub(crate) fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.current
+            .read()
+            .expect("failed to lock filter")
+            .enabled(metadata)
+    }
+
+    /// Maximal level enabled by any directive
+    pub(crate) fn max_level(&self) -> LevelFilter {
+        self.current
+            .read()
+            .expect("failed to lock filter")
+            .max_level()
+    }
+
+    /// Replace the directives set at runtime, restoring the initial filter if empty
+    pub(crate) fn set(&self, directives: &str) -> Result<(), InvalidFilter> {
+        let mut filter = self.initial.clone();
+        filter.extend(directives.parse()?);
+        *self.current.write().expect("failed to lock filter") = filter;
+        // Re-evaluate the enabled spans and events, the lock must not be held
+        rebuild_interest_cache();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let filter: Filter = "info, feo::scheduler=trace,[step]=debug,feo_com[send]=off,feo_log"
+            .parse()
+            .unwrap();
+        let directive = |target: Option<&str>, name: Option<&str>, level| Directive {
+            target: target.map(str::to_string),
+            name: name.map(str::to_string),
+            level,
+        };
+        assert_eq!(
+            filter.directives,
+            vec![
+                directive(None, None, LevelFilter::INFO),
+                directive(Some("feo::scheduler"), None, LevelFilter::TRACE),
+                directive(None, Some("step"), LevelFilter::DEBUG),
+                directive(Some("feo_com"), Some("send"), LevelFilter::OFF),
+                directive(Some("feo_log"), None, LevelFilter::TRACE),
+            ]
+        );
+        assert_eq!("".parse::<Filter>().unwrap(), Filter::default());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for directives in ["feo=loud", "[step=debug", "feo[step", "feo=info=debug"] {
+            assert_eq!(
+                directives.parse::<Filter>(),
+                Err(InvalidFilter(directives.to_string())),
+                "{directives}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_level() {
+        let filter: Filter = "warn,feo=info,feo::scheduler=debug,[step]=error,feo[step]=trace"
+            .parse()
+            .unwrap();
+        assert_eq!(filter.level("other", "span"), LevelFilter::WARN);
+        assert_eq!(filter.level("feo::com", "span"), LevelFilter::INFO);
+        assert_eq!(filter.level("feo::scheduler", "span"), LevelFilter::DEBUG);
+        // A name takes precedence over a longer target
+        assert_eq!(filter.level("other", "step"), LevelFilter::ERROR);
+        assert_eq!(filter.level("feo::scheduler", "step"), LevelFilter::TRACE);
+        assert_eq!(filter.max_level(), LevelFilter::TRACE);
+
+        let filter: Filter = "feo=debug".parse().unwrap();
+        assert_eq!(filter.level("other", "span"), LevelFilter::OFF);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut filter: Filter = "info,feo=debug,[step]=trace".parse().unwrap();
+        // Of equally specific directives, the last one applies
+        assert_eq!(
+            "feo=info,feo=warn"
+                .parse::<Filter>()
+                .unwrap()
+                .level("feo", "span"),
+            LevelFilter::WARN
+        );
+        filter.extend("feo=off".parse().unwrap());
+        assert_eq!(filter.level("feo", "span"), LevelFilter::OFF);
+        assert_eq!(filter.level("feo", "step"), LevelFilter::TRACE);
+        assert_eq!(filter.level("other", "span"), LevelFilter::INFO);
+        assert_eq!(filter.max_level(), LevelFilter::TRACE);
+    }
+}
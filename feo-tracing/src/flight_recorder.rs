@@ -0,0 +1,343 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-process flight recorder keeping the most recent trace packets in memory
+//!
+//! A dump consists of a [`Hello`] packet followed by the recorded trace packets, serialized
+//! like the stream sent to the feo-tracer, which converts it with `feo-tracer --input`.
+
+use crate::feo_subscriber::{PacketSink, Subscriber, ThreadBuffers};
+use crate::protocol::{Hello, MAX_PACKET_SIZE};
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::time::Duration;
+use feo_log::{error, info};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::time::Instant;
+use std::{panic, process, thread};
+use tracing::{event, Level};
+
+/// Environment variable enabling the flight recorder in [`init`](crate::init), giving the directory of the dumps
+pub const FLIGHT_RECORDER_ENV: &str = "FEO_TRACING_FLIGHT_RECORDER";
+
+/// Number of trace packets kept by the flight recorder enabled by [`FLIGHT_RECORDER_ENV`]
+pub const FLIGHT_RECORDER_PACKETS: usize = 65536;
+
+/// Signal requesting the flight recorder to dump its packets
+pub const DUMP_SIGNAL: libc::c_int = libc::SIGUSR1;
+
+/// Minimal time interval between dumps on request, e.g. in case of overruns in every cycle
+const MIN_DUMP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximal time the panic hook waits for each lock of the flight recorder
+const PANIC_LOCK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Time between attempts of the panic hook to take a lock
+const PANIC_LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Set if a dump has been requested and not yet started
+static DUMP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Request the flight recorder to dump its packets into a new file
+///
+/// The dump is written in the background and includes the packets emitted before the request.
+/// Requests within 10 seconds after the previous dump on request are ignored.
+/// Does nothing if the flight recorder is not enabled.
+pub fn request_dump() {
+    DUMP_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Ring of the most recent serialized trace packets
+pub(crate) struct FlightRecorder {
+    /// Serialized packets, oldest first
+    packets: Mutex<VecDeque<Vec<u8>>>,
+    /// Maximal number of packets
+    capacity: usize,
+    /// Directory of the dumps
+    directory: PathBuf,
+    /// Number of dumps written
+    dumps: AtomicUsize,
+}
+
+impl FlightRecorder {
+    pub(crate) fn new(capacity: usize, directory: PathBuf) -> Self {
+        Self {
+            packets: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity: capacity.max(1),
+            directory,
+            dumps: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add a serialized packet, replacing the oldest one if the ring is full
+    fn push(&self, packet: &[u8]) {
+        let mut packets = self.packets.lock().expect("failed to lock flight recorder");
+        self.push_locked(&mut packets, packet);
+    }
+
+    /// Add a serialized packet to the locked packets
+    fn push_locked(&self, packets: &mut VecDeque<Vec<u8>>, packet: &[u8]) {
+        let mut slot = if packets.len() >= self.capacity {
+            packets.pop_front().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        slot.clear();
+        slot.extend_from_slice(packet);
+        packets.push_back(slot);
+    }
+
+    /// Write all packets into a new file in the dump directory
+    fn dump(&self) -> io::Result<(PathBuf, usize)> {
+        let packets = self.packets.lock().expect("failed to lock flight recorder");
+        self.dump_locked(&packets)
+    }
+
+    /// Write the locked packets into a new file in the dump directory
+    fn dump_locked(&self, packets: &VecDeque<Vec<u8>>) -> io::Result<(PathBuf, usize)> {
+        let index = self.dumps.fetch_add(1, Ordering::Relaxed);
+        let path = self
+            .directory
+            .join(format!("feo-trace-{}-{index}.bin", process::id()));
+        let mut writer = io::BufWriter::new(File::create(&path)?);
+
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        let hello =
+            postcard::to_slice_cobs(&Hello::this(), &mut buffer[..]).map_err(io::Error::other)?;
+        writer.write_all(hello)?;
+        for packet in packets.iter() {
+            writer.write_all(packet)?;
+        }
+        writer.flush()?;
+        Ok((path, packets.len()))
+    }
+
+    /// Dump the packets, logging the result
+    fn dump_logged(&self) {
+        log_dump(self.dump());
+    }
+}
+
+/// Log the result of a dump
+fn log_dump(result: io::Result<(PathBuf, usize)>) {
+    match result {
+        Ok((path, packets)) => info!("Dumped {packets} trace packets to {path:?}"),
+        Err(e) => error!("Failed to dump flight recorder: {e:?}"),
+    }
+}
+
+/// Sink of the panic hook keeping the packets in the already locked flight recorder
+struct LockedSink<'r> {
+    recorder: &'r FlightRecorder,
+    packets: &'r mut VecDeque<Vec<u8>>,
+}
+
+impl PacketSink for LockedSink<'_> {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.recorder.push_locked(self.packets, packet);
+        Ok(())
+    }
+
+    fn poll(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sink of the serializing thread keeping the packets in the flight recorder
+pub(crate) struct FlightSink {
+    recorder: Arc<FlightRecorder>,
+    /// Dump requested in the previous round
+    pending: bool,
+    /// Time of the last dump on request
+    last_dump: Option<Instant>,
+}
+
+impl FlightSink {
+    pub(crate) fn new(recorder: Arc<FlightRecorder>) -> Self {
+        Self {
+            recorder,
+            pending: false,
+            last_dump: None,
+        }
+    }
+}
+
+impl PacketSink for FlightSink {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.recorder.push(packet);
+        Ok(())
+    }
+
+    fn poll(&mut self) -> io::Result<()> {
+        // Dump one round after the request, when the packets emitted before it have been taken
+        if self.pending {
+            self.recorder.dump_logged();
+            self.pending = false;
+            self.last_dump = Some(Instant::now());
+        }
+        let requested = DUMP_REQUESTED.swap(false, Ordering::Relaxed);
+        let ready = match self.last_dump {
+            Some(last_dump) => last_dump.elapsed() >= MIN_DUMP_INTERVAL,
+            None => true,
+        };
+        self.pending = requested && ready;
+        Ok(())
+    }
+}
+
+/// Dump the flight recorder on panic and on [`DUMP_SIGNAL`]
+///
+/// The signal handler is only installed if the process does not handle [`DUMP_SIGNAL`] yet.
+pub(crate) fn install(recorder: Arc<FlightRecorder>, buffers: ThreadBuffers) {
+    install_signal_handler();
+
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        event!(name: "panic", Level::ERROR, message = %info);
+
+        // The locks are held briefly by the serializing thread, but the panicking thread
+        // may hold one of them, e.g. if it is the serializing thread, so waiting is bounded
+        let (Some(mut packets), Some(threads)) = (
+            try_lock_for(&recorder.packets, PANIC_LOCK_TIMEOUT),
+            try_lock_for(&buffers, PANIC_LOCK_TIMEOUT).map(|buffers| buffers.clone()),
+        ) else {
+            error!("Flight recorder is busy, skipping dump on panic");
+            return;
+        };
+
+        // Take all packets still buffered by the threads, including the panicking one, and dump
+        let mut sink = LockedSink {
+            recorder: &recorder,
+            packets: &mut packets,
+        };
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        let mut taken = Vec::new();
+        let _ = Subscriber::drain_threads(&threads, &mut sink, &mut buffer, &mut taken, usize::MAX);
+        log_dump(recorder.dump_locked(&packets));
+    }));
+}
+
+/// Lock the given mutex, or return None if it is still locked after `timeout`
+fn try_lock_for<T>(mutex: &Mutex<T>, timeout: Duration) -> Option<MutexGuard<'_, T>> {
+    let start = Instant::now();
+    loop {
+        match mutex.try_lock() {
+            Ok(guard) => return Some(guard),
+            // A poisoned lock is not held anymore
+            Err(TryLockError::Poisoned(poisoned)) => return Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                thread::sleep(PANIC_LOCK_RETRY_INTERVAL)
+            }
+            Err(TryLockError::WouldBlock) => return None,
+        }
+    }
+}
+
+/// Install the handler of [`DUMP_SIGNAL`], unless another handler is installed
+fn install_signal_handler() {
+    // Safety: sigaction is plain old data, for which all zeros are valid
+    let mut action: libc::sigaction = unsafe { core::mem::zeroed() };
+    // Safety: FFI call querying the current action into a valid sigaction
+    if unsafe { libc::sigaction(DUMP_SIGNAL, ptr::null(), &mut action) } != 0 {
+        error!(
+            "Failed to query handler of signal {DUMP_SIGNAL}: {}",
+            io::Error::last_os_error()
+        );
+        return;
+    }
+    if action.sa_sigaction != libc::SIG_DFL {
+        info!("Signal {DUMP_SIGNAL} is already handled, flight recorder dumps on signal disabled");
+        return;
+    }
+
+    action.sa_sigaction = on_dump_signal as *const () as libc::sighandler_t;
+    action.sa_flags = libc::SA_RESTART;
+    // Safety: FFI calls with a valid sigaction, whose handler only stores to an atomic,
+    // which is async-signal-safe
+    unsafe {
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(DUMP_SIGNAL, &action, ptr::null_mut()) != 0 {
+            error!(
+                "Failed to install handler of signal {DUMP_SIGNAL}: {}",
+                io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+extern "C" fn on_dump_signal(_signal: libc::c_int) {
+    request_dump();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{env, fs};
+
+    /// Create an empty dump directory for the given test
+    fn test_directory(test: &str) -> PathBuf {
+        let directory =
+            env::temp_dir().join(format!("feo-flight-recorder-{}-{test}", process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn dump_count(directory: &PathBuf) -> usize {
+        fs::read_dir(directory).unwrap().count()
+    }
+
+    #[test]
+    fn test_push_capacity() {
+        let recorder = FlightRecorder::new(3, PathBuf::new());
+        for packet in 0..5u8 {
+            recorder.push(&[packet; 2]);
+        }
+        // The oldest packets are replaced
+        let packets = recorder.packets.lock().unwrap();
+        assert_eq!(*packets, [vec![2, 2], vec![3, 3], vec![4, 4]]);
+
+        // At least one packet is kept
+        let recorder = FlightRecorder::new(0, PathBuf::new());
+        let mut packets = recorder.packets.lock().unwrap();
+        recorder.push_locked(&mut packets, &[1]);
+        recorder.push_locked(&mut packets, &[2, 3]);
+        assert_eq!(*packets, [vec![2, 3]]);
+    }
+
+    #[test]
+    fn test_poll_rate_limited() {
+        let directory = test_directory("poll");
+        let recorder = Arc::new(FlightRecorder::new(4, directory.clone()));
+        let mut sink = FlightSink::new(Arc::clone(&recorder));
+        sink.send(&[1]).unwrap();
+
+        // Dumped one round after the request
+        request_dump();
+        sink.poll().unwrap();
+        assert_eq!(dump_count(&directory), 0);
+        sink.poll().unwrap();
+        assert_eq!(dump_count(&directory), 1);
+
+        // Requests within the minimal interval are ignored
+        request_dump();
+        sink.poll().unwrap();
+        sink.poll().unwrap();
+        assert_eq!(dump_count(&directory), 1);
+
+        // Requests after the minimal interval are served
+        sink.last_dump = Instant::now().checked_sub(MIN_DUMP_INTERVAL);
+        request_dump();
+        sink.poll().unwrap();
+        sink.poll().unwrap();
+        assert_eq!(dump_count(&directory), 2);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -4,13 +4,54 @@
 
 //! Tracing library for the FEO project.
 
+pub mod endpoint;
 /// The `subscriber` module contains the `Subscriber` struct, which is a custom `tracing` subscriber.
 /// The tracing data is forward to `feo-tracer`
 #[path = "subscriber.rs"]
 mod feo_subscriber;
+pub mod filter;
+pub mod flight_recorder;
 pub mod protocol;
 
+/// Endpoint of the feo-tracer
+pub use endpoint::Endpoint;
 /// Initialize tracing
-pub use feo_subscriber::init;
+pub use feo_subscriber::{init, init_flight_recorder, init_with_endpoint};
+/// Filter of the traced spans and events
+pub use filter::Filter;
+/// Request a dump of the flight recorder
+pub use flight_recorder::request_dump;
 /// Re-export of the `tracing` crate.
 pub use tracing::{self, event, instrument, level_filters::LevelFilter, span, Level};
+
+/// Name of an integer field of a span or event starting or continuing a flow with the given id
+///
+/// Spans and events with the same flow id are linked in the trace, e.g. across processes.
+pub const FLOW_FIELD: &str = "flow";
+
+/// Name of an integer field of a span or event ending the flow with the given id
+pub const TERMINATING_FLOW_FIELD: &str = "terminating_flow";
+
+/// Target of the events emitted by [`counter!`]
+pub const COUNTER_TARGET: &str = "feo_tracing::counter";
+
+/// Emit the current value of a numeric time series, shown as a counter track in the trace
+///
+/// The name is a string slice identifying the counter within the process,
+/// the value is an integer or floating point number.
+///
+/// ```
+/// feo_tracing::counter!("queue_depth", 3u64);
+/// feo_tracing::counter!("cpu_load", 0.25);
+/// ```
+#[macro_export]
+macro_rules! counter {
+    ($name:expr, $value:expr) => {
+        $crate::tracing::event!(
+            target: $crate::COUNTER_TARGET,
+            $crate::Level::INFO,
+            counter = $name,
+            value = $value
+        )
+    };
+}
@@ -2,19 +2,25 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use core::fmt::Debug;
-use serde::{Deserialize, Serialize};
+use core::fmt::{self, Debug};
+use serde::de::{SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::process;
 use std::time::{self, UNIX_EPOCH};
 use tracing::field::Field;
 use tracing_subscriber::field::Visit;
 
-pub const MAX_INFO_SIZE: usize = 30;
+/// The maximal size of names and string values in bytes, longer ones are truncated
+pub const MAX_INFO_SIZE: usize = 64;
+
+/// The maximal number of fields attached to a span or event, further fields are dropped
+pub const MAX_FIELDS: usize = 8;
 
 /// The maximal allowed size of serialized packet data
 ///
 /// Packets exceeding this size will be dropped with an error message
-pub const MAX_PACKET_SIZE: usize = 148;
+pub const MAX_PACKET_SIZE: usize = 1280;
 
 type Id = u64;
 
@@ -37,17 +43,16 @@ impl Process {
 pub enum TraceData {
     NewSpan {
         id: Id,
-        name: [u8; MAX_INFO_SIZE],
-        name_len: usize,
+        name: InfoString,
         info: EventInfo,
     },
     Record {
         span: Id,
+        info: EventInfo,
     },
     Event {
         parent_span: Option<Id>,
-        name: [u8; MAX_INFO_SIZE],
-        name_len: usize,
+        name: InfoString,
         info: EventInfo,
     },
     Enter {
@@ -56,24 +61,259 @@ pub enum TraceData {
     Exit {
         span: Id,
     },
+    Counter {
+        name: InfoString,
+        value: CounterValue,
+    },
+    /// Number of packets of the thread lost since the last report
+    Dropped {
+        packets: u64,
+    },
+}
+
+/// Value of a counter
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CounterValue {
+    I64(i64),
+    F64(f64),
+}
+
+impl CounterValue {
+    /// Convert a numeric field value, saturating unsigned values exceeding the range of `i64`
+    pub fn from_field(value: &FieldValue) -> Option<Self> {
+        match *value {
+            FieldValue::I64(value) => Some(Self::I64(value)),
+            FieldValue::U64(value) => Some(Self::I64(value.try_into().unwrap_or(i64::MAX))),
+            FieldValue::F64(value) => Some(Self::F64(value)),
+            FieldValue::Bool(_) | FieldValue::Str(_) | FieldValue::Debug(_) => None,
+        }
+    }
+}
+
+/// A string of at most [`MAX_INFO_SIZE`] bytes, stored without allocation
+///
+/// Longer strings are truncated at a character boundary.
+/// Only the used bytes are serialized.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct InfoString {
+    bytes: [u8; MAX_INFO_SIZE],
+    len: usize,
+}
+
+impl InfoString {
+    /// Create a string from the given string slice, truncating it if necessary
+    pub fn new(slice: &str) -> Self {
+        let mut string = Self::default();
+        string.len = truncate(slice, &mut string.bytes);
+        string
+    }
+
+    /// Get the string slice
+    pub fn as_str(&self) -> &str {
+        // The bytes are always copied from a string slice, truncated at a character boundary
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Default for InfoString {
+    fn default() -> Self {
+        Self {
+            bytes: [0; MAX_INFO_SIZE],
+            len: 0,
+        }
+    }
+}
+
+impl Debug for InfoString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
 }
 
-/// Additional info that can be attached to an event
-#[derive(Debug, Serialize, Deserialize, Default)]
+impl fmt::Write for InfoString {
+    /// Append the given string slice, truncating it at the maximal size
+    ///
+    /// Returns an error if the slice has been truncated, to stop further formatting.
+    fn write_str(&mut self, slice: &str) -> fmt::Result {
+        let len = truncate(slice, &mut self.bytes[self.len..]);
+        self.len += len;
+        if len < slice.len() {
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for InfoString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for InfoString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InfoStringVisitor;
+
+        impl Visitor<'_> for InfoStringVisitor {
+            type Value = InfoString;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<InfoString, E> {
+                Ok(InfoString::new(value))
+            }
+        }
+
+        deserializer.deserialize_str(InfoStringVisitor)
+    }
+}
+
+/// Typed value of a field
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FieldValue {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    Str(InfoString),
+    /// Debug representation of a value
+    Debug(InfoString),
+}
+
+/// A named field of a span or event
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EventField {
+    pub name: InfoString,
+    pub value: FieldValue,
+}
+
+/// Fields attached to a span or event
+///
+/// Holds up to [`MAX_FIELDS`] fields without allocation. Only the used fields are serialized.
+#[derive(Debug, Default)]
 pub struct EventInfo {
-    pub name: [u8; MAX_INFO_SIZE],
-    pub name_len: Option<usize>,
-    pub value: [u8; MAX_INFO_SIZE],
-    pub value_len: usize,
+    fields: [Option<EventField>; MAX_FIELDS],
+    len: usize,
+}
+
+impl EventInfo {
+    /// Iterate over the fields
+    pub fn fields(&self) -> impl Iterator<Item = &EventField> {
+        self.fields[..self.len].iter().flatten()
+    }
+
+    /// Add a field, dropping it if the maximal number of fields is reached
+    pub fn push(&mut self, name: &str, value: FieldValue) {
+        if let Some(slot) = self.fields.get_mut(self.len) {
+            *slot = Some(EventField {
+                name: InfoString::new(name),
+                value,
+            });
+            self.len += 1;
+        }
+    }
+}
+
+impl Serialize for EventInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // The length must be known up front for postcard
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        for field in self.fields() {
+            seq.serialize_element(field)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for EventInfo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EventInfoVisitor;
+
+        impl<'de> Visitor<'de> for EventInfoVisitor {
+            type Value = EventInfo;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of fields")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<EventInfo, A::Error> {
+                let mut info = EventInfo::default();
+                while let Some(field) = seq.next_element::<EventField>()? {
+                    if let Some(slot) = info.fields.get_mut(info.len) {
+                        *slot = Some(field);
+                        info.len += 1;
+                    }
+                }
+                Ok(info)
+            }
+        }
+
+        deserializer.deserialize_seq(EventInfoVisitor)
+    }
 }
 
 impl Visit for EventInfo {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field.name(), FieldValue::F64(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field.name(), FieldValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field.name(), FieldValue::U64(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field.name(), FieldValue::Bool(value));
+    }
+
     fn record_str(&mut self, field: &Field, value: &str) {
-        self.name_len = Some(truncate(field.name(), &mut self.name));
-        self.value_len = truncate(value, &mut self.value);
+        self.push(field.name(), FieldValue::Str(InfoString::new(value)));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let mut string = InfoString::default();
+        // Writing fails if the value has been truncated, which is accepted
+        let _ = fmt::Write::write_fmt(&mut string, format_args!("{value:?}"));
+        self.push(field.name(), FieldValue::Debug(string));
     }
+}
+
+/// The maximal allowed size of a serialized control message
+pub const MAX_CONTROL_SIZE: usize = 1024;
 
-    fn record_debug(&mut self, _: &Field, _: &dyn Debug) {}
+/// Message sent by the feo-tracer to a connected client
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Control<'a> {
+    /// Replace the filter directives set at runtime, which are added to the filter
+    /// given at initialization. Empty directives restore the initial filter.
+    SetFilter(&'a str),
+}
+
+/// First packet sent on a connection to identify the client
+///
+/// The trace packets following on the connection are attributed to this process.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Hello {
+    pub pid: u32,
+    pub name: Option<InfoString>,
+}
+
+impl Hello {
+    /// Identify the current process
+    pub fn this() -> Self {
+        let name = std::fs::read_to_string("/proc/self/comm")
+            .ok()
+            .map(|name| InfoString::new(name.trim_end()));
+        Self {
+            pid: process::id(),
+            name,
+        }
+    }
 }
 
 /// A trace packet
@@ -129,21 +369,16 @@ fn timestamp() -> u64 {
 }
 
 /// Return the byte length of the given utf-8 encoded string slice
-/// truncated to fit into the specified maximal length in bytes
+/// truncated at a character boundary to fit into the specified maximal length in bytes
 fn trunc_len(slice: &str, max_byte_len: usize) -> usize {
-    let mut len: usize = 0;
-    for (pos, _) in slice.char_indices() {
-        if pos >= max_byte_len {
-            break;
-        }
-        if pos + 1 > len && pos < max_byte_len {
-            len = pos + 1;
-        }
+    let mut len = slice.len().min(max_byte_len);
+    while !slice.is_char_boundary(len) {
+        len -= 1;
     }
     len
 }
 
-mod thread {
+pub(crate) mod thread {
     /// The type of a thread id
     pub(crate) type ThreadId = u32;
 
@@ -153,3 +388,87 @@ mod thread {
         unsafe { libc::gettid() as u32 }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Serialize and deserialize the given packet as sent by the subscriber
+    fn round_trip(packet: &TracePacket) -> TracePacket {
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        let serialized = postcard::to_slice_cobs(packet, &mut buffer[..]).unwrap();
+        postcard::from_bytes_cobs(serialized).unwrap()
+    }
+
+    #[test]
+    fn test_event_info_round_trip() {
+        let long = "x".repeat(2 * MAX_INFO_SIZE);
+        let mut info = EventInfo::default();
+        info.push("i64", FieldValue::I64(-1));
+        info.push("u64", FieldValue::U64(u64::MAX));
+        info.push("f64", FieldValue::F64(0.5));
+        info.push("bool", FieldValue::Bool(true));
+        info.push("str", FieldValue::Str(InfoString::new(&long)));
+        info.push("debug", FieldValue::Debug(InfoString::new("Some(1)")));
+
+        let packet = TracePacket::new(
+            42,
+            Some(Process { pid: 1, tid: 2 }),
+            TraceData::NewSpan {
+                id: 3,
+                name: InfoString::new("span"),
+                info,
+            },
+        );
+        let decoded = round_trip(&packet);
+        assert_eq!(decoded.timestamp, 42);
+        assert!(matches!(decoded.process, Some(Process { pid: 1, tid: 2 })));
+        let TraceData::NewSpan { id, name, info } = decoded.data else {
+            panic!("unexpected data {:?}", decoded.data);
+        };
+        assert_eq!(id, 3);
+        assert_eq!(name.as_str(), "span");
+        let TraceData::NewSpan { info: sent, .. } = packet.data else {
+            unreachable!()
+        };
+        assert!(info.fields().eq(sent.fields()));
+        assert_eq!(info.fields().count(), 6);
+        let truncated = info.fields().nth(4).unwrap();
+        assert_eq!(
+            truncated.value,
+            FieldValue::Str(InfoString::new(&long[..MAX_INFO_SIZE]))
+        );
+    }
+
+    #[test]
+    fn test_empty_and_full_event_info_round_trip() {
+        let packet = TracePacket::new(
+            0,
+            None,
+            TraceData::Event {
+                parent_span: None,
+                name: InfoString::default(),
+                info: EventInfo::default(),
+            },
+        );
+        let decoded = round_trip(&packet);
+        assert!(decoded.process.is_none());
+        let TraceData::Event { info, .. } = decoded.data else {
+            panic!("unexpected data {:?}", decoded.data);
+        };
+        assert_eq!(info.fields().count(), 0);
+
+        // Fields beyond the maximum are dropped
+        let mut info = EventInfo::default();
+        for i in 0..MAX_FIELDS as u64 + 2 {
+            info.push("field", FieldValue::U64(i));
+        }
+        let packet = TracePacket::new(0, None, TraceData::Record { span: 1, info });
+        let TraceData::Record { info, .. } = round_trip(&packet).data else {
+            panic!("unexpected data");
+        };
+        let values: Vec<_> = info.fields().map(|field| field.value).collect();
+        let expected: Vec<_> = (0..MAX_FIELDS as u64).map(FieldValue::U64).collect();
+        assert_eq!(values, expected);
+    }
+}
@@ -2,27 +2,44 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::endpoint::Endpoint;
+use crate::filter::{DynamicFilter, Filter};
+use crate::flight_recorder::{
+    self, FlightRecorder, FlightSink, FLIGHT_RECORDER_ENV, FLIGHT_RECORDER_PACKETS,
+};
 use crate::protocol::{
-    truncate, EventInfo, TraceData, TracePacket, MAX_INFO_SIZE, MAX_PACKET_SIZE,
+    Control, CounterValue, EventInfo, FieldValue, Hello, InfoString, Process, TraceData,
+    TracePacket, MAX_CONTROL_SIZE, MAX_PACKET_SIZE,
 };
+use crate::COUNTER_TARGET;
+use core::cell::OnceCell;
 use core::sync::atomic;
-use core::sync::atomic::AtomicBool;
+use core::sync::atomic::{AtomicBool, AtomicU64};
 use core::time::Duration;
-use feo_log::error;
-use std::io::Write;
+use crossbeam_queue::ArrayQueue;
+use feo_log::{error, info};
+use postcard::accumulator::{CobsAccumulator, FeedResult};
+use std::io::{Read, Write};
+use std::net::TcpStream;
 use std::os::unix::net::UnixStream;
-use std::sync::{mpsc, Arc};
-use std::thread::JoinHandle;
-use std::{io, thread};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread::{JoinHandle, Thread};
+use std::{env, io, process, thread};
 use tracing::level_filters::LevelFilter;
 use tracing::span;
 use tracing::subscriber::set_global_default;
 
-/// The unix socket path used by the tracing daemon to receive trace packets
-pub const UNIX_PACKET_PATH: &str = "/tmp/feo-tracer.sock";
+/// Size of the buffer (number of packets) of each thread for transmitting trace packets to the serializing thread
+const THREAD_BUFFER_SIZE: usize = 512;
+
+/// Maximal number of packets taken from each thread buffer in one round of the serializing thread
+const THREAD_BATCH_SIZE: usize = 64;
 
-/// Size of the channel (number of packets) for transmitting trace packets to the serializing thread
-const MPSC_CHANNEL_BOUND: usize = 512;
+/// Maximal time the serializing thread blocks while the thread buffers are empty
+///
+/// Bounds the latency of periodic flushes and dump requests, which do not wake up the thread.
+const IDLE_TIMEOUT: Duration = Duration::from_millis(100);
 
 /// Size of the buffer (bytes) for transmitting serialized packets to the trace daemon
 const BUFWRITER_SIZE: usize = 512 * MAX_PACKET_SIZE;
@@ -30,34 +47,116 @@ const BUFWRITER_SIZE: usize = 512 * MAX_PACKET_SIZE;
 /// Size of the maximal time interval after which to flush packets to the daemon
 const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
 
-/// Initialize the tracing subscriber with the given level
-pub fn init(level: LevelFilter) {
-    let (sender, receiver) = mpsc::sync_channel::<TracePacket>(MPSC_CHANNEL_BOUND);
-    let enabled = Arc::new(AtomicBool::new(true));
+/// Time interval between attempts to connect to the daemon
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Buffers of all threads emitting trace packets
+pub(crate) type ThreadBuffers = Arc<Mutex<Vec<Arc<ThreadBuffer>>>>;
+
+thread_local! {
+    /// Buffer of the current thread, registered at the subscriber on first use
+    static THREAD_BUFFER: OnceCell<Arc<ThreadBuffer>> = const { OnceCell::new() };
+}
+
+/// Initialize the tracing subscriber with the given level or filter
+///
+/// The directives of the environment variable [`FILTER_ENV`](crate::filter::FILTER_ENV)
+/// are added to the filter.
+/// The endpoint of the feo-tracer is taken from the environment variable
+/// [`ENDPOINT_ENV`](crate::endpoint::ENDPOINT_ENV), defaulting to a unix socket at
+/// [`UNIX_PACKET_PATH`](crate::endpoint::UNIX_PACKET_PATH).
+/// If the environment variable [`FLIGHT_RECORDER_ENV`] is set, the flight recorder is
+/// used instead, see [`init_flight_recorder`].
+pub fn init(filter: impl Into<Filter>) {
+    if let Some(directory) = env::var_os(FLIGHT_RECORDER_ENV) {
+        init_flight_recorder(filter, FLIGHT_RECORDER_PACKETS, directory);
+        return;
+    }
+    let endpoint = Endpoint::from_env().unwrap_or_else(|e| {
+        error!("{e}, using {}", Endpoint::default());
+        Endpoint::default()
+    });
+    init_with_endpoint(filter, endpoint);
+}
+
+/// Initialize the tracing subscriber with the given level or filter, sending to the feo-tracer at the given endpoint
+///
+/// The feo-tracer may add filter directives at runtime.
+pub fn init_with_endpoint(filter: impl Into<Filter>, endpoint: Endpoint) {
+    let buffers = ThreadBuffers::default();
+    let filter = Arc::new(DynamicFilter::new(filter.into()));
+    // Tracing is enabled as soon as the connection to the daemon is established
+    let enabled = Arc::new(AtomicBool::new(false));
 
     // Spawn thread for serializing trace packets and sending to the trace daemon
-    let _thread = {
+    let forwarder = {
         let enabled = Arc::clone(&enabled);
-        thread::spawn(|| Subscriber::thread_main(receiver, enabled))
+        let buffers = Arc::clone(&buffers);
+        let filter = Arc::clone(&filter);
+        thread::spawn(|| Subscriber::thread_main(endpoint, buffers, filter, enabled))
     };
 
     let subscriber = Subscriber {
-        max_level: level,
+        filter,
         enabled,
-        _thread,
-        sender,
+        thread: forwarder,
+        buffers,
+    };
+    set_global_default(subscriber).expect("setting tracing default failed");
+}
+
+/// Initialize the tracing subscriber with the given level or filter, keeping the last `packets`
+/// trace packets in memory instead of sending them to the feo-tracer
+///
+/// The packets are dumped into a new file in `directory` on panic, on the signal
+/// [`DUMP_SIGNAL`](crate::flight_recorder::DUMP_SIGNAL) and on [`request_dump`](crate::request_dump).
+/// The signal is not handled if the process installed a handler of it before.
+/// The files are converted by `feo-tracer --input`.
+pub fn init_flight_recorder(
+    filter: impl Into<Filter>,
+    packets: usize,
+    directory: impl Into<PathBuf>,
+) {
+    let buffers = ThreadBuffers::default();
+    let recorder = Arc::new(FlightRecorder::new(packets, directory.into()));
+    flight_recorder::install(Arc::clone(&recorder), Arc::clone(&buffers));
+
+    // Spawn thread for serializing trace packets into the flight recorder
+    let forwarder = {
+        let buffers = Arc::clone(&buffers);
+        thread::spawn(move || {
+            let mut buffer = [0u8; MAX_PACKET_SIZE];
+            let error = Subscriber::forward(&buffers, &mut FlightSink::new(recorder), &mut buffer);
+            error!("Flight recorder failed: {error:?}");
+        })
+    };
+
+    let subscriber = Subscriber {
+        filter: Arc::new(DynamicFilter::new(filter.into())),
+        enabled: Arc::new(AtomicBool::new(true)),
+        thread: forwarder,
+        buffers,
     };
     set_global_default(subscriber).expect("setting tracing default failed");
 }
 
-/// A subscriber sending trace data to the feo-tracer via unix socket and postcard serialized data.
+/// A subscriber sending trace data to the feo-tracer via unix or TCP socket and postcard serialized data.
+///
+/// The subscriber connects to the feo-tracer in the background and reconnects whenever the
+/// connection is lost. Trace data emitted while not connected is discarded.
+/// In flight recorder mode, the trace data is kept in memory instead.
+///
+/// Each thread pushes its packets into a lock-free buffer of its own. Packets that do not fit
+/// into the buffer are dropped and reported to the feo-tracer by a [`TraceData::Dropped`] packet.
+/// The serializing thread is parked while all buffers are empty and unparked on every push.
 ///
 /// See the `TraceData` and `TracePacket` types for the data format.
-struct Subscriber {
-    max_level: LevelFilter,
+pub(crate) struct Subscriber {
+    filter: Arc<DynamicFilter>,
     enabled: Arc<AtomicBool>,
-    _thread: JoinHandle<()>,
-    sender: mpsc::SyncSender<TracePacket>,
+    /// Serializing thread, taking the packets from the thread buffers
+    thread: JoinHandle<()>,
+    buffers: ThreadBuffers,
 }
 
 impl Subscriber {
@@ -72,50 +171,209 @@ impl Subscriber {
         span::Id::from_u64(id)
     }
 
-    fn thread_main(receiver: mpsc::Receiver<TracePacket>, enabled: Arc<AtomicBool>) {
-        let connection = match UnixStream::connect(UNIX_PACKET_PATH) {
-            Ok(connection) => connection,
-            Err(e) => {
-                error!("Failed to connect to feo-tracer: {:?}, aborting", e);
-                // disable further tracing (TODO: add a time period of retrying)
-                enabled.store(false, atomic::Ordering::Relaxed);
-                return;
-            }
-        };
-
+    fn thread_main(
+        endpoint: Endpoint,
+        buffers: ThreadBuffers,
+        filter: Arc<DynamicFilter>,
+        enabled: Arc<AtomicBool>,
+    ) {
         // Create buffer for serialization
         let mut buffer = [0u8; MAX_PACKET_SIZE];
 
-        // Create BufferedWriter for socket
-        let mut socket_writer = io::BufWriter::with_capacity(BUFWRITER_SIZE, connection);
-        let mut last_flush = std::time::Instant::now();
+        loop {
+            let (connection, control) = Self::connect(&endpoint);
+
+            // Restore the initial filter and receive the directives of this daemon
+            if let Err(e) = filter.set("") {
+                error!("Failed to reset trace filter: {e}");
+            }
+            {
+                let filter = Arc::clone(&filter);
+                thread::spawn(move || Self::receive_control(control, &filter));
+            }
+
+            // Create BufferedWriter for socket
+            let mut sink = SocketSink {
+                writer: io::BufWriter::with_capacity(BUFWRITER_SIZE, connection),
+                last_flush: std::time::Instant::now(),
+            };
+
+            // Discard the packets emitted before or during a previous connection
+            for thread in buffers
+                .lock()
+                .expect("failed to lock thread buffers")
+                .iter()
+            {
+                thread.clear();
+            }
+
+            // Identify this process and start tracing
+            let hello = postcard::to_slice_cobs(&Hello::this(), &mut buffer[..])
+                .expect("failed to serialize hello packet");
+            let error = match sink.send(hello) {
+                Ok(()) => {
+                    enabled.store(true, atomic::Ordering::Relaxed);
+                    Self::forward(&buffers, &mut sink, &mut buffer)
+                }
+                Err(error) => error,
+            };
+
+            error!("Failed to send to feo-tracer at {endpoint}: {error:?}, reconnecting");
+            enabled.store(false, atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Connect to the daemon, retrying until it is available
+    ///
+    /// Returns the writing and reading side of the connection.
+    fn connect(endpoint: &Endpoint) -> (Box<dyn Write + Send>, Box<dyn Read + Send>) {
+        let mut logged = false;
+        loop {
+            let connection = match endpoint {
+                Endpoint::Unix(path) => UnixStream::connect(path).and_then(|connection| {
+                    let reader: Box<dyn Read + Send> = Box::new(connection.try_clone()?);
+                    let writer: Box<dyn Write + Send> = Box::new(connection);
+                    Ok((writer, reader))
+                }),
+                Endpoint::Tcp(address) => TcpStream::connect(address).and_then(|connection| {
+                    let reader: Box<dyn Read + Send> = Box::new(connection.try_clone()?);
+                    let writer: Box<dyn Write + Send> = Box::new(connection);
+                    Ok((writer, reader))
+                }),
+            };
+            match connection {
+                Ok(connection) => return connection,
+                Err(e) if !logged => {
+                    error!("Failed to connect to feo-tracer at {endpoint}: {e:?}, retrying");
+                    logged = true;
+                }
+                Err(_) => (),
+            }
+            thread::sleep(RECONNECT_INTERVAL);
+        }
+    }
+
+    /// Apply the control messages of the daemon until the connection is closed
+    fn receive_control(mut control: Box<dyn Read + Send>, filter: &DynamicFilter) {
+        let mut read_buffer = [0u8; MAX_CONTROL_SIZE];
+        let mut cobs_buffer: CobsAccumulator<MAX_CONTROL_SIZE> = CobsAccumulator::new();
 
         loop {
-            let packet = receiver
-                .recv()
-                .expect("trace subscriber failed to receive, aborting");
+            let len = match control.read(&mut read_buffer) {
+                Ok(0) | Err(_) => return,
+                Ok(len) => len,
+            };
+
+            let mut remaining = &read_buffer[..len];
+            while !remaining.is_empty() {
+                remaining = match cobs_buffer.feed_ref::<Control>(remaining) {
+                    FeedResult::Consumed => break,
+                    FeedResult::OverFull(remaining) | FeedResult::DeserError(remaining) => {
+                        error!("Ignoring invalid control message from feo-tracer");
+                        remaining
+                    }
+                    FeedResult::Success { data, remaining } => {
+                        match data {
+                            Control::SetFilter(directives) => match filter.set(directives) {
+                                Ok(()) => info!("Set trace filter directives '{directives}'"),
+                                Err(e) => error!("{e}"),
+                            },
+                        }
+                        remaining
+                    }
+                };
+            }
+        }
+    }
+
+    /// Forward packets from the thread buffers to the sink until sending fails
+    ///
+    /// Must be called on the serializing thread, which is unparked by the thread buffers.
+    pub(crate) fn forward(
+        buffers: &ThreadBuffers,
+        sink: &mut impl PacketSink,
+        buffer: &mut [u8; MAX_PACKET_SIZE],
+    ) -> io::Error {
+        // Packets taken from the thread buffers in one round, tagged with the index of their buffer
+        let mut packets = Vec::new();
 
+        loop {
+            match Self::drain(buffers, sink, buffer, &mut packets, THREAD_BATCH_SIZE) {
+                Ok(true) => (),
+                Ok(false) => thread::park_timeout(IDLE_TIMEOUT),
+                Err(error) => return error,
+            }
+            if let Err(error) = sink.poll() {
+                return error;
+            }
+        }
+    }
+
+    /// Send up to `batch_size` packets of each thread buffer to the sink
+    ///
+    /// Returns whether any packets were taken.
+    pub(crate) fn drain(
+        buffers: &ThreadBuffers,
+        sink: &mut impl PacketSink,
+        buffer: &mut [u8; MAX_PACKET_SIZE],
+        packets: &mut Vec<(usize, TracePacket)>,
+        batch_size: usize,
+    ) -> io::Result<bool> {
+        let threads = {
+            let mut buffers = buffers.lock().expect("failed to lock thread buffers");
+            // Release the buffers of exited threads after all their packets have been sent
+            buffers.retain(|thread| Arc::strong_count(thread) > 1 || !thread.is_drained());
+            buffers.clone()
+        };
+        Self::drain_threads(&threads, sink, buffer, packets, batch_size)
+    }
+
+    /// Send up to `batch_size` packets of each of the given thread buffers to the sink
+    ///
+    /// Returns whether any packets were taken.
+    pub(crate) fn drain_threads(
+        threads: &[Arc<ThreadBuffer>],
+        sink: &mut impl PacketSink,
+        buffer: &mut [u8; MAX_PACKET_SIZE],
+        packets: &mut Vec<(usize, TracePacket)>,
+        batch_size: usize,
+    ) -> io::Result<bool> {
+        // Take a batch of packets from each thread, preceded by a report of previously dropped packets
+        for (index, thread) in threads.iter().enumerate() {
+            packets.extend(thread.take_dropped().map(|packet| (index, packet)));
+            packets.extend(
+                core::iter::from_fn(|| thread.queue.pop())
+                    .take(batch_size)
+                    .map(|packet| (index, packet)),
+            );
+        }
+        let taken = !packets.is_empty();
+
+        // Interleave the packets of all threads in the order of their creation
+        packets.sort_by_key(|(_, packet)| packet.timestamp);
+
+        for (index, packet) in packets.drain(..) {
             let serialized = match postcard::to_slice_cobs(&packet, &mut buffer[..]) {
                 Ok(serialized) => serialized,
                 Err(e) => {
                     error!("Failed to serialize trace packet: {e:?}");
+                    threads[index].drop_packets(packet.timestamp, 1);
                     continue;
                 }
             };
-
-            let ret = socket_writer.write_all(serialized);
-            if let Err(error) = ret {
-                error!("Failed to send to feo-tracer: {error:?}, aborting");
-                enabled.store(false, atomic::Ordering::Relaxed);
-                return;
-            }
-
-            // Flush, if pre-defined time interval elapsed or insufficient spare capacity
-            if last_flush.elapsed() > FLUSH_INTERVAL {
-                socket_writer.flush().expect("failed to flush");
-                last_flush = std::time::Instant::now();
-            }
+            sink.send(serialized)?;
         }
+        Ok(taken)
+    }
+
+    /// Register the buffer of the current thread
+    fn register_thread(&self) -> Arc<ThreadBuffer> {
+        let thread = Arc::new(ThreadBuffer::new(self.thread.thread().clone()));
+        self.buffers
+            .lock()
+            .expect("failed to lock thread buffers")
+            .push(Arc::clone(&thread));
+        thread
     }
 
     // Send a value to the tracer
@@ -123,34 +381,129 @@ impl Subscriber {
         if !self.enabled.load(atomic::Ordering::Relaxed) {
             return;
         }
-        if let Err(e) = self.sender.send(packet) {
-            error!("Failed to connect to feo-tracer: {:?}, aborting", e);
-            self.enabled.store(false, atomic::Ordering::Relaxed);
+        // Packets emitted while the thread local storage is being destroyed are ignored
+        let _ = THREAD_BUFFER.try_with(|thread| {
+            thread.get_or_init(|| self.register_thread()).push(packet);
+        });
+    }
+}
+
+/// Destination of the serialized trace packets
+pub(crate) trait PacketSink {
+    /// Send a serialized packet
+    fn send(&mut self, packet: &[u8]) -> io::Result<()>;
+
+    /// Called after each round of draining the thread buffers
+    fn poll(&mut self) -> io::Result<()>;
+}
+
+/// Sink sending the packets to the daemon
+struct SocketSink {
+    writer: io::BufWriter<Box<dyn Write + Send>>,
+    last_flush: std::time::Instant,
+}
+
+impl PacketSink for SocketSink {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.writer.write_all(packet)
+    }
+
+    fn poll(&mut self) -> io::Result<()> {
+        // Flush, if pre-defined time interval elapsed
+        if self.last_flush.elapsed() > FLUSH_INTERVAL {
+            self.writer.flush()?;
+            self.last_flush = std::time::Instant::now();
+        }
+        Ok(())
+    }
+}
+
+/// Buffer of the trace packets emitted by a single thread
+pub(crate) struct ThreadBuffer {
+    /// Id of the emitting thread
+    tid: u32,
+    /// Packets not yet taken by the serializing thread
+    queue: ArrayQueue<TracePacket>,
+    /// Number of packets dropped since the last report
+    dropped: AtomicU64,
+    /// Timestamp of the first packet dropped since the last report
+    first_dropped: AtomicU64,
+    /// Serializing thread taking the packets
+    forwarder: Thread,
+}
+
+impl ThreadBuffer {
+    /// Create the buffer of the current thread, drained by the given serializing thread
+    fn new(forwarder: Thread) -> Self {
+        Self {
+            tid: crate::protocol::thread::id(),
+            queue: ArrayQueue::new(THREAD_BUFFER_SIZE),
+            dropped: AtomicU64::new(0),
+            first_dropped: AtomicU64::new(0),
+            forwarder,
+        }
+    }
+
+    /// Push a packet, dropping it if the buffer is full, and wake up the serializing thread
+    fn push(&self, packet: TracePacket) {
+        if let Err(packet) = self.queue.push(packet) {
+            self.drop_packets(packet.timestamp, 1);
+        }
+        // Only an atomic swap, unless the serializing thread is parked
+        self.forwarder.unpark();
+    }
+
+    /// Account for lost packets, the first of which was emitted at the given timestamp
+    fn drop_packets(&self, timestamp: u64, packets: u64) {
+        if self.dropped.fetch_add(packets, atomic::Ordering::Relaxed) == 0 {
+            self.first_dropped
+                .store(timestamp, atomic::Ordering::Relaxed);
         }
     }
+
+    /// Take the report of the packets dropped since the last report, if any
+    fn take_dropped(&self) -> Option<TracePacket> {
+        let timestamp = self.first_dropped.load(atomic::Ordering::Relaxed);
+        let packets = self.dropped.swap(0, atomic::Ordering::Relaxed);
+        (packets > 0).then(|| {
+            let process = Process {
+                pid: process::id(),
+                tid: self.tid,
+            };
+            TracePacket::new(timestamp, Some(process), TraceData::Dropped { packets })
+        })
+    }
+
+    /// Discard all packets and drop reports
+    fn clear(&self) {
+        while self.queue.pop().is_some() {}
+        self.dropped.store(0, atomic::Ordering::Relaxed);
+    }
+
+    /// Check if all packets and drop reports have been taken
+    fn is_drained(&self) -> bool {
+        self.queue.is_empty() && self.dropped.load(atomic::Ordering::Relaxed) == 0
+    }
 }
 
 impl tracing::Subscriber for Subscriber {
     fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
-        // A span or event is enabled if it is at or below the configured
-        // maximum level
-        metadata.level() <= &self.max_level
+        // A span or event is enabled if it is at or below the level
+        // of the most specific matching filter directive
+        self.filter.enabled(metadata)
     }
 
     fn max_level_hint(&self) -> Option<LevelFilter> {
-        Some(self.max_level)
+        Some(self.filter.max_level())
     }
 
     fn new_span(&self, span: &span::Attributes) -> span::Id {
         let id = self.new_span_id();
-        let mut name = [0u8; MAX_INFO_SIZE];
-        let name_len = truncate(span.metadata().name(), &mut name);
         let mut info = EventInfo::default();
         span.record(&mut info);
         let trace_data = TraceData::NewSpan {
             id: id.into_u64(),
-            name,
-            name_len,
+            name: InfoString::new(span.metadata().name()),
             info,
         };
         let trace_packet = TracePacket::now_with_data(trace_data);
@@ -158,9 +511,12 @@ impl tracing::Subscriber for Subscriber {
         id
     }
 
-    fn record(&self, span: &span::Id, _: &span::Record) {
+    fn record(&self, span: &span::Id, values: &span::Record) {
+        let mut info = EventInfo::default();
+        values.record(&mut info);
         let trace_data = TraceData::Record {
             span: span.into_u64(),
+            info,
         };
         let trace_packet = TracePacket::now_with_data(trace_data);
         self.send(trace_packet);
@@ -169,14 +525,18 @@ impl tracing::Subscriber for Subscriber {
     fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
 
     fn event(&self, event: &tracing::Event) {
-        let mut name = [0u8; MAX_INFO_SIZE];
-        let name_len = truncate(event.metadata().name(), &mut name);
         let mut info = EventInfo::default();
         event.record(&mut info);
+        if event.metadata().target() == COUNTER_TARGET {
+            match counter(&info) {
+                Some(trace_data) => self.send(TracePacket::now_without_process(trace_data)),
+                None => error!("Ignoring counter without name or numeric value: {info:?}"),
+            }
+            return;
+        }
         let trace_data = TraceData::Event {
             parent_span: self.current_span().id().map(|id| id.into_u64()),
-            name,
-            name_len,
+            name: InfoString::new(event.metadata().name()),
             info,
         };
         let trace_packet = TracePacket::now_with_data(trace_data);
@@ -199,3 +559,165 @@ impl tracing::Subscriber for Subscriber {
         self.send(trace_packet);
     }
 }
+
+/// Extract name and value of a counter emitted by [`crate::counter!`]
+fn counter(info: &EventInfo) -> Option<TraceData> {
+    let mut name = None;
+    let mut value = None;
+    for field in info.fields() {
+        match (field.name.as_str(), &field.value) {
+            ("counter", FieldValue::Str(counter)) => name = Some(*counter),
+            ("value", field_value) => value = CounterValue::from_field(field_value),
+            _ => (),
+        }
+    }
+    Some(TraceData::Counter {
+        name: name?,
+        value: value?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Sink deserializing the sent packets
+    #[derive(Default)]
+    struct TestSink(Vec<TracePacket>);
+
+    impl PacketSink for TestSink {
+        fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+            let packet = postcard::from_bytes_cobs(&mut packet.to_vec()).unwrap();
+            self.0.push(packet);
+            Ok(())
+        }
+
+        fn poll(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Packet entering a span with the given id, to tell packets apart
+    fn packet(timestamp: u64, span: u64) -> TracePacket {
+        TracePacket::new(timestamp, None, TraceData::Enter { span })
+    }
+
+    fn thread_buffer() -> Arc<ThreadBuffer> {
+        Arc::new(ThreadBuffer::new(thread::current()))
+    }
+
+    /// Drain the given thread buffers once, returning the sent packets
+    fn drain_threads(threads: &[Arc<ThreadBuffer>], batch_size: usize) -> Vec<TracePacket> {
+        let mut sink = TestSink::default();
+        Subscriber::drain_threads(
+            threads,
+            &mut sink,
+            &mut [0; MAX_PACKET_SIZE],
+            &mut Vec::new(),
+            batch_size,
+        )
+        .unwrap();
+        sink.0
+    }
+
+    #[test]
+    fn test_overflow_reported() {
+        let thread = thread_buffer();
+        let overflow = 3;
+        let pushed = (THREAD_BUFFER_SIZE + overflow) as u64;
+        for timestamp in 1..=pushed {
+            thread.push(packet(timestamp, timestamp));
+        }
+
+        // The first round reports the dropped packets ahead of the packets still buffered
+        let threads = [thread];
+        let first = drain_threads(&threads, THREAD_BATCH_SIZE);
+        assert_eq!(first.len(), THREAD_BATCH_SIZE + 1);
+        let report = first.last().unwrap();
+        assert!(matches!(
+            report.data,
+            TraceData::Dropped { packets } if packets == overflow as u64
+        ));
+        assert_eq!(report.timestamp, THREAD_BUFFER_SIZE as u64 + 1);
+        assert_eq!(report.process.as_ref().unwrap().pid, process::id());
+
+        // The remaining packets follow in order without another report
+        let mut packets = first;
+        loop {
+            let round = drain_threads(&threads, THREAD_BATCH_SIZE);
+            if round.is_empty() {
+                break;
+            }
+            packets.extend(round);
+        }
+        let dropped: Vec<_> = packets
+            .iter()
+            .filter(|packet| matches!(packet.data, TraceData::Dropped { .. }))
+            .collect();
+        assert_eq!(dropped.len(), 1);
+        let timestamps: Vec<_> = packets
+            .iter()
+            .filter(|packet| matches!(packet.data, TraceData::Enter { .. }))
+            .map(|packet| packet.timestamp)
+            .collect();
+        assert_eq!(
+            timestamps,
+            (1..=THREAD_BUFFER_SIZE as u64).collect::<Vec<_>>()
+        );
+        assert!(threads[0].is_drained());
+    }
+
+    #[test]
+    fn test_threads_interleaved() {
+        let threads = [thread_buffer(), thread_buffer()];
+        for (index, timestamps) in [[1, 4, 5], [2, 3, 6]].iter().enumerate() {
+            for timestamp in timestamps {
+                threads[index].push(packet(*timestamp, index as u64));
+            }
+        }
+
+        let packets = drain_threads(&threads, THREAD_BATCH_SIZE);
+        let sent: Vec<_> = packets
+            .iter()
+            .map(|packet| match packet.data {
+                TraceData::Enter { span } => (packet.timestamp, span),
+                ref other => panic!("unexpected packet {other:?}"),
+            })
+            .collect();
+        assert_eq!(sent, [(1, 0), (2, 1), (3, 1), (4, 0), (5, 0), (6, 1)]);
+    }
+
+    #[test]
+    fn test_exited_threads_released() {
+        let running = thread_buffer();
+        let exited = thread_buffer();
+        let exited_pending = thread_buffer();
+        exited_pending.push(packet(1, 1));
+        let buffers: ThreadBuffers = Arc::new(Mutex::new(vec![
+            Arc::clone(&running),
+            exited,
+            exited_pending,
+        ]));
+
+        let mut sink = TestSink::default();
+        let mut drain = || {
+            Subscriber::drain(
+                &buffers,
+                &mut sink,
+                &mut [0; MAX_PACKET_SIZE],
+                &mut Vec::new(),
+                THREAD_BATCH_SIZE,
+            )
+            .unwrap()
+        };
+
+        // The buffer of an exited thread is kept until its packets have been sent
+        assert!(drain());
+        assert_eq!(buffers.lock().unwrap().len(), 2);
+        assert!(!drain());
+        let remaining = buffers.lock().unwrap().clone();
+        assert_eq!(remaining.len(), 1);
+        assert!(Arc::ptr_eq(&remaining[0], &running));
+        assert_eq!(sink.0.len(), 1);
+    }
+}
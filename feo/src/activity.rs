@@ -5,6 +5,7 @@
 //! Activity and related structs and traits
 use crate::ids::ActivityId;
 use alloc::boxed::Box;
+use core::cell::Cell;
 
 /// Activity trait, to be implemented by any activity intended to run in a WorkerPool
 pub trait Activity {
@@ -34,3 +35,21 @@ impl<T: FnOnce(ActivityId) -> Box<dyn Activity> + Send> ActivityBuilder for T {}
 
 /// [ActivityId] coupled with an [ActivityBuilder].
 pub type ActivityIdAndBuilder = (ActivityId, Box<dyn ActivityBuilder>);
+
+std::thread_local! {
+    /// Whether the activity currently running in this thread requested a recording trigger
+    static RECORDING_TRIGGER_REQUESTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Request recorders to write their black-box buffers to disk
+///
+/// To be called from within one of the [Activity] methods. The request is sent
+/// to the recorders once the method returns.
+pub fn request_recording_trigger() {
+    RECORDING_TRIGGER_REQUESTED.with(|requested| requested.set(true));
+}
+
+/// Take a pending recording trigger request of the current thread
+pub(crate) fn take_recording_trigger_request() -> bool {
+    RECORDING_TRIGGER_REQUESTED.with(|requested| requested.replace(false))
+}
@@ -2,11 +2,12 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::agent::AgentConfig;
 use crate::ids::{ActivityId, AgentId, WorkerId};
 use crate::topicspec::{Direction, TopicSpecification};
 use alloc::vec::Vec;
 use feo_com::interface::{
-    run_backend, ComBackend, ComBackendTopicPrimaryInitialization,
+    configure_namespace, run_backend, ComBackend, ComBackendTopicPrimaryInitialization,
     ComBackendTopicSecondaryInitialization, TopicHandle,
 };
 use std::collections::{HashMap, HashSet};
@@ -16,6 +17,8 @@ use std::collections::{HashMap, HashSet};
 /// # Arguments
 ///
 /// * backend: the com backend to use
+/// * config: the configuration of the agent, giving the com namespace isolating
+///   this application instance from others on the host
 /// * agent_id: the agent id of the primary agent
 /// * topics_specs: Specifications of all topics used in the application
 ///   (i.e., primary and secondary agents)
@@ -24,11 +27,13 @@ use std::collections::{HashMap, HashSet};
 ///   (usually recorder processes)
 pub fn initialize_com_primary(
     backend: ComBackend,
+    config: &impl AgentConfig,
     agent_id: AgentId,
     topic_specs: Vec<TopicSpecification>,
     agent_assignments: &HashMap<AgentId, Vec<(WorkerId, Vec<ActivityId>)>>,
     max_additional_readers: usize,
 ) -> Vec<TopicHandle> {
+    configure_namespace(config.namespace());
     let num_local_requests = local_requests(agent_assignments, agent_id, &topic_specs);
     let num_remote_requests = remote_requests(
         agent_assignments,
@@ -77,13 +82,16 @@ pub fn initialize_com_primary(
 /// # Arguments
 ///
 /// * backend: the com backend to use
+/// * config: the configuration of the agent, giving the com namespace of the application instance
 /// * topics_specs: Specifications of all topics used by this agent
 /// * local_activities: Set of ids of activities executed by this agent
 pub fn initialize_com_secondary(
     backend: ComBackend,
+    config: &impl AgentConfig,
     topic_specs: Vec<TopicSpecification>,
     local_activities: &HashSet<ActivityId>,
 ) -> Vec<TopicHandle> {
+    configure_namespace(config.namespace());
     let mut handles = Vec::with_capacity(topic_specs.len());
     for spec in topic_specs {
         let is_local_write = is_write(local_activities, &spec);
@@ -100,11 +108,14 @@ pub fn initialize_com_secondary(
 /// # Arguments
 ///
 /// * backend: the com backend to use
+/// * config: the configuration of the agent, giving the com namespace of the application instance
 /// * topics_specs: Specifications of all topics possibly recorded
 pub fn initialize_com_recorder(
     backend: ComBackend,
+    config: &impl AgentConfig,
     topic_specs: Vec<TopicSpecification>,
 ) -> Vec<TopicHandle> {
+    configure_namespace(config.namespace());
     let mut handles = Vec::with_capacity(topic_specs.len());
     for spec in topic_specs {
         let init_params = ComBackendTopicSecondaryInitialization::new(spec.topic, backend, false);
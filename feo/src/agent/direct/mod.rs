@@ -11,4 +11,6 @@ pub mod primary;
 pub mod primary_mpsc;
 #[cfg(feature = "recording")]
 pub mod recorder;
+#[cfg(feature = "recording")]
+pub mod replay;
 pub mod secondary;
@@ -5,7 +5,7 @@
 //! Implementation of the primary agent for direct scheduler-to-worker signalling
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::NodeAddress;
+use crate::agent::{AgentConfig, NodeAddress};
 use crate::error::Error;
 use crate::ids::{ActivityId, AgentId, WorkerId};
 use crate::scheduler::Scheduler;
@@ -17,11 +17,14 @@ use crate::worker::Worker;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::time::Duration;
+use feo_com::interface::Namespace;
 use std::collections::HashMap;
 use std::thread::{self, JoinHandle};
 
 /// Configuration of the primary agent
 pub struct PrimaryConfig {
+    /// Id of the primary agent
+    pub id: AgentId,
     /// Cycle time of the step loop
     pub cycle_time: Duration,
     /// Dependencies per activity
@@ -34,6 +37,14 @@ pub struct PrimaryConfig {
     pub timeout: Duration,
     /// Endpoint on which the connector of the scheduler waits for connections
     pub endpoint: NodeAddress,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for PrimaryConfig {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
 }
 
 /// Primary agent
@@ -48,14 +59,22 @@ impl Primary {
     /// Create a new instance
     pub fn new(config: PrimaryConfig) -> Self {
         let PrimaryConfig {
+            id,
             cycle_time,
             activity_dependencies,
             recorder_ids,
             endpoint,
             worker_assignments,
             timeout,
+            namespace: _,
         } = config;
 
+        // The id of the primary is only used to attribute traces
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+        #[cfg(not(feature = "runtime_tracing"))]
+        let _ = id;
+
         // Create worker threads first so that the connector of the scheduler can connect
         let _worker_threads = worker_assignments
             .into_iter()
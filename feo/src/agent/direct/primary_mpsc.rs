@@ -5,6 +5,7 @@
 //! Implementation of the primary agent for mpsc-only signalling
 
 use crate::activity::ActivityIdAndBuilder;
+use crate::agent::AgentConfig;
 use crate::error::Error;
 use crate::ids::{ActivityId, AgentId, WorkerId};
 use crate::scheduler::Scheduler;
@@ -15,11 +16,14 @@ use crate::worker::Worker;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::time::Duration;
+use feo_com::interface::Namespace;
 use std::collections::HashMap;
 use std::thread::{self, JoinHandle};
 
 /// Configuration of the primary agent
 pub struct PrimaryConfig {
+    /// Id of the primary agent
+    pub id: AgentId,
     /// Cycle time of the step loop
     pub cycle_time: Duration,
     /// Dependencies per activity
@@ -30,6 +34,14 @@ pub struct PrimaryConfig {
     pub worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
     /// Receive timeout of the scheduler's connector
     pub timeout: Duration,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for PrimaryConfig {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
 }
 
 /// Primary agent
@@ -44,13 +56,21 @@ impl Primary {
     /// Create a new instance
     pub fn new(config: PrimaryConfig) -> Self {
         let PrimaryConfig {
+            id,
             cycle_time,
             activity_dependencies,
             recorder_ids,
             worker_assignments,
             timeout,
+            namespace: _,
         } = config;
 
+        // The id of the primary is only used to attribute traces
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+        #[cfg(not(feature = "runtime_tracing"))]
+        let _ = id;
+
         let activity_worker_map: HashMap<ActivityId, WorkerId> = worker_assignments
             .iter()
             .flat_map(|(wid, aid_bld)| aid_bld.iter().map(move |id_b| (id_b.0, *wid)))
@@ -4,21 +4,33 @@
 
 //! Implementation of a recorder for direct scheduler-to-worker signalling
 
-use crate::agent::NodeAddress;
+use crate::agent::{AgentConfig, NodeAddress};
 use crate::ids::AgentId;
+use crate::recording::blackbox::{BlackBoxConfig, TriggerHandle};
+use crate::recording::decoupled::DecoupledConfig;
 use crate::recording::recorder::{FileRecorder, RecordingRules};
 use crate::recording::registry::TypeRegistry;
+use crate::recording::rotation::Rotation;
 use crate::signalling::common::interface::ConnectRecorder;
 use crate::signalling::direct::recorder::{TcpRecorderConnector, UnixRecorderConnector};
 use alloc::boxed::Box;
 use core::time::Duration;
+use feo_com::interface::Namespace;
 
 /// Configuration of a recorder
 pub struct RecorderConfig<'r> {
     /// ID of the recorder
     pub id: AgentId,
+    /// Name of the recorded application, written to the recording header
+    pub app_name: &'static str,
     /// File to which to write recorded data
     pub record_file: &'static str,
+    /// Rotation of the recording file into numbered split files
+    pub rotation: Rotation,
+    /// Black-box configuration, keeping data in memory until triggered (overrides rotation)
+    pub black_box: Option<BlackBoxConfig>,
+    /// Decoupled mode, writing on a background thread without delaying the task chain
+    pub decoupled: Option<DecoupledConfig>,
     /// Rules about which data to record
     pub rules: RecordingRules,
     /// Registry with types sent on topics
@@ -27,6 +39,14 @@ pub struct RecorderConfig<'r> {
     pub receive_timeout: Duration,
     /// Endpoint on which the scheduler connector is listening
     pub endpoint: NodeAddress,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for RecorderConfig<'_> {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
 }
 
 /// Recorder agent
@@ -40,13 +60,21 @@ impl<'s> Recorder<'s> {
     pub fn new<'c: 's>(config: RecorderConfig<'c>) -> Self {
         let RecorderConfig {
             id,
+            app_name,
             endpoint,
             receive_timeout,
             record_file,
+            rotation,
+            black_box,
+            decoupled,
             rules,
             registry,
+            namespace: _,
         } = config;
 
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+
         let mut connector = match endpoint {
             NodeAddress::Tcp(addr) => {
                 Box::new(TcpRecorderConnector::new(id, addr)) as Box<dyn ConnectRecorder>
@@ -59,13 +87,28 @@ impl<'s> Recorder<'s> {
             .connect_remote()
             .expect("failed to connect to scheduler");
 
-        let recorder =
-            FileRecorder::new(id, connector, receive_timeout, record_file, rules, registry)
-                .unwrap();
+        let recorder = FileRecorder::new(
+            id,
+            connector,
+            receive_timeout,
+            app_name,
+            record_file,
+            rotation,
+            black_box,
+            decoupled,
+            rules,
+            registry,
+        )
+        .unwrap();
 
         Self { recorder }
     }
 
+    /// Get a handle to trigger the black box from outside of the task chain
+    pub fn trigger_handle(&self) -> TriggerHandle {
+        self.recorder.trigger_handle()
+    }
+
     /// Run the agent
     pub fn run(&mut self) {
         self.recorder.run();
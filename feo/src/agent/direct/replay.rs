@@ -0,0 +1,144 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of a replay agent for direct scheduler-to-worker signalling
+
+use crate::activity::ActivityIdAndBuilder;
+use crate::agent::{AgentConfig, NodeAddress};
+use crate::error::Error;
+use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::recording::registry::TypeRegistry;
+use crate::recording::replay::Replayer;
+use crate::signalling::common::interface::{ConnectScheduler, ConnectWorker};
+use crate::signalling::direct::scheduler::{TcpSchedulerConnector, UnixSchedulerConnector};
+use crate::signalling::direct::worker::{TcpWorkerConnector, UnixWorkerConnector};
+use crate::timestamp;
+use crate::worker::Worker;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::time::Duration;
+use feo_com::interface::Namespace;
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+
+/// Configuration of the replay agent
+pub struct ReplayConfig<'r> {
+    /// Recording file to replay
+    pub record_file: PathBuf,
+    /// Topics on which to publish the recorded data
+    pub topics: Vec<&'static str>,
+    /// Registry with types sent on topics
+    pub registry: &'r TypeRegistry,
+    /// Activities to re-run, in this or in secondary agents
+    pub activities: Vec<ActivityId>,
+    /// IDs of all recorders for which the replay waits
+    pub recorder_ids: Vec<AgentId>,
+    /// Worker assignments to be run in this agent
+    pub worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
+    /// Receive timeout of the replay's connector
+    pub timeout: Duration,
+    /// Endpoint on which the connector of the replay waits for connections
+    pub endpoint: NodeAddress,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for ReplayConfig<'_> {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+}
+
+/// Replay agent
+///
+/// The replay agent takes the place of the primary agent. Instead of scheduling
+/// all activities, it publishes recorded data and re-runs the given activities
+/// in the recorded order.
+pub struct Replay {
+    /// Replayer
+    replayer: Replayer,
+    /// Handles to the worker threads
+    _worker_threads: Vec<JoinHandle<()>>,
+}
+
+impl Replay {
+    /// Create a new instance
+    pub fn new(config: ReplayConfig<'_>) -> Result<Self, Error> {
+        let ReplayConfig {
+            record_file,
+            topics,
+            registry,
+            activities,
+            recorder_ids,
+            worker_assignments,
+            timeout,
+            endpoint,
+            namespace: _,
+        } = config;
+
+        // Create worker threads first so that the connector of the replay can connect
+        let _worker_threads = worker_assignments
+            .into_iter()
+            .map(|(id, activities)| {
+                let endpoint = endpoint.clone();
+                thread::spawn(move || match endpoint {
+                    NodeAddress::Tcp(addr) => {
+                        let mut connector =
+                            TcpWorkerConnector::new(addr, activities.iter().map(|(id, _)| *id));
+                        connector.connect_remote().expect("failed to connect");
+                        let worker = Worker::new(id, activities, connector, timeout);
+                        worker.run().expect("failed to run worker");
+                    }
+                    NodeAddress::UnixSocket(path) => {
+                        let mut connector =
+                            UnixWorkerConnector::new(path, activities.iter().map(|(id, _)| *id));
+                        connector.connect_remote().expect("failed to connect");
+                        let worker = Worker::new(id, activities, connector, timeout);
+                        worker.run().expect("failed to run worker");
+                    }
+                })
+            })
+            .collect();
+
+        let mut connector = match endpoint {
+            NodeAddress::Tcp(addr) => Box::new(TcpSchedulerConnector::new(
+                addr,
+                activities.iter().cloned(),
+                recorder_ids.iter().cloned(),
+            )) as Box<dyn ConnectScheduler>,
+            NodeAddress::UnixSocket(path) => Box::new(UnixSchedulerConnector::new(
+                &path,
+                activities.iter().cloned(),
+                recorder_ids.iter().cloned(),
+            )) as Box<dyn ConnectScheduler>,
+        };
+        connector.connect_remotes().expect("failed to connect");
+
+        let replayer = Replayer::new(
+            &record_file,
+            &topics,
+            registry,
+            activities,
+            timeout,
+            connector,
+            recorder_ids,
+        )?;
+
+        Ok(Self {
+            replayer,
+            _worker_threads,
+        })
+    }
+
+    /// Run the agent until the end of the recording
+    pub fn run(&mut self) -> Result<(), Error> {
+        // Initialize local time
+        timestamp::initialize();
+
+        // Sync time on remotes
+        self.replayer.sync_remotes()?;
+
+        self.replayer.run()
+    }
+}
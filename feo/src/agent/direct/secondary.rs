@@ -5,13 +5,14 @@
 //! Implementation of a secondary agent for direct scheduler-to-worker signalling
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::NodeAddress;
+use crate::agent::{AgentConfig, NodeAddress};
 use crate::ids::{AgentId, WorkerId};
 use crate::signalling::common::interface::ConnectWorker;
 use crate::signalling::direct::worker::{TcpWorkerConnector, UnixWorkerConnector};
 use crate::worker::Worker;
 use alloc::vec::Vec;
 use core::time::Duration;
+use feo_com::interface::Namespace;
 use feo_log::debug;
 use std::thread::{self, JoinHandle};
 
@@ -25,6 +26,14 @@ pub struct SecondaryConfig {
     pub timeout: Duration,
     /// Endpoint on which the scheduler connector is listening
     pub endpoint: NodeAddress,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for SecondaryConfig {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
 }
 
 /// Secondary agent
@@ -43,8 +52,12 @@ impl Secondary {
             worker_assignments,
             timeout,
             endpoint,
+            namespace: _,
         } = config;
 
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+
         let worker_threads = worker_assignments
             .into_iter()
             .map(|(id, activities)| {
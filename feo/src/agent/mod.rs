@@ -9,6 +9,7 @@
 //! across all agents.
 
 use core::net::SocketAddr;
+use feo_com::interface::Namespace;
 use std::path::PathBuf;
 
 pub mod com_init;
@@ -21,3 +22,9 @@ pub enum NodeAddress {
     Tcp(SocketAddr),
     UnixSocket(PathBuf),
 }
+
+/// Agent configuration parameters also used by the com initialization
+pub trait AgentConfig {
+    /// Namespace of the com resources of the application instance
+    fn namespace(&self) -> &Namespace;
+}
@@ -15,4 +15,6 @@
 pub mod primary;
 #[cfg(feature = "recording")]
 pub mod recorder;
+#[cfg(feature = "recording")]
+pub mod replay;
 pub mod secondary;
@@ -5,7 +5,7 @@
 //! Implementation of the primary agent for mixed signalling using sockets and mpsc channels
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::NodeAddress;
+use crate::agent::{AgentConfig, NodeAddress};
 use crate::error::Error;
 use crate::ids::{ActivityId, AgentId, WorkerId};
 use crate::scheduler::Scheduler;
@@ -16,6 +16,7 @@ use crate::worker::Worker;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::time::Duration;
+use feo_com::interface::Namespace;
 use std::collections::HashMap;
 use std::thread::{self, JoinHandle};
 
@@ -41,6 +42,14 @@ pub struct PrimaryConfig {
     pub worker_agent_map: HashMap<WorkerId, AgentId>,
     /// Map of all activities to worker ids
     pub activity_worker_map: HashMap<ActivityId, WorkerId>,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for PrimaryConfig {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
 }
 
 /// Primary agent
@@ -65,8 +74,12 @@ impl Primary {
             timeout,
             worker_agent_map,
             activity_worker_map,
+            namespace: _,
         } = config;
 
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+
         // Create scheduler connector depending on given address types and
         // get worker connector builders to be moved into worker threads
         let (mut connector, mut builders) = match (bind_address_receivers, bind_address_senders) {
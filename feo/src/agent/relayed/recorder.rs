@@ -4,21 +4,33 @@
 
 //! Implementation of a recorder for mixed signalling using sockets and mpsc channels
 
-use crate::agent::NodeAddress;
+use crate::agent::{AgentConfig, NodeAddress};
 use crate::ids::AgentId;
+use crate::recording::blackbox::{BlackBoxConfig, TriggerHandle};
+use crate::recording::decoupled::DecoupledConfig;
 use crate::recording::recorder::{FileRecorder, RecordingRules};
 use crate::recording::registry::TypeRegistry;
+use crate::recording::rotation::Rotation;
 use crate::signalling::common::interface::ConnectRecorder;
 use crate::signalling::relayed::sockets_mpsc::{RecorderConnectorTcp, RecorderConnectorUnix};
 use alloc::boxed::Box;
 use core::time::Duration;
+use feo_com::interface::Namespace;
 
 /// Configuration of a recorder
 pub struct RecorderConfig<'r> {
     /// ID of the recorder
     pub id: AgentId,
+    /// Name of the recorded application, written to the recording header
+    pub app_name: &'static str,
     /// File to which to write recorded data
     pub record_file: &'static str,
+    /// Rotation of the recording file into numbered split files
+    pub rotation: Rotation,
+    /// Black-box configuration, keeping data in memory until triggered (overrides rotation)
+    pub black_box: Option<BlackBoxConfig>,
+    /// Decoupled mode, writing on a background thread without delaying the task chain
+    pub decoupled: Option<DecoupledConfig>,
     /// Rules about which data to record
     pub rules: RecordingRules,
     /// Registry with types sent on topics
@@ -29,6 +41,14 @@ pub struct RecorderConfig<'r> {
     pub bind_address_senders: NodeAddress,
     /// Address on which the scheduler connector is listening for receiver channel connections
     pub bind_address_receivers: NodeAddress,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for RecorderConfig<'_> {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
 }
 
 /// Recorder agent
@@ -42,14 +62,22 @@ impl<'s> Recorder<'s> {
     pub fn new<'c: 's>(config: RecorderConfig<'c>) -> Self {
         let RecorderConfig {
             id,
+            app_name,
             bind_address_senders,
             bind_address_receivers,
             receive_timeout,
             record_file,
+            rotation,
+            black_box,
+            decoupled,
             rules,
             registry,
+            namespace: _,
         } = config;
 
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+
         let mut connector = match (bind_address_receivers, bind_address_senders) {
             (NodeAddress::Tcp(bind_receivers), NodeAddress::Tcp(bind_senders)) => Box::new(
                 RecorderConnectorTcp::new(id, bind_senders, bind_receivers, receive_timeout),
@@ -73,13 +101,28 @@ impl<'s> Recorder<'s> {
             .connect_remote()
             .expect("failed to connect to scheduler");
 
-        let recorder =
-            FileRecorder::new(id, connector, receive_timeout, record_file, rules, registry)
-                .unwrap();
+        let recorder = FileRecorder::new(
+            id,
+            connector,
+            receive_timeout,
+            app_name,
+            record_file,
+            rotation,
+            black_box,
+            decoupled,
+            rules,
+            registry,
+        )
+        .unwrap();
 
         Self { recorder }
     }
 
+    /// Get a handle to trigger the black box from outside of the task chain
+    pub fn trigger_handle(&self) -> TriggerHandle {
+        self.recorder.trigger_handle()
+    }
+
     /// Run the agent
     pub fn run(&mut self) {
         self.recorder.run();
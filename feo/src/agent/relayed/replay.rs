@@ -0,0 +1,170 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of a replay agent for mixed signalling using sockets and mpsc channels
+
+use crate::activity::ActivityIdAndBuilder;
+use crate::agent::{AgentConfig, NodeAddress};
+use crate::error::Error;
+use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::recording::registry::TypeRegistry;
+use crate::recording::replay::Replayer;
+use crate::signalling::common::interface::{ConnectScheduler, ConnectWorker};
+use crate::signalling::relayed::sockets_mpsc::{SchedulerConnectorTcp, SchedulerConnectorUnix};
+use crate::timestamp;
+use crate::worker::Worker;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::time::Duration;
+use feo_com::interface::Namespace;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+
+/// Configuration of the replay agent
+pub struct ReplayConfig<'r> {
+    /// Id of the replay agent
+    pub id: AgentId,
+    /// Recording file to replay
+    pub record_file: PathBuf,
+    /// Topics on which to publish the recorded data
+    pub topics: Vec<&'static str>,
+    /// Registry with types sent on topics
+    pub registry: &'r TypeRegistry,
+    /// IDs of all recorders for which the replay waits
+    pub recorder_ids: Vec<AgentId>,
+    /// Worker assignments to be run in this agent
+    pub worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
+    /// Receive timeout of the replay's connector
+    pub timeout: Duration,
+    /// The socket address to which secondary agents' senders shall connect
+    pub bind_address_senders: NodeAddress,
+    /// The socket address to which secondary agents' receivers shall connect
+    pub bind_address_receivers: NodeAddress,
+    // Map of all workers running replayed activities to agent ids
+    pub worker_agent_map: HashMap<WorkerId, AgentId>,
+    /// Map of all activities to re-run to worker ids
+    pub activity_worker_map: HashMap<ActivityId, WorkerId>,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for ReplayConfig<'_> {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+}
+
+/// Replay agent
+///
+/// The replay agent takes the place of the primary agent. Instead of scheduling
+/// all activities, it publishes recorded data and re-runs the activities
+/// in the activity-worker map in the recorded order.
+pub struct Replay {
+    /// Replayer
+    replayer: Replayer,
+    /// Handles to the worker threads
+    _worker_threads: Vec<JoinHandle<()>>,
+}
+
+impl Replay {
+    /// Create a new instance
+    pub fn new(config: ReplayConfig<'_>) -> Result<Self, Error> {
+        let ReplayConfig {
+            id,
+            record_file,
+            topics,
+            registry,
+            recorder_ids,
+            worker_assignments,
+            timeout,
+            bind_address_senders,
+            bind_address_receivers,
+            worker_agent_map,
+            activity_worker_map,
+            namespace: _,
+        } = config;
+
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+
+        let activities: Vec<ActivityId> = activity_worker_map.keys().copied().collect();
+
+        // Create replay connector depending on given address types and
+        // get worker connector builders to be moved into worker threads
+        let (mut connector, mut builders) = match (bind_address_receivers, bind_address_senders) {
+            (NodeAddress::Tcp(bind_receivers), NodeAddress::Tcp(bind_senders)) => {
+                let mut connector = Box::new(SchedulerConnectorTcp::new(
+                    id,
+                    bind_senders,
+                    bind_receivers,
+                    timeout,
+                    worker_agent_map,
+                    activity_worker_map,
+                    recorder_ids.clone(),
+                ));
+                let builders = connector.worker_connector_builders();
+                (connector as Box<dyn ConnectScheduler>, builders)
+            }
+            (NodeAddress::UnixSocket(bind_receivers), NodeAddress::UnixSocket(bind_senders)) => {
+                let mut connector = Box::new(SchedulerConnectorUnix::new(
+                    id,
+                    bind_senders,
+                    bind_receivers,
+                    timeout,
+                    worker_agent_map,
+                    activity_worker_map,
+                    recorder_ids.clone(),
+                ));
+                let builders = connector.worker_connector_builders();
+                (connector as Box<dyn ConnectScheduler>, builders)
+            }
+            _ => {
+                panic!("bind addresses must either be both TCP socket addresses or both Unix socket paths")
+            }
+        };
+
+        // Create worker threads first so that the connector of the replay can connect
+        let _worker_threads = worker_assignments
+            .into_iter()
+            .map(|(id, activities)| {
+                let connector_builder = builders.remove(&id).expect("missing connector builder");
+                thread::spawn(move || {
+                    let mut connector = connector_builder();
+                    connector.connect_remote().expect("failed to connect");
+                    let worker = Worker::new(id, activities, connector, timeout);
+                    worker.run().expect("failed to run worker");
+                })
+            })
+            .collect();
+
+        connector.connect_remotes().expect("failed to connect");
+
+        let replayer = Replayer::new(
+            &record_file,
+            &topics,
+            registry,
+            activities,
+            timeout,
+            connector,
+            recorder_ids,
+        )?;
+
+        Ok(Self {
+            replayer,
+            _worker_threads,
+        })
+    }
+
+    /// Run the agent until the end of the recording
+    pub fn run(&mut self) -> Result<(), Error> {
+        // Initialize local time
+        timestamp::initialize();
+
+        // Sync time on remotes
+        self.replayer.sync_remotes()?;
+
+        self.replayer.run()
+    }
+}
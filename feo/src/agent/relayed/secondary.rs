@@ -5,7 +5,7 @@
 //! Implementation of a secondary agent for mixed signalling using sockets and mpsc channels
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::NodeAddress;
+use crate::agent::{AgentConfig, NodeAddress};
 use crate::ids::{ActivityId, AgentId, WorkerId};
 use crate::signalling::common::interface::ConnectWorker;
 use crate::signalling::relayed::sockets_mpsc::{SecondaryConnectorTcp, SecondaryConnectorUnix};
@@ -14,6 +14,7 @@ use crate::worker::Worker;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::time::Duration;
+use feo_com::interface::Namespace;
 use feo_log::debug;
 use std::collections::HashMap;
 use std::thread::{self, JoinHandle};
@@ -30,6 +31,14 @@ pub struct SecondaryConfig {
     pub bind_address_senders: NodeAddress,
     /// Address on which the scheduler connector is listening for receiver channel connections
     pub bind_address_receivers: NodeAddress,
+    /// Namespace of the com resources, configured by the com initialization with this config
+    pub namespace: Namespace,
+}
+
+impl AgentConfig for SecondaryConfig {
+    fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
 }
 
 /// Secondary agent
@@ -51,8 +60,12 @@ impl Secondary {
             timeout,
             bind_address_senders,
             bind_address_receivers,
+            namespace: _,
         } = config;
 
+        #[cfg(feature = "runtime_tracing")]
+        crate::trace::set_agent_id(id);
+
         let activity_worker_map: HashMap<ActivityId, WorkerId> = worker_assignments
             .iter()
             .flat_map(|(wid, acts)| acts.iter().map(|(aid, _)| (*aid, *wid)))
@@ -16,6 +16,8 @@ pub enum Error {
     Channel(&'static str),
     ChannelNotFound(ChannelId),
     Io((std::io::Error, &'static str)),
+    #[cfg(feature = "recording")]
+    Recording(crate::recording::reader::ReadError),
     Timeout(Duration, &'static str),
     UnexpectedProtocolSignal,
     UnexpectedSignal(Signal),
@@ -31,6 +33,8 @@ impl core::fmt::Display for Error {
             Error::Channel(description) => write!(f, "channel error: {description}"),
             Error::ChannelNotFound(id) => write!(f, "failed to find channel with ID {id}"),
             Error::Io((e, description)) => write!(f, "{description}: io error: {e}"),
+            #[cfg(feature = "recording")]
+            Error::Recording(e) => write!(f, "recording error: {e}"),
             Error::Timeout(duration, action) => {
                 write!(
                     f,
@@ -50,3 +54,10 @@ impl From<std::io::Error> for Error {
         Error::Io((err, "failed"))
     }
 }
+
+#[cfg(feature = "recording")]
+impl From<crate::recording::reader::ReadError> for Error {
+    fn from(err: crate::recording::reader::ReadError) -> Self {
+        Error::Recording(err)
+    }
+}
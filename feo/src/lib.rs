@@ -43,4 +43,6 @@ pub mod scheduler;
 pub mod signalling;
 mod timestamp;
 pub mod topicspec;
+#[cfg(feature = "runtime_tracing")]
+mod trace;
 pub mod worker;
@@ -0,0 +1,278 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Black-box recording
+//!
+//! In black-box mode, the recorder keeps the records of the most recent task chains in memory
+//! and only writes them to disk when triggered, e.g. by an activity request, a task chain
+//! exceeding the cycle time, a panicking activity or a [`TriggerHandle`].
+//! Every trigger produces a complete recording in a numbered file,
+//! e.g. `rec.0000.bin`, `rec.0001.bin`, ... for the recording file `rec.bin`.
+
+use crate::recording::format::{encode_record, FileHeader, RecordingWriter};
+use crate::recording::recorder::Record;
+use crate::recording::rotation::split_path;
+use crate::signalling::common::signals::TriggerReason;
+use crate::timestamp::Timestamp;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use feo_log::{debug, info};
+use std::fs;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+/// Configuration of black-box recording
+#[derive(Debug, Clone, Copy)]
+pub struct BlackBoxConfig {
+    /// Amount of task chains kept in memory before a trigger
+    pub window: Window,
+    /// Number of task chains to record after a trigger before writing the file
+    pub post_trigger_cycles: u64,
+}
+
+/// Amount of task chains kept in memory
+#[derive(Debug, Clone, Copy)]
+pub enum Window {
+    /// The given number of most recent task chains
+    Cycles(usize),
+    /// All task chains started within the given duration before the most recent one
+    Duration(Duration),
+}
+
+/// Handle to trigger a black-box recorder from outside of the task chain
+///
+/// The handle can be cloned and sent to other threads, e.g. to trigger on user input.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerHandle(Arc<AtomicBool>);
+
+impl TriggerHandle {
+    /// Trigger writing the black-box buffer
+    pub fn trigger(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Take a pending trigger
+    pub(crate) fn take(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Records of a task chain
+struct BufferedCycle {
+    /// Time at which the task chain started
+    timestamp: Timestamp,
+    /// Encoded records of the task chain
+    records: Vec<u8>,
+}
+
+/// In-memory ring buffer of recorded task chains
+pub(crate) struct BlackBox {
+    /// Configuration
+    config: BlackBoxConfig,
+    /// Path of the recording as configured
+    path: PathBuf,
+    /// Buffered task chains, oldest first
+    cycles: VecDeque<BufferedCycle>,
+    /// Pending trigger with the number of task chains still to be recorded
+    pending: Option<(TriggerReason, u64)>,
+    /// Number of files written so far
+    dumps: usize,
+}
+
+impl BlackBox {
+    /// Create an empty black box writing files named after the given recording path
+    pub(crate) fn new(path: impl Into<PathBuf>, config: BlackBoxConfig) -> Self {
+        Self {
+            config,
+            path: path.into(),
+            cycles: VecDeque::new(),
+            pending: None,
+            dumps: 0,
+        }
+    }
+
+    /// Start buffering a new task chain, dropping task chains outside the window
+    pub(crate) fn start_cycle(&mut self, timestamp: Timestamp) {
+        self.cycles.push_back(BufferedCycle {
+            timestamp,
+            records: Vec::new(),
+        });
+
+        // Keep everything from the window before a pending trigger up to writing the file
+        if self.pending.is_some() {
+            return;
+        }
+        match self.config.window {
+            Window::Cycles(max) => {
+                while self.cycles.len() > max.max(1) {
+                    self.cycles.pop_front();
+                }
+            }
+            Window::Duration(max) => {
+                while self
+                    .cycles
+                    .front()
+                    .is_some_and(|front| timestamp.0.saturating_sub(front.timestamp.0) > max)
+                {
+                    self.cycles.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Buffer the given record, followed by the given serialized data
+    ///
+    /// Records before the first task chain are not buffered.
+    pub(crate) fn write_record(&mut self, record: &Record, data: &[u8]) -> io::Result<()> {
+        match self.cycles.back_mut() {
+            Some(cycle) => encode_record(&mut cycle.records, record, data),
+            None => Ok(()),
+        }
+    }
+
+    /// Register a trigger, unless another one is pending
+    pub(crate) fn trigger(&mut self, reason: TriggerReason) {
+        match self.pending {
+            Some((pending, _)) => debug!("Ignoring trigger {reason}, {pending} is pending"),
+            None => {
+                info!("Black box triggered: {reason}");
+                self.pending = Some((reason, self.config.post_trigger_cycles));
+            }
+        }
+    }
+
+    /// Finish buffering the current task chain and write the buffered task chains
+    /// to a new file, if a trigger is due
+    ///
+    /// Returns the path of the written file.
+    pub(crate) fn end_cycle(&mut self, header: &FileHeader) -> io::Result<Option<PathBuf>> {
+        match self.pending.as_mut() {
+            None => Ok(None),
+            Some((_, remaining)) if *remaining > 0 => {
+                *remaining -= 1;
+                Ok(None)
+            }
+            Some(_) => {
+                self.pending = None;
+                self.dump(header).map(Some)
+            }
+        }
+    }
+
+    /// Write the buffered task chains to a new file right away, if a trigger is pending
+    ///
+    /// This is used if no more task chains are expected to finish, e.g. when the primary
+    /// process disconnects. Returns the path of the written file.
+    pub(crate) fn end_pending(&mut self, header: &FileHeader) -> io::Result<Option<PathBuf>> {
+        match self.pending.take() {
+            Some(_) => self.dump(header).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Write all buffered task chains to the next numbered file and clear the buffer
+    fn dump(&mut self, header: &FileHeader) -> io::Result<PathBuf> {
+        let path = split_path(&self.path, self.dumps);
+        self.dumps += 1;
+
+        let file = BufWriter::new(fs::File::create(&path)?);
+        let mut writer = RecordingWriter::new(file, header)?;
+        for cycle in self.cycles.drain(..) {
+            writer.write_cycle(cycle.timestamp, &cycle.records)?;
+        }
+        writer.finish()?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ids::ActivityId;
+    use crate::recording::reader::{RecordEntry, RecordingReader};
+    use crate::recording::recorder::SignalRecord;
+    use crate::recording::test_util::TestDir;
+    use crate::signalling::common::signals::Signal;
+    use crate::timestamp::SyncInfo;
+    use alloc::string::String;
+
+    #[test]
+    fn test_trigger_writes_window() {
+        let dir = TestDir::new("blackbox");
+        let header = FileHeader {
+            app_name: String::from("test"),
+            sync_info: SyncInfo::from(0u64),
+            topics: Vec::new(),
+        };
+        let config = BlackBoxConfig {
+            window: Window::Cycles(3),
+            post_trigger_cycles: 1,
+        };
+        let mut black_box = BlackBox::new(dir.join("rec.bin"), config);
+
+        let mut paths = Vec::new();
+        for cycle in 0..10u64 {
+            let timestamp = Timestamp(Duration::from_millis(cycle));
+            black_box.start_cycle(timestamp);
+            let record = Record::Signal(SignalRecord {
+                timestamp,
+                signal: Signal::TaskChainStart(timestamp),
+            });
+            black_box.write_record(&record, &[]).unwrap();
+            if cycle == 6 {
+                black_box.trigger(TriggerReason::ActivityRequest(ActivityId::from(1)));
+            }
+            paths.extend(black_box.end_cycle(&header).unwrap());
+        }
+
+        // Three cycles before and including the trigger, one cycle after it
+        assert_eq!(paths, [dir.join("rec.0000.bin")]);
+        let mut reader = RecordingReader::open(&paths[0]).unwrap();
+        let cycles: Vec<_> = reader
+            .index()
+            .unwrap()
+            .iter()
+            .map(|e| e.timestamp)
+            .collect();
+        assert_eq!(cycles.len(), 4);
+        let mut starts = Vec::new();
+        while let Some(entry) = reader.next_record().unwrap() {
+            if let RecordEntry::Signal(signal) = entry {
+                starts.push(signal.timestamp);
+            }
+        }
+        assert_eq!(starts, cycles);
+        assert_eq!(starts[0], Timestamp(Duration::from_millis(4)));
+    }
+
+    #[test]
+    fn test_pending_trigger_written_without_end_of_cycle() {
+        let dir = TestDir::new("blackbox");
+        let header = FileHeader {
+            app_name: String::from("test"),
+            sync_info: SyncInfo::from(0u64),
+            topics: Vec::new(),
+        };
+        let config = BlackBoxConfig {
+            window: Window::Cycles(3),
+            post_trigger_cycles: 5,
+        };
+        let mut black_box = BlackBox::new(dir.join("rec.bin"), config);
+        assert_eq!(black_box.end_pending(&header).unwrap(), None);
+
+        // The task chain of the failing activity never ends
+        for cycle in 0..2u64 {
+            black_box.start_cycle(Timestamp(Duration::from_millis(cycle)));
+        }
+        black_box.trigger(TriggerReason::ActivityFailure(ActivityId::from(1)));
+        let path = black_box.end_pending(&header).unwrap();
+        assert_eq!(path, Some(dir.join("rec.0000.bin")));
+        let mut reader = RecordingReader::open(path.unwrap()).unwrap();
+        assert_eq!(reader.index().unwrap().len(), 2);
+        assert_eq!(black_box.end_pending(&header).unwrap(), None);
+    }
+}
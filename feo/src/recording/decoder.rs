@@ -0,0 +1,201 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of recorded data through the type registry
+
+use crate::recording::reader::{ReadError, RecordEntry, RecordingReader};
+use crate::recording::recorder::{DataDescriptionRecord, DroppedCyclesRecord, SignalRecord};
+use crate::recording::registry::TypeRegistry;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::any::Any;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, Read, Write};
+
+/// A decoded value of a registered type
+pub trait Decoded: core::fmt::Debug {
+    /// Convert the value into a JSON value
+    fn to_json(&self) -> serde_json::Result<serde_json::Value>;
+
+    /// Get the value as [`Any`], allowing to downcast it to its concrete type
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Serialize + core::fmt::Debug + 'static> Decoded for T {
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Function deserializing recorded data of a registered type
+pub type DecodeFn = fn(&[u8]) -> Result<Box<dyn Decoded>, postcard::Error>;
+
+/// Deserialize recorded data of the given type
+pub(crate) fn decode<T: DeserializeOwned + Serialize + core::fmt::Debug + 'static>(
+    data: &[u8],
+) -> Result<Box<dyn Decoded>, postcard::Error> {
+    postcard::from_bytes::<T>(data).map(|value| Box::new(value) as Box<dyn Decoded>)
+}
+
+/// Error decoding recorded data
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The recorded type name is not in the registry
+    UnknownType(String),
+    /// The recorded data could not be deserialized as the registered type
+    Deserialize(postcard::Error),
+}
+
+impl core::error::Error for DecodeError {}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DecodeError::UnknownType(type_name) => {
+                write!(f, "type name {type_name} not in registry")
+            }
+            DecodeError::Deserialize(e) => write!(f, "failed to deserialize data: {e}"),
+        }
+    }
+}
+
+/// A record with data decoded through the type registry
+#[derive(Debug)]
+pub enum DecodedRecord<'r> {
+    /// A recorded signal
+    Signal(SignalRecord),
+    /// A recorded data description and the decoded data
+    Data {
+        description: DataDescriptionRecord<'r>,
+        value: Result<Box<dyn Decoded>, DecodeError>,
+    },
+    /// Task chains missing from the recording
+    DroppedCycles(DroppedCyclesRecord),
+}
+
+/// Output format of [`write_records`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Pretty-printed [`Debug`](core::fmt::Debug) representation
+    Debug,
+    /// One JSON object per line
+    Json,
+}
+
+impl<R: Read> RecordingReader<R> {
+    /// Read the next record and decode its data through the given registry,
+    /// or return None at the end of records
+    pub fn next_decoded(
+        &mut self,
+        registry: &TypeRegistry,
+    ) -> Result<Option<DecodedRecord<'_>>, ReadError> {
+        let record = match self.next_record()? {
+            None => return Ok(None),
+            Some(RecordEntry::Signal(signal)) => DecodedRecord::Signal(signal),
+            Some(RecordEntry::Data { description, data }) => DecodedRecord::Data {
+                description,
+                value: registry.decode(description.type_name, data),
+            },
+            Some(RecordEntry::DroppedCycles(dropped)) => DecodedRecord::DroppedCycles(dropped),
+        };
+        Ok(Some(record))
+    }
+
+    /// Read all remaining records, passing each one decoded to the given callback
+    pub fn for_each_decoded(
+        &mut self,
+        registry: &TypeRegistry,
+        mut callback: impl FnMut(DecodedRecord<'_>),
+    ) -> Result<(), ReadError> {
+        while let Some(record) = self.next_decoded(registry)? {
+            callback(record);
+        }
+        Ok(())
+    }
+}
+
+/// Write all remaining records of the reader in the given format
+///
+/// Data which cannot be decoded, e.g. because their type is not in the registry,
+/// are written without value.
+pub fn write_records<R: Read>(
+    reader: &mut RecordingReader<R>,
+    registry: &TypeRegistry,
+    format: OutputFormat,
+    mut out: impl Write,
+) -> Result<(), ReadError> {
+    let mut result = Ok(());
+    reader.for_each_decoded(registry, |record| {
+        if result.is_ok() {
+            result = match format {
+                OutputFormat::Debug => write_debug(&mut out, &record),
+                OutputFormat::Json => write_json(&mut out, &record),
+            };
+        }
+    })?;
+    result.map_err(ReadError::Io)
+}
+
+fn write_debug(out: &mut impl Write, record: &DecodedRecord) -> io::Result<()> {
+    match record {
+        DecodedRecord::Signal(signal) => writeln!(out, "{signal:#?}"),
+        DecodedRecord::Data { description, value } => {
+            writeln!(out, "{description:#?}")?;
+            match value {
+                Ok(value) => writeln!(out, "{value:#?}"),
+                Err(e) => writeln!(out, "<{e}>"),
+            }
+        }
+        DecodedRecord::DroppedCycles(dropped) => writeln!(out, "{dropped:#?}"),
+    }
+}
+
+fn write_json(out: &mut impl Write, record: &DecodedRecord) -> io::Result<()> {
+    let json = match record {
+        DecodedRecord::Signal(signal) => serde_json::to_value(signal)?,
+        DecodedRecord::Data { description, value } => {
+            let data = match value {
+                Ok(value) => value.to_json()?,
+                Err(_) => serde_json::Value::Null,
+            };
+            serde_json::json!({
+                "timestamp": description.timestamp,
+                "topic": description.topic,
+                "type_name": description.type_name,
+                "data_size": description.data_size,
+                "data": data,
+            })
+        }
+        DecodedRecord::DroppedCycles(dropped) => serde_json::json!({
+            "timestamp": dropped.timestamp,
+            "dropped_cycles": dropped.cycles,
+        }),
+    };
+    serde_json::to_writer(&mut *out, &json)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let mut buf = [0u8; 16];
+        let data = postcard::to_slice(&(42u32, true), &mut buf).unwrap();
+
+        let value = decode::<(u32, bool)>(data).unwrap();
+        assert_eq!(
+            value.as_any().downcast_ref::<(u32, bool)>(),
+            Some(&(42, true))
+        );
+        assert_eq!(value.to_json().unwrap(), serde_json::json!([42, true]));
+        assert!(decode::<(u32, bool)>(&data[..1]).is_err());
+    }
+}
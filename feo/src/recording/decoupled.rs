@@ -0,0 +1,244 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoupled recording
+//!
+//! In decoupled mode, the recorder buffers the records of every task chain in memory,
+//! queues them at the end of the task chain and signals ready to the primary right away.
+//! A background thread writes the queued task chains to the recording file,
+//! so that a slow disk does not extend the cycle.
+//!
+//! If the background thread falls behind by more than the configured number of task chains,
+//! further task chains are dropped. Dropped task chains are logged and reported by a
+//! [`Record::DroppedCycles`] in front of the next task chain written to the recording.
+
+use crate::recording::format::{encode_record, FileHeader, RecordingWriter};
+use crate::recording::recorder::{DroppedCyclesRecord, Record};
+use crate::recording::rotation::SplitFiles;
+use crate::timestamp::Timestamp;
+use alloc::vec::Vec;
+use feo_log::{error, warn};
+use std::fs;
+use std::io::{self, BufWriter};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+
+/// Configuration of decoupled recording
+#[derive(Debug, Clone, Copy)]
+pub struct DecoupledConfig {
+    /// Maximum number of task chains queued for writing before further task chains are dropped
+    pub queue_capacity: usize,
+}
+
+/// Records of a task chain queued for writing
+struct QueuedCycle {
+    /// Time at which the task chain started
+    timestamp: Timestamp,
+    /// Encoded records of the task chain
+    records: Vec<u8>,
+    /// Task chains dropped right before this one
+    dropped: Option<DroppedCyclesRecord>,
+}
+
+/// Writer passing recorded task chains through a bounded queue to a background thread
+pub(crate) struct DecoupledWriter {
+    /// Queue to the background thread
+    queue: Option<SyncSender<QueuedCycle>>,
+    /// The background thread, returning the first write error
+    thread: Option<JoinHandle<io::Result<()>>>,
+    /// Start time of the current task chain
+    timestamp: Option<Timestamp>,
+    /// Encoded records of the current task chain
+    records: Vec<u8>,
+    /// Task chains dropped since the last queued one
+    dropped: Option<DroppedCyclesRecord>,
+}
+
+impl DecoupledWriter {
+    /// Start a background thread writing to the given writer, rotating the split files if required
+    pub(crate) fn new(
+        writer: RecordingWriter<BufWriter<fs::File>>,
+        splits: SplitFiles,
+        header: FileHeader,
+        config: DecoupledConfig,
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(config.queue_capacity);
+        let thread = thread::spawn(move || write_queued(receiver, writer, splits, header));
+        Self {
+            queue: Some(sender),
+            thread: Some(thread),
+            timestamp: None,
+            records: Vec::new(),
+            dropped: None,
+        }
+    }
+
+    /// Start buffering a new task chain
+    pub(crate) fn start_cycle(&mut self, timestamp: Timestamp) {
+        self.timestamp = Some(timestamp);
+    }
+
+    /// Buffer the given record, followed by the given serialized data
+    ///
+    /// Records outside of task chains are written in front of the next task chain.
+    pub(crate) fn write_record(&mut self, record: &Record, data: &[u8]) -> io::Result<()> {
+        encode_record(&mut self.records, record, data)
+    }
+
+    /// Queue the current task chain for writing, or drop it if the queue is full
+    pub(crate) fn end_cycle(&mut self) -> io::Result<()> {
+        let (Some(timestamp), Some(queue)) = (self.timestamp.take(), self.queue.as_ref()) else {
+            return Ok(());
+        };
+        let cycle = QueuedCycle {
+            timestamp,
+            records: core::mem::take(&mut self.records),
+            dropped: self.dropped,
+        };
+        match queue.try_send(cycle) {
+            Ok(()) => {
+                self.dropped = None;
+                Ok(())
+            }
+            Err(TrySendError::Full(cycle)) => {
+                let dropped = self.dropped.get_or_insert(DroppedCyclesRecord {
+                    timestamp,
+                    cycles: 0,
+                });
+                dropped.cycles += 1;
+                error!(
+                    "Recording queue full, dropped task chain ({} in a row)",
+                    dropped.cycles
+                );
+                // Reuse the allocation for the next task chain
+                self.records = cycle.records;
+                self.records.clear();
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => self.join(),
+        }
+    }
+
+    /// Write the remaining task chains, finish the recording file and stop the background thread
+    ///
+    /// Subsequent calls have no effect.
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        let Some(queue) = self.queue.take() else {
+            return Ok(());
+        };
+        // Wait for space in the queue for an unfinished task chain
+        if let Some(timestamp) = self.timestamp.take() {
+            let cycle = QueuedCycle {
+                timestamp,
+                records: core::mem::take(&mut self.records),
+                dropped: self.dropped.take(),
+            };
+            // If the thread has stopped, its error is returned below
+            let _ = queue.send(cycle);
+        }
+        if let Some(dropped) = self.dropped {
+            warn!(
+                "{} dropped task chains at the end of the recording are not reported in the file",
+                dropped.cycles
+            );
+        }
+        drop(queue);
+        self.join()
+    }
+
+    /// Wait for the background thread to stop and return its result
+    fn join(&mut self) -> io::Result<()> {
+        self.queue = None;
+        match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("recording writer thread panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Write queued task chains until the queue is closed, then finish the recording file
+fn write_queued(
+    queue: Receiver<QueuedCycle>,
+    mut writer: RecordingWriter<BufWriter<fs::File>>,
+    mut splits: SplitFiles,
+    header: FileHeader,
+) -> io::Result<()> {
+    for cycle in queue {
+        splits.rotate_if_required(&mut writer, &header)?;
+        if let Some(dropped) = cycle.dropped {
+            writer.write_record(&Record::DroppedCycles(dropped), &[])?;
+        }
+        splits.cycle_started();
+        writer.write_cycle(cycle.timestamp, &cycle.records)?;
+        writer.flush()?;
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::recording::reader::{RecordEntry, RecordingReader};
+    use crate::recording::recorder::SignalRecord;
+    use crate::recording::rotation::Rotation;
+    use crate::recording::test_util::TestDir;
+    use crate::signalling::common::signals::Signal;
+    use crate::timestamp::SyncInfo;
+    use alloc::string::String;
+    use core::time::Duration;
+
+    #[test]
+    fn test_dropped_cycles_are_reported() {
+        let dir = TestDir::new("decoupled");
+        let path = dir.join("rec.bin");
+        let header = FileHeader {
+            app_name: String::from("test"),
+            sync_info: SyncInfo::from(0u64),
+            topics: Vec::new(),
+        };
+        let mut splits = SplitFiles::new(&path, Rotation::default());
+        let file = BufWriter::new(splits.create_next().unwrap());
+        let writer = RecordingWriter::new(file, &header).unwrap();
+
+        // Simulate a stalled background thread by replacing the queue with a full one
+        let config = DecoupledConfig { queue_capacity: 4 };
+        let mut decoupled = DecoupledWriter::new(writer, splits, header, config);
+        let (full, _receiver) = mpsc::sync_channel(0);
+        let queue = decoupled.queue.replace(full);
+
+        for cycle in 0..5u64 {
+            let timestamp = Timestamp(Duration::from_millis(cycle));
+            decoupled.start_cycle(timestamp);
+            let record = Record::Signal(SignalRecord {
+                timestamp,
+                signal: Signal::TaskChainStart(timestamp),
+            });
+            decoupled.write_record(&record, &[]).unwrap();
+            if cycle == 3 {
+                decoupled.queue = queue.clone();
+            }
+            decoupled.end_cycle().unwrap();
+        }
+        drop(queue);
+        decoupled.finish().unwrap();
+
+        // The first three cycles are dropped and reported in front of the fourth one
+        let mut reader = RecordingReader::open(&path).unwrap();
+        assert_eq!(reader.index().unwrap().len(), 2);
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.next_record().unwrap() {
+            match entry {
+                RecordEntry::DroppedCycles(dropped) => {
+                    entries.push((dropped.timestamp, dropped.cycles))
+                }
+                RecordEntry::Signal(signal) => entries.push((signal.timestamp, 0)),
+                RecordEntry::Data { .. } => panic!("unexpected data"),
+            }
+        }
+        let millis = |ms| Timestamp(Duration::from_millis(ms));
+        assert_eq!(entries, [(millis(0), 3), (millis(3), 0), (millis(4), 0)]);
+    }
+}
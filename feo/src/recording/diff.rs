@@ -0,0 +1,500 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Comparison of recordings
+//!
+//! Two recordings are compared cycle by cycle, e.g. a golden recording against a recording of
+//! the replay of changed activities. Within every cycle, the order of the recorded signals and
+//! the samples of every topic are compared, decoding the samples through the type registry.
+//! Timestamps are ignored, since they differ between any two runs.
+
+use crate::recording::reader::{ReadError, RecordEntry, RecordingReader};
+use crate::recording::recorder::SignalRecord;
+use crate::recording::registry::TypeRegistry;
+use crate::signalling::common::signals::Signal;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString as _};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use serde_json::Value;
+use std::io::Read;
+
+/// Options of the comparison
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffOptions {
+    /// Maximum absolute difference of numbers considered equal
+    pub tolerance: f64,
+}
+
+/// Result of the comparison of two recordings
+#[derive(Debug, Default)]
+pub struct DiffReport {
+    /// Number of cycles in the left recording
+    pub left_cycles: u64,
+    /// Number of cycles in the right recording
+    pub right_cycles: u64,
+    /// Number of cycles present in both recordings which differ
+    pub divergent_cycles: u64,
+    /// The first cycle which differs, if any
+    pub first_divergence: Option<CycleDiff>,
+}
+
+impl DiffReport {
+    /// Check whether the recordings are equal
+    pub fn is_equal(&self) -> bool {
+        self.left_cycles == self.right_cycles && self.first_divergence.is_none()
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.left_cycles != self.right_cycles {
+            writeln!(
+                f,
+                "number of cycles differs: {} != {}",
+                self.left_cycles, self.right_cycles
+            )?;
+        }
+        match &self.first_divergence {
+            None => writeln!(f, "all common cycles are equal"),
+            Some(cycle) => {
+                writeln!(
+                    f,
+                    "{} cycles differ, first divergent cycle {}:",
+                    self.divergent_cycles, cycle.cycle
+                )?;
+                for difference in &cycle.differences {
+                    writeln!(f, "  {difference}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Differences within a cycle
+#[derive(Debug)]
+pub struct CycleDiff {
+    /// Number of the cycle, counting from zero
+    pub cycle: u64,
+    /// Differences found in the cycle
+    pub differences: Vec<Difference>,
+}
+
+/// A difference between two recorded cycles
+#[derive(Debug, PartialEq)]
+pub enum Difference {
+    /// The order or kind of the recorded signals differs
+    Signals {
+        /// Position of the first differing signal
+        position: usize,
+        left: Option<String>,
+        right: Option<String>,
+    },
+    /// The number of samples recorded on a topic differs
+    SampleCount {
+        topic: String,
+        left: usize,
+        right: usize,
+    },
+    /// A field of a sample differs, given by its path within the sample
+    Field {
+        topic: String,
+        sample: usize,
+        path: String,
+        left: Value,
+        right: Value,
+    },
+    /// Samples which could not be decoded differ in their serialized data
+    Undecoded { topic: String, sample: usize },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_none = |signal: &Option<String>| signal.clone().unwrap_or_else(|| "-".into());
+        match self {
+            Difference::Signals {
+                position,
+                left,
+                right,
+            } => write!(
+                f,
+                "signal {position}: {} != {}",
+                or_none(left),
+                or_none(right)
+            ),
+            Difference::SampleCount { topic, left, right } => {
+                write!(f, "{topic}: number of samples {left} != {right}")
+            }
+            Difference::Field {
+                topic,
+                sample,
+                path,
+                left,
+                right,
+            } => write!(f, "{topic}[{sample}]{path}: {left} != {right}"),
+            Difference::Undecoded { topic, sample } => {
+                write!(f, "{topic}[{sample}]: undecodable data differs")
+            }
+        }
+    }
+}
+
+/// Compare the remaining cycles of the given recordings
+pub fn diff_recordings<L: Read, R: Read>(
+    left: &mut RecordingReader<L>,
+    right: &mut RecordingReader<R>,
+    registry: &TypeRegistry,
+    options: DiffOptions,
+) -> Result<DiffReport, ReadError> {
+    let mut left = CycleReader::new(left, registry);
+    let mut right = CycleReader::new(right, registry);
+    let mut report = DiffReport::default();
+
+    loop {
+        let (left_cycle, right_cycle) = match (left.next_cycle()?, right.next_cycle()?) {
+            (Some(l), Some(r)) => (l, r),
+            (l, r) => {
+                // Count the remaining cycles of the longer recording
+                report.left_cycles += u64::from(l.is_some());
+                report.right_cycles += u64::from(r.is_some());
+                while left.next_cycle()?.is_some() {
+                    report.left_cycles += 1;
+                }
+                while right.next_cycle()?.is_some() {
+                    report.right_cycles += 1;
+                }
+                return Ok(report);
+            }
+        };
+
+        let cycle = report.left_cycles;
+        report.left_cycles += 1;
+        report.right_cycles += 1;
+        let differences = diff_cycles(&left_cycle, &right_cycle, options);
+        if differences.is_empty() {
+            continue;
+        }
+        report.divergent_cycles += 1;
+        if report.first_divergence.is_none() {
+            report.first_divergence = Some(CycleDiff { cycle, differences });
+        }
+    }
+}
+
+/// Records of a cycle prepared for comparison
+#[derive(Debug, Default)]
+struct Cycle {
+    /// Recorded signals without timestamps
+    signals: Vec<String>,
+    /// Decoded samples per topic, or the serialized data if it could not be decoded
+    samples: BTreeMap<String, Vec<Result<Value, Vec<u8>>>>,
+}
+
+/// Reader of the records of a recording grouped into cycles
+struct CycleReader<'r, R> {
+    reader: &'r mut RecordingReader<R>,
+    registry: &'r TypeRegistry,
+    /// The cycle read so far, once the first cycle has started
+    current: Option<Cycle>,
+}
+
+impl<'r, R: Read> CycleReader<'r, R> {
+    fn new(reader: &'r mut RecordingReader<R>, registry: &'r TypeRegistry) -> Self {
+        Self {
+            reader,
+            registry,
+            current: None,
+        }
+    }
+
+    /// Read the records up to the start of the next cycle, or return None at the end of records
+    ///
+    /// Records before the first cycle are skipped.
+    fn next_cycle(&mut self) -> Result<Option<Cycle>, ReadError> {
+        loop {
+            let Some(entry) = self.reader.next_record()? else {
+                return Ok(self.current.take());
+            };
+            if let RecordEntry::Signal(SignalRecord {
+                signal: signal @ Signal::TaskChainStart(_),
+                ..
+            }) = entry
+            {
+                let next = Cycle {
+                    signals: vec![signal_name(&signal)],
+                    ..Default::default()
+                };
+                match self.current.replace(next) {
+                    Some(finished) => return Ok(Some(finished)),
+                    None => continue,
+                }
+            }
+
+            let Some(cycle) = self.current.as_mut() else {
+                continue;
+            };
+            match entry {
+                RecordEntry::Signal(SignalRecord { signal, .. }) => {
+                    cycle.signals.push(signal_name(&signal));
+                }
+                RecordEntry::Data { description, data } => {
+                    let sample = self
+                        .registry
+                        .decode(description.type_name, data)
+                        .ok()
+                        .and_then(|value| value.to_json().ok())
+                        .ok_or_else(|| data.to_vec());
+                    cycle
+                        .samples
+                        .entry(description.topic.to_string())
+                        .or_default()
+                        .push(sample);
+                }
+                RecordEntry::DroppedCycles(dropped) => {
+                    cycle
+                        .signals
+                        .push(format!("DroppedCycles({})", dropped.cycles));
+                }
+            }
+        }
+    }
+}
+
+/// Name and arguments of a signal without timestamps
+fn signal_name(signal: &Signal) -> String {
+    match signal {
+        Signal::StartupSync(_) => "StartupSync".into(),
+        Signal::Startup((id, _)) => format!("Startup({id})"),
+        Signal::Shutdown((id, _)) => format!("Shutdown({id})"),
+        Signal::Step((id, _, _)) => format!("Step({id})"),
+        Signal::Ready((id, _)) => format!("Ready({id})"),
+        Signal::TaskChainStart(_) => "TaskChainStart".into(),
+        Signal::TaskChainEnd(_) => "TaskChainEnd".into(),
+        Signal::RecorderReady((id, _)) => format!("RecorderReady({id})"),
+        Signal::RecordingTrigger((reason, _)) => format!("RecordingTrigger({reason})"),
+    }
+}
+
+/// Compare the records of two cycles
+fn diff_cycles(left: &Cycle, right: &Cycle, options: DiffOptions) -> Vec<Difference> {
+    let mut differences = vec![];
+
+    let position = left
+        .signals
+        .iter()
+        .zip(&right.signals)
+        .position(|(l, r)| l != r)
+        .or_else(|| {
+            (left.signals.len() != right.signals.len())
+                .then(|| left.signals.len().min(right.signals.len()))
+        });
+    if let Some(position) = position {
+        differences.push(Difference::Signals {
+            position,
+            left: left.signals.get(position).cloned(),
+            right: right.signals.get(position).cloned(),
+        });
+    }
+
+    let empty = vec![];
+    let topics: BTreeSet<&String> = left.samples.keys().chain(right.samples.keys()).collect();
+    for topic in topics {
+        let left_samples = left.samples.get(topic).unwrap_or(&empty);
+        let right_samples = right.samples.get(topic).unwrap_or(&empty);
+        if left_samples.len() != right_samples.len() {
+            differences.push(Difference::SampleCount {
+                topic: topic.clone(),
+                left: left_samples.len(),
+                right: right_samples.len(),
+            });
+        }
+        for (sample, (l, r)) in left_samples.iter().zip(right_samples).enumerate() {
+            match (l, r) {
+                (Ok(l), Ok(r)) => {
+                    let mut fields = vec![];
+                    diff_values(String::new(), l, r, options.tolerance, &mut fields);
+                    differences.extend(fields.into_iter().map(|(path, left, right)| {
+                        Difference::Field {
+                            topic: topic.clone(),
+                            sample,
+                            path,
+                            left,
+                            right,
+                        }
+                    }));
+                }
+                (l, r) if l != r => differences.push(Difference::Undecoded {
+                    topic: topic.clone(),
+                    sample,
+                }),
+                _ => {}
+            }
+        }
+    }
+    differences
+}
+
+/// Collect the paths and values of the differing fields of two JSON values
+fn diff_values(
+    path: String,
+    left: &Value,
+    right: &Value,
+    tolerance: f64,
+    differences: &mut Vec<(String, Value, Value)>,
+) {
+    match (left, right) {
+        // Fields missing on one side are compared as null, giving one difference per field
+        (Value::Object(l), Value::Object(r)) => {
+            for (key, l_value) in l {
+                let r_value = r.get(key).unwrap_or(&Value::Null);
+                diff_values(
+                    format!("{path}.{key}"),
+                    l_value,
+                    r_value,
+                    tolerance,
+                    differences,
+                );
+            }
+            for (key, r_value) in r.iter().filter(|(key, _)| !l.contains_key(*key)) {
+                diff_values(
+                    format!("{path}.{key}"),
+                    &Value::Null,
+                    r_value,
+                    tolerance,
+                    differences,
+                );
+            }
+        }
+        (Value::Array(l), Value::Array(r)) if l.len() == r.len() => {
+            for (i, (l_value, r_value)) in l.iter().zip(r).enumerate() {
+                diff_values(
+                    format!("{path}[{i}]"),
+                    l_value,
+                    r_value,
+                    tolerance,
+                    differences,
+                );
+            }
+        }
+        (Value::Number(l), Value::Number(r)) => {
+            let equal = match (l.as_f64(), r.as_f64()) {
+                (Some(l), Some(r)) => (l - r).abs() <= tolerance,
+                _ => l == r,
+            };
+            if !equal {
+                differences.push((path, left.clone(), right.clone()));
+            }
+        }
+        _ => {
+            if left != right {
+                differences.push((path, left.clone(), right.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ids::ActivityId;
+    use crate::recording::format::{FileHeader, RecordingWriter};
+    use crate::recording::recorder::{DataDescriptionRecord, Record};
+    use crate::timestamp::{SyncInfo, Timestamp};
+    use core::time::Duration;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    /// Write a recording with the given activity stepped and data recorded in every cycle
+    fn write_recording(cycles: &[(u64, u8)]) -> RecordingReader<Cursor<Vec<u8>>> {
+        let header = FileHeader {
+            app_name: "test".into(),
+            sync_info: SyncInfo::from(0u64),
+            topics: vec![],
+        };
+        let mut bytes = Vec::new();
+        let mut writer = RecordingWriter::new(&mut bytes, &header).unwrap();
+        for (cycle, (activity, data)) in cycles.iter().enumerate() {
+            let timestamp = Timestamp(Duration::from_millis(cycle as u64));
+            let signals = [
+                Signal::TaskChainStart(timestamp),
+                Signal::Step((ActivityId::from(*activity), cycle as u64, timestamp)),
+            ];
+            writer.start_cycle(timestamp);
+            for signal in signals {
+                let record = Record::Signal(SignalRecord { timestamp, signal });
+                writer.write_record(&record, &[]).unwrap();
+            }
+            let description = Record::DataDescription(DataDescriptionRecord {
+                timestamp,
+                data_size: 1,
+                type_name: "unregistered",
+                topic: "topic",
+            });
+            writer.write_record(&description, &[*data]).unwrap();
+        }
+        writer.finish().unwrap();
+        RecordingReader::new(Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn test_diff_recordings() {
+        let registry = TypeRegistry::new();
+        let mut left = write_recording(&[(1, 0), (1, 1), (1, 2), (1, 3)]);
+        let mut right = write_recording(&[(1, 0), (2, 5), (1, 2)]);
+        let report =
+            diff_recordings(&mut left, &mut right, &registry, DiffOptions::default()).unwrap();
+
+        assert!(!report.is_equal());
+        assert_eq!((report.left_cycles, report.right_cycles), (4, 3));
+        assert_eq!(report.divergent_cycles, 1);
+        let first = report.first_divergence.unwrap();
+        assert_eq!(first.cycle, 1);
+        assert_eq!(
+            first.differences,
+            [
+                Difference::Signals {
+                    position: 1,
+                    left: Some("Step(A1)".into()),
+                    right: Some("Step(A2)".into()),
+                },
+                Difference::Undecoded {
+                    topic: "topic".into(),
+                    sample: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let left = json!({"a": 1.0, "b": [1, 2], "c": {"d": "x"}});
+        let right = json!({"a": 1.05, "b": [1, 3], "c": {"d": "y"}});
+        let mut differences = vec![];
+        diff_values(String::new(), &left, &right, 0.1, &mut differences);
+        assert_eq!(
+            differences,
+            [
+                (".b[1]".into(), json!(2), json!(3)),
+                (".c.d".into(), json!("x"), json!("y")),
+            ]
+        );
+
+        let left = json!({"a": 1, "c": {"d": "x", "e": 2}});
+        let right = json!({"b": 1, "c": {"d": "x", "f": 3}});
+        let mut differences = vec![];
+        diff_values(String::new(), &left, &right, 0.1, &mut differences);
+        assert_eq!(
+            differences,
+            [
+                (".a".into(), json!(1), Value::Null),
+                (".c.e".into(), json!(2), Value::Null),
+                (".c.f".into(), Value::Null, json!(3)),
+                (".b".into(), Value::Null, json!(1)),
+            ]
+        );
+    }
+}
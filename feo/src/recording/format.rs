@@ -0,0 +1,352 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording file format
+//!
+//! A recording file consists of
+//! - a preamble made of [`MAGIC`] and the format version as little-endian `u32`,
+//! - the [`FileHeader`] describing the application and the recorded topics,
+//! - a sequence of [`Record`]s, where each data description record is directly followed
+//!   by the serialized data it describes,
+//! - an end-of-records marker, followed by the index of cycle offsets and a trailer
+//!   made of the index offset as little-endian `u64` and [`INDEX_MAGIC`].
+//!
+//! The header, every record and the index are postcard-serialized and prefixed with their
+//! length as little-endian `u32`.
+//!
+//! The serialized data of a topic may be compressed as given by its [`TopicEntry`].
+//! Every sample is compressed on its own, so that the index stays valid for seeking.
+//!
+//! A recorder which is killed never writes the end-of-records marker, the index and the trailer.
+//! Readers therefore treat the end of file as the end of records
+//! and rebuild the index by scanning the records if no trailer is present.
+
+use crate::recording::recorder::Record;
+use crate::timestamp::{SyncInfo, Timestamp};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use postcard::experimental::max_size::MaxSize;
+use postcard::ser_flavors::Flavor;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// Magic bytes at the start of every recording file
+pub const MAGIC: [u8; 8] = *b"FEOREC\0\0";
+
+/// Magic bytes at the end of a recording file with an index
+pub const INDEX_MAGIC: [u8; 8] = *b"FEOIDX\0\0";
+
+/// Version of the recording file format written by this implementation
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Length prefix marking the end of the records
+pub(crate) const END_OF_RECORDS: u32 = u32::MAX;
+
+/// Size of the trailer at the end of a finished recording file
+pub(crate) const TRAILER_SIZE: usize = size_of::<u64>() + INDEX_MAGIC.len();
+
+/// Header of a recording file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHeader {
+    /// Name of the recorded application
+    pub app_name: String,
+    /// Synchronization info of the recorded application at startup
+    pub sync_info: SyncInfo,
+    /// Recorded topics and their types
+    pub topics: Vec<TopicEntry>,
+}
+
+impl FileHeader {
+    /// Retrieve the entry of the given topic, or None if the topic is not recorded
+    pub fn topic(&self, topic: &str) -> Option<&TopicEntry> {
+        self.topics.iter().find(|entry| entry.topic == topic)
+    }
+}
+
+/// Description of a recorded topic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicEntry {
+    /// Com layer topic
+    pub topic: String,
+    /// Type name as registered in the type registry
+    pub type_name: String,
+    /// Schema of the recorded type
+    pub schema: TypeSchema,
+    /// Compression of the serialized data
+    pub compression: Compression,
+}
+
+/// Compression of the serialized data of a topic
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Compression {
+    /// Data is stored uncompressed
+    #[default]
+    None,
+    /// Raw deflate stream with the given compression level from 0 (fastest) to 10 (smallest)
+    Deflate(u8),
+}
+
+impl Compression {
+    /// Compress the given serialized data
+    pub(crate) fn compress<'d>(&self, data: &'d [u8]) -> Cow<'d, [u8]> {
+        match self {
+            Compression::None => Cow::Borrowed(data),
+            Compression::Deflate(level) => {
+                Cow::Owned(miniz_oxide::deflate::compress_to_vec(data, *level))
+            }
+        }
+    }
+
+    /// Decompress the given data, which must not exceed the given size when decompressed
+    ///
+    /// Returns None if the data is corrupted or exceeds the given size.
+    pub(crate) fn decompress<'d>(&self, data: &'d [u8], max_size: usize) -> Option<Cow<'d, [u8]>> {
+        match self {
+            Compression::None => Some(Cow::Borrowed(data)),
+            Compression::Deflate(_) => {
+                miniz_oxide::inflate::decompress_to_vec_with_limit(data, max_size)
+                    .ok()
+                    .map(Cow::Owned)
+            }
+        }
+    }
+}
+
+/// Schema of a recorded type
+///
+/// Postcard is not self-describing, so the schema captures the properties of a type
+/// which are available at registration time. The fingerprint allows detecting
+/// recordings made with a different definition of a type, but it cannot detect
+/// changes which leave the type name, layout and maximum serialized size unchanged.
+///
+/// Neither the type name nor the layout are guaranteed to be stable across compiler
+/// versions and targets, so the schema of an unchanged type may differ between recordings
+/// made with different builds. Recordings are thus only known to be compatible with
+/// the same build of the recorded types.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeSchema {
+    /// System type name as provided by [`core::any::type_name`]
+    pub system_type_name: String,
+    /// Size of the type in memory
+    pub size: u64,
+    /// Alignment of the type in memory
+    pub align: u64,
+    /// Maximum size of the serialized type
+    pub max_serialized_size: u64,
+    /// Fingerprint over all other schema properties
+    pub fingerprint: u64,
+}
+
+impl TypeSchema {
+    /// Create the schema of the given type
+    pub fn of<T: MaxSize>() -> Self {
+        Self::new(
+            core::any::type_name::<T>(),
+            size_of::<T>() as u64,
+            align_of::<T>() as u64,
+            T::POSTCARD_MAX_SIZE as u64,
+        )
+    }
+
+    fn new(system_type_name: &str, size: u64, align: u64, max_serialized_size: u64) -> Self {
+        // FNV-1a, which itself gives the same hash for the same properties on every platform
+        let mut fingerprint: u64 = 0xcbf2_9ce4_8422_2325;
+        let properties = [size, align, max_serialized_size];
+        let bytes = system_type_name
+            .bytes()
+            .chain(properties.iter().flat_map(|p| p.to_le_bytes()));
+        for byte in bytes {
+            fingerprint ^= u64::from(byte);
+            fingerprint = fingerprint.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+
+        Self {
+            system_type_name: system_type_name.into(),
+            size,
+            align,
+            max_serialized_size,
+            fingerprint,
+        }
+    }
+}
+
+/// Entry of the cycle index
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Number of the cycle, starting at zero
+    pub cycle: u64,
+    /// Offset of the task chain start record of the cycle from the start of the file
+    pub offset: u64,
+    /// Time at which the cycle started
+    pub timestamp: Timestamp,
+}
+
+/// Writer of recording files
+pub(crate) struct RecordingWriter<W: Write> {
+    writer: W,
+    /// Number of bytes written so far
+    position: u64,
+    /// Index of cycles written so far
+    index: Vec<IndexEntry>,
+    /// Whether the index and trailer have been written
+    finished: bool,
+    /// Buffer for encoding frames, reused across frames
+    frame: Vec<u8>,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// Create a writer and write the preamble and the given header
+    pub(crate) fn new(writer: W, header: &FileHeader) -> io::Result<Self> {
+        let mut this = Self {
+            writer,
+            position: 0,
+            index: Vec::new(),
+            finished: false,
+            frame: Vec::new(),
+        };
+        this.write(&MAGIC)?;
+        this.write(&FORMAT_VERSION.to_le_bytes())?;
+        this.write_frame(header)?;
+        Ok(this)
+    }
+
+    /// Write the given record, followed by the given serialized data
+    pub(crate) fn write_record(&mut self, record: &Record, data: &[u8]) -> io::Result<()> {
+        self.write_frame(record)?;
+        self.write(data)
+    }
+
+    /// Add the current position to the index as start of a new cycle
+    ///
+    /// Must be called before writing the task chain start record of the cycle.
+    pub(crate) fn start_cycle(&mut self, timestamp: Timestamp) {
+        let entry = IndexEntry {
+            cycle: self.index.len() as u64,
+            offset: self.position,
+            timestamp,
+        };
+        self.index.push(entry);
+    }
+
+    /// Write a cycle of records previously encoded with [`encode_record`]
+    pub(crate) fn write_cycle(&mut self, timestamp: Timestamp, records: &[u8]) -> io::Result<()> {
+        self.start_cycle(timestamp);
+        self.write(records)
+    }
+
+    /// Number of bytes written so far
+    pub(crate) fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Flush the underlying writer
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Write the end-of-records marker, the index and the trailer, then flush
+    ///
+    /// Subsequent calls have no effect.
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+
+        self.write(&END_OF_RECORDS.to_le_bytes())?;
+        let index_offset = self.position;
+        let index = core::mem::take(&mut self.index);
+        self.write_frame(&index)?;
+        self.write(&index_offset.to_le_bytes())?;
+        self.write(&INDEX_MAGIC)?;
+        self.flush()
+    }
+
+    /// Write the given value with a length prefix
+    fn write_frame<T: Serialize>(&mut self, value: &T) -> io::Result<()> {
+        let mut frame = core::mem::take(&mut self.frame);
+        frame.clear();
+        let result = encode_frame(&mut frame, value).and_then(|_| self.write(&frame));
+        self.frame = frame;
+        result
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// Append the given record, followed by the given serialized data, to the buffer
+///
+/// The records are framed as written by [`RecordingWriter::write_record`].
+pub(crate) fn encode_record(buf: &mut Vec<u8>, record: &Record, data: &[u8]) -> io::Result<()> {
+    encode_frame(buf, record)?;
+    buf.extend_from_slice(data);
+    Ok(())
+}
+
+/// Append the given value with a length prefix to the buffer
+///
+/// The value is serialized directly into the buffer, so that a reused buffer avoids allocations.
+fn encode_frame<T: Serialize>(buf: &mut Vec<u8>, value: &T) -> io::Result<()> {
+    let start = buf.len();
+    buf.extend_from_slice(&0u32.to_le_bytes());
+    if let Err(e) = postcard::serialize_with_flavor(value, AppendFlavor(buf)) {
+        buf.truncate(start);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+    }
+    let Some(len) = u32::try_from(buf.len() - start - size_of::<u32>())
+        .ok()
+        .filter(|len| *len != END_OF_RECORDS)
+    else {
+        buf.truncate(start);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    };
+    buf[start..start + size_of::<u32>()].copy_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+/// Postcard flavor appending to a borrowed vector
+struct AppendFlavor<'a>(&'a mut Vec<u8>);
+
+impl Flavor for AppendFlavor<'_> {
+    type Output = ();
+
+    fn try_extend(&mut self, data: &[u8]) -> postcard::Result<()> {
+        self.0.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn try_push(&mut self, data: u8) -> postcard::Result<()> {
+        self.0.push(data);
+        Ok(())
+    }
+
+    fn finalize(self) -> postcard::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let schema = TypeSchema::of::<u64>();
+        assert_eq!(schema, TypeSchema::of::<u64>());
+        assert_eq!(schema.size, 8);
+        assert_ne!(schema.fingerprint, TypeSchema::of::<u32>().fingerprint);
+        assert_ne!(
+            schema.fingerprint,
+            TypeSchema::new("u64", 8, 8, 9).fingerprint
+        );
+    }
+}
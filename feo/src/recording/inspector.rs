@@ -0,0 +1,242 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inspection of the current sample of com layer topics
+
+use crate::recording::registry::TypeRegistry;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+use core::ops::Deref as _;
+use core::time::Duration;
+use feo_com::interface::{init_topic_observer, ActivityInput, ComBackend, TopicHandle};
+use feo_com::introspection::{list_topics, TopicInfo};
+use postcard::experimental::max_size::MaxSize;
+use serde::Serialize;
+use std::thread;
+use std::time::{Instant, SystemTime};
+
+/// Interval between read attempts when waiting for a sample
+const READ_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Current sample of a topic
+#[derive(Debug, Clone)]
+pub struct TopicSample {
+    /// Debug representation of the sample
+    pub debug: String,
+    /// Serialized (postcard) representation of the sample
+    pub bytes: Vec<u8>,
+}
+
+/// Output format of [`dump_topic`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// Pretty-printed [`Debug`](core::fmt::Debug) representation
+    Debug,
+    /// Hex dump of the serialized representation
+    Hex,
+}
+
+/// Error type of topic inspection
+#[derive(Debug)]
+pub enum InspectionError {
+    /// No topic with the given name exists
+    UnknownTopic(String),
+    /// The topic's type is not registered in the type registry
+    UnknownType(String),
+    /// No sample could be read within the given timeout
+    NoSample(String),
+}
+
+impl core::fmt::Display for InspectionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InspectionError::UnknownTopic(topic) => write!(f, "unknown topic {topic}"),
+            InspectionError::UnknownType(type_name) => {
+                write!(f, "type {type_name} not found in type registry")
+            }
+            InspectionError::NoSample(topic) => write!(f, "no sample available on topic {topic}"),
+        }
+    }
+}
+
+impl core::error::Error for InspectionError {}
+
+/// Trait for reading the current sample of a topic in a type-erased way
+pub trait TopicInspector {
+    /// Read the current sample, if any
+    fn inspect(&self) -> Option<TopicSample>;
+}
+
+/// Typed [`TopicInspector`] reading from a com layer input
+pub(crate) struct Inspector<T: Serialize + MaxSize + core::fmt::Debug + 'static> {
+    input: Box<dyn ActivityInput<T>>,
+    // Keep the topic mapping alive while inspecting
+    _handle: TopicHandle,
+}
+
+impl<T: Serialize + MaxSize + core::fmt::Debug + 'static> Inspector<T> {
+    /// Observe the given topic and create an inspector reading from it
+    pub fn build(
+        input_builder: impl Fn(&str) -> Box<dyn ActivityInput<T>>,
+        topic: &str,
+        backend: ComBackend,
+    ) -> Box<dyn TopicInspector> {
+        let _handle = init_topic_observer::<T>(topic, backend);
+        let input = input_builder(topic);
+        Box::new(Inspector::<T> { input, _handle })
+    }
+}
+
+impl<T: Serialize + MaxSize + core::fmt::Debug + 'static> TopicInspector for Inspector<T> {
+    fn inspect(&self) -> Option<TopicSample> {
+        let value = self.input.read().ok()?;
+        let value = value.deref();
+        let mut bytes = vec![0; T::POSTCARD_MAX_SIZE];
+        let len = postcard::to_slice(value, &mut bytes)
+            .expect("serialization failed")
+            .len();
+        bytes.truncate(len);
+        Some(TopicSample {
+            debug: format!("{value:#?}"),
+            bytes,
+        })
+    }
+}
+
+/// Builder trait for a [`TopicInspector`] object
+///
+/// A builder is a function taking a com layer topic and backend and creating
+/// a [`TopicInspector`] object for that topic
+pub trait TopicInspectorBuilder: Fn(&str, ComBackend) -> Box<dyn TopicInspector> {}
+
+/// Implement the builder trait for any function matching the [`TopicInspectorBuilder`] builder trait.
+impl<T: Fn(&str, ComBackend) -> Box<dyn TopicInspector>> TopicInspectorBuilder for T {}
+
+/// Read the current sample of the given topic, using the type from the given registry
+///
+/// Depending on the backend, a sample may only become available with the next publication,
+/// so reading is retried until the given timeout expires.
+pub fn read_topic(
+    backend: ComBackend,
+    registry: &TypeRegistry,
+    topic: &str,
+    timeout: Duration,
+) -> Result<TopicSample, InspectionError> {
+    let info = list_topics(backend)
+        .into_iter()
+        .find(|info| info.topic == topic)
+        .ok_or_else(|| InspectionError::UnknownTopic(topic.into()))?;
+    let type_name = info.type_name.unwrap_or_default();
+    let type_info = registry
+        .info_system_name(&type_name)
+        .ok_or(InspectionError::UnknownType(type_name))?;
+    let inspector = (type_info.inspector_builder)(topic, backend);
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(sample) = inspector.inspect() {
+            return Ok(sample);
+        }
+        if Instant::now() >= deadline {
+            return Err(InspectionError::NoSample(topic.into()));
+        }
+        thread::sleep(READ_RETRY_INTERVAL);
+    }
+}
+
+/// Read the current sample of the given topic and format it in the given format
+pub fn dump_topic(
+    backend: ComBackend,
+    registry: &TypeRegistry,
+    topic: &str,
+    format: DumpFormat,
+    timeout: Duration,
+) -> Result<String, InspectionError> {
+    let sample = read_topic(backend, registry, topic, timeout)?;
+    Ok(match format {
+        DumpFormat::Debug => sample.debug,
+        DumpFormat::Hex => hex_dump(&sample.bytes),
+    })
+}
+
+/// Format the given topic information as a table
+pub fn format_topics(topics: &[TopicInfo]) -> String {
+    let unknown = || String::from("-");
+    let width = topics
+        .iter()
+        .map(|info| info.topic.len())
+        .max()
+        .unwrap_or_default()
+        .max("TOPIC".len());
+    let mut out = format!(
+        "{:<width$} {:>8} {:>7} {:>7} {:>14} {:>9} {:>9} {:>12}  TYPE\n",
+        "TOPIC", "SIZE", "WRITERS", "READERS", "LAST PUBLISH", "PUBLISHES", "READS", "MAX AGE"
+    );
+    for info in topics {
+        let last_publish = info
+            .last_publish
+            .map(|time| match SystemTime::now().duration_since(time) {
+                Ok(age) => format!("{:.3}s ago", age.as_secs_f64()),
+                Err(_) => String::from("now"),
+            })
+            .unwrap_or_else(unknown);
+        let stats = info.stats.unwrap_or_default();
+        let count = |n: u64| {
+            if info.stats.is_some() {
+                format!("{n}")
+            } else {
+                unknown()
+            }
+        };
+        let _ = writeln!(
+            out,
+            "{:<width$} {:>8} {:>7} {:>7} {:>14} {:>9} {:>9} {:>12}  {}",
+            info.topic,
+            info.size,
+            info.writers.map(|n| format!("{n}")).unwrap_or_else(unknown),
+            info.readers.map(|n| format!("{n}")).unwrap_or_else(unknown),
+            last_publish,
+            count(stats.publishes),
+            count(stats.reads),
+            stats
+                .max_age
+                .map(|age| format!("{:.3}ms", age.as_secs_f64() * 1000.0))
+                .unwrap_or_else(unknown),
+            info.type_name.clone().unwrap_or_else(unknown),
+        );
+    }
+    out
+}
+
+/// Format the given bytes as hex dump with 16 bytes per line
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        let _ = write!(out, "{:08x}:", line * 16);
+        for byte in chunk {
+            let _ = write!(out, " {byte:02x}");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump() {
+        let bytes: Vec<u8> = (0..18).collect();
+        assert_eq!(
+            hex_dump(&bytes),
+            "00000000: 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n00000010: 10 11\n"
+        );
+        assert_eq!(hex_dump(&[]), "");
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Export of recordings to MCAP files
+//!
+//! The export writes an unindexed MCAP file with JSON-encoded messages:
+//! one channel per recorded topic and a dedicated channel for the recorded signals.
+//! Log and publish times of messages are the recorded timestamps,
+//! converted to time since the UNIX epoch using the sync info of the recording.
+
+use crate::recording::decoder::DecodedRecord;
+use crate::recording::format::FORMAT_VERSION;
+use crate::recording::reader::{ReadError, RecordingReader};
+use crate::recording::registry::TypeRegistry;
+use crate::timestamp::{SyncInfo, Timestamp};
+use alloc::format;
+use alloc::string::ToString as _;
+use alloc::vec::Vec;
+use feo_log::warn;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// Magic bytes at the start and the end of an MCAP file
+const MAGIC: &[u8] = b"\x89MCAP0\r\n";
+
+/// Topic of the channel carrying the recorded signals
+pub const SIGNAL_TOPIC: &str = "feo/signals";
+
+/// Message encoding of all channels
+const MESSAGE_ENCODING: &str = "json";
+
+/// Record opcodes as defined by the MCAP specification
+mod opcode {
+    pub(super) const HEADER: u8 = 0x01;
+    pub(super) const FOOTER: u8 = 0x02;
+    pub(super) const CHANNEL: u8 = 0x04;
+    pub(super) const MESSAGE: u8 = 0x05;
+    pub(super) const METADATA: u8 = 0x0C;
+    pub(super) const DATA_END: u8 = 0x0F;
+}
+
+/// Statistics of an MCAP export
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExportStats {
+    /// Number of exported topic data messages
+    pub data: usize,
+    /// Number of exported signal messages
+    pub signals: usize,
+    /// Number of skipped topic data records which could not be decoded
+    pub skipped: usize,
+}
+
+/// Export all remaining records of the reader to an MCAP file written to `out`
+///
+/// Topic data are decoded through the given registry and written as JSON.
+/// Data which cannot be decoded are skipped and counted in the returned statistics.
+pub fn export_mcap<R: Read>(
+    reader: &mut RecordingReader<R>,
+    registry: &TypeRegistry,
+    out: impl Write,
+) -> Result<ExportStats, ReadError> {
+    let header = reader.header().clone();
+    let mut writer = McapWriter::new(out, header.sync_info)?;
+
+    writer.metadata(
+        "feo",
+        &[
+            ("app_name", header.app_name.as_str()),
+            ("format_version", &FORMAT_VERSION.to_string()),
+        ],
+    )?;
+
+    // Channel ids are assigned in the order of the header, with signals first
+    let signal_channel = writer.channel(SIGNAL_TOPIC, &[])?;
+    let mut channels = HashMap::new();
+    for entry in header.topics.iter() {
+        let fingerprint = format!("{:016x}", entry.schema.fingerprint);
+        let id = writer.channel(
+            &entry.topic,
+            &[
+                ("type_name", entry.type_name.as_str()),
+                ("fingerprint", &fingerprint),
+            ],
+        )?;
+        channels.insert(entry.topic.clone(), id);
+    }
+
+    let mut stats = ExportStats::default();
+    let mut result = Ok(());
+    reader.for_each_decoded(registry, |record| {
+        if result.is_err() {
+            return;
+        }
+        result = match record {
+            DecodedRecord::Signal(signal) => {
+                stats.signals += 1;
+                serde_json::to_vec(&signal.signal)
+                    .map_err(io::Error::from)
+                    .and_then(|json| writer.message(signal_channel, signal.timestamp, &json))
+            }
+            DecodedRecord::Data { description, value } => {
+                let json = value.ok().and_then(|value| value.to_json().ok());
+                match (channels.get(description.topic), json) {
+                    (Some(channel), Some(json)) => {
+                        stats.data += 1;
+                        serde_json::to_vec(&json)
+                            .map_err(io::Error::from)
+                            .and_then(|json| writer.message(*channel, description.timestamp, &json))
+                    }
+                    _ => {
+                        warn!(
+                            "Skipping data of type {} on topic {}",
+                            description.type_name, description.topic
+                        );
+                        stats.skipped += 1;
+                        Ok(())
+                    }
+                }
+            }
+            DecodedRecord::DroppedCycles(dropped) => {
+                warn!(
+                    "Recording lacks {} task chains from {:?}",
+                    dropped.cycles, dropped.timestamp
+                );
+                Ok(())
+            }
+        };
+    })?;
+    result.map_err(ReadError::Io)?;
+
+    writer.finish()?;
+    Ok(stats)
+}
+
+/// Minimal writer of unindexed MCAP files
+struct McapWriter<W: Write> {
+    writer: W,
+    /// Sync info used to convert recorded timestamps to time since the UNIX epoch
+    sync_info: SyncInfo,
+    /// Next message sequence number per channel
+    sequences: Vec<u32>,
+}
+
+impl<W: Write> McapWriter<W> {
+    /// Create a writer and write the magic bytes and the header record
+    fn new(mut writer: W, sync_info: SyncInfo) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        let mut this = Self {
+            writer,
+            sync_info,
+            sequences: Vec::new(),
+        };
+
+        let mut header = Vec::new();
+        put_str(&mut header, ""); // profile
+        put_str(&mut header, "feo"); // library
+        this.record(opcode::HEADER, &header)?;
+        Ok(this)
+    }
+
+    /// Write a metadata record
+    fn metadata(&mut self, name: &str, entries: &[(&str, &str)]) -> io::Result<()> {
+        let mut record = Vec::new();
+        put_str(&mut record, name);
+        put_map(&mut record, entries);
+        self.record(opcode::METADATA, &record)
+    }
+
+    /// Write a schemaless channel record and return the channel id
+    fn channel(&mut self, topic: &str, metadata: &[(&str, &str)]) -> io::Result<u16> {
+        let id = u16::try_from(self.sequences.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many channels"))?;
+        self.sequences.push(0);
+
+        let mut record = Vec::new();
+        record.extend_from_slice(&id.to_le_bytes());
+        record.extend_from_slice(&0u16.to_le_bytes()); // no schema
+        put_str(&mut record, topic);
+        put_str(&mut record, MESSAGE_ENCODING);
+        put_map(&mut record, metadata);
+        self.record(opcode::CHANNEL, &record)?;
+        Ok(id)
+    }
+
+    /// Write a message record on the given channel
+    fn message(&mut self, channel: u16, timestamp: Timestamp, data: &[u8]) -> io::Result<()> {
+        let sequence = &mut self.sequences[channel as usize];
+        let time = (u128::from(self.sync_info) + timestamp.0.as_nanos()) as u64;
+
+        let mut record = Vec::with_capacity(22 + data.len());
+        record.extend_from_slice(&channel.to_le_bytes());
+        record.extend_from_slice(&sequence.to_le_bytes());
+        record.extend_from_slice(&time.to_le_bytes()); // log time
+        record.extend_from_slice(&time.to_le_bytes()); // publish time
+        record.extend_from_slice(data);
+        *sequence = sequence.wrapping_add(1);
+        self.record(opcode::MESSAGE, &record)
+    }
+
+    /// Write the data end and footer records without summary, then the closing magic bytes
+    fn finish(mut self) -> io::Result<()> {
+        self.record(opcode::DATA_END, &0u32.to_le_bytes())?;
+        let mut footer = Vec::new();
+        footer.extend_from_slice(&0u64.to_le_bytes()); // summary start
+        footer.extend_from_slice(&0u64.to_le_bytes()); // summary offset start
+        footer.extend_from_slice(&0u32.to_le_bytes()); // summary crc
+        self.record(opcode::FOOTER, &footer)?;
+        self.writer.write_all(MAGIC)?;
+        self.writer.flush()
+    }
+
+    fn record(&mut self, opcode: u8, content: &[u8]) -> io::Result<()> {
+        self.writer.write_all(&[opcode])?;
+        self.writer
+            .write_all(&(content.len() as u64).to_le_bytes())?;
+        self.writer.write_all(content)
+    }
+}
+
+/// Append a length-prefixed string
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Append a map of strings, prefixed with its length in bytes
+fn put_map(buf: &mut Vec<u8>, entries: &[(&str, &str)]) {
+    let mut map = Vec::new();
+    for (key, value) in entries {
+        put_str(&mut map, key);
+        put_str(&mut map, value);
+    }
+    buf.extend_from_slice(&(map.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&map);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::time::Duration;
+
+    #[test]
+    fn test_mcap_records() {
+        let mut buf = Vec::new();
+        let mut writer = McapWriter::new(&mut buf, SyncInfo::from(1_000u64)).unwrap();
+        let channel = writer.channel("topic", &[("key", "value")]).unwrap();
+        writer
+            .message(channel, Timestamp(Duration::from_nanos(234)), b"{}")
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert!(buf.starts_with(MAGIC) && buf.ends_with(MAGIC));
+
+        // Walk the records and check opcodes and the message time
+        let mut records = &buf[MAGIC.len()..buf.len() - MAGIC.len()];
+        let mut opcodes = Vec::new();
+        while !records.is_empty() {
+            let opcode = records[0];
+            let len = u64::from_le_bytes(records[1..9].try_into().unwrap()) as usize;
+            let content = &records[9..9 + len];
+            if opcode == opcode::MESSAGE {
+                let time = u64::from_le_bytes(content[6..14].try_into().unwrap());
+                assert_eq!(time, 1_234);
+                assert_eq!(&content[22..], b"{}");
+            }
+            opcodes.push(opcode);
+            records = &records[9 + len..];
+        }
+        assert_eq!(
+            opcodes,
+            [
+                opcode::HEADER,
+                opcode::CHANNEL,
+                opcode::MESSAGE,
+                opcode::DATA_END,
+                opcode::FOOTER
+            ]
+        );
+    }
+}
@@ -2,11 +2,44 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "recording")]
+pub mod blackbox;
+
+#[cfg(feature = "recording")]
+pub mod decoder;
+
+#[cfg(feature = "recording")]
+pub mod decoupled;
+
+#[cfg(feature = "recording")]
+pub mod diff;
+
+#[cfg(feature = "recording")]
+pub mod format;
+
+#[cfg(feature = "recording")]
+pub mod inspector;
+
 #[cfg(feature = "recording")]
 pub mod recorder;
 
+#[cfg(feature = "recording")]
+pub mod mcap;
+
+#[cfg(feature = "recording")]
+pub mod reader;
+
 #[cfg(feature = "recording")]
 pub mod registry;
 
+#[cfg(feature = "recording")]
+pub(crate) mod replay;
+
+#[cfg(feature = "recording")]
+pub mod rotation;
+
+#[cfg(all(test, feature = "recording"))]
+mod test_util;
+
 #[cfg(feature = "recording")]
 mod transcoder;
@@ -0,0 +1,522 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reader of recording files
+
+use crate::recording::format::{
+    Compression, FileHeader, IndexEntry, END_OF_RECORDS, FORMAT_VERSION, INDEX_MAGIC, MAGIC,
+    TRAILER_SIZE,
+};
+use crate::recording::recorder::{
+    DataDescriptionRecord, DroppedCyclesRecord, Record, SignalRecord,
+};
+use crate::signalling::common::signals::Signal;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::fs;
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Error reading a recording file
+#[derive(Debug)]
+pub enum ReadError {
+    /// Failed to read from the underlying reader
+    Io(io::Error),
+    /// The file does not start with the recording magic bytes
+    NotARecording,
+    /// The file has been written with an unknown format version
+    UnsupportedVersion(u32),
+    /// The file ends in the middle of a record
+    Truncated,
+    /// A record could not be deserialized
+    Corrupted(postcard::Error),
+    /// A record describes data of the given size, which exceeds the addressable size
+    InvalidDataSize(usize),
+    /// Compressed data could not be decompressed
+    Decompression,
+}
+
+impl core::error::Error for ReadError {}
+
+impl core::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "failed to read recording: {e}"),
+            ReadError::NotARecording => write!(f, "not a recording file"),
+            ReadError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported recording format version {version} (supported: {FORMAT_VERSION})"
+            ),
+            ReadError::Truncated => write!(f, "recording is truncated"),
+            ReadError::Corrupted(e) => write!(f, "recording is corrupted: {e}"),
+            ReadError::InvalidDataSize(size) => {
+                write!(f, "recording is corrupted: invalid data size {size}")
+            }
+            ReadError::Decompression => write!(f, "recording contains corrupted compressed data"),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == ErrorKind::UnexpectedEof {
+            ReadError::Truncated
+        } else {
+            ReadError::Io(err)
+        }
+    }
+}
+
+impl From<postcard::Error> for ReadError {
+    fn from(err: postcard::Error) -> Self {
+        ReadError::Corrupted(err)
+    }
+}
+
+/// A record read from a recording file
+///
+/// Compressed data is returned decompressed, with the data size of the description adjusted.
+#[derive(Debug)]
+pub enum RecordEntry<'r> {
+    /// A recorded signal
+    Signal(SignalRecord),
+    /// A recorded data description and the serialized data
+    Data {
+        description: DataDescriptionRecord<'r>,
+        data: &'r [u8],
+    },
+    /// Task chains missing from the recording
+    DroppedCycles(DroppedCyclesRecord),
+}
+
+/// Reader of recording files
+///
+/// The reader validates the preamble and reads the header on creation,
+/// refusing files with an unknown format version.
+pub struct RecordingReader<R> {
+    reader: R,
+    header: FileHeader,
+    /// Current offset from the start of the file
+    position: u64,
+    /// Offset of the first record from the start of the file
+    records_start: u64,
+    /// Whether the end of records has been reached
+    end_of_records: bool,
+    /// Buffer holding the last read record and data
+    buffer: Vec<u8>,
+    /// Buffer holding the last decompressed data
+    decompressed: Vec<u8>,
+}
+
+impl RecordingReader<BufReader<fs::File>> {
+    /// Open the recording file at the given path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReadError> {
+        let file = fs::File::open(path).map_err(ReadError::Io)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> RecordingReader<R> {
+    /// Create a reader reading a recording from the given reader
+    pub fn new(mut reader: R) -> Result<Self, ReadError> {
+        let mut magic = [0u8; MAGIC.len()];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|_| ReadError::NotARecording)?;
+        if magic != MAGIC {
+            return Err(ReadError::NotARecording);
+        }
+        let version = read_u32(&mut reader)?;
+        if version != FORMAT_VERSION {
+            return Err(ReadError::UnsupportedVersion(version));
+        }
+
+        let mut buffer = vec![];
+        let len = read_u32(&mut reader)?;
+        read_bytes(&mut reader, &mut buffer, len as usize)?;
+        let header = postcard::from_bytes(&buffer)?;
+        let records_start = (MAGIC.len() + 2 * size_of::<u32>()) as u64 + u64::from(len);
+
+        Ok(Self {
+            reader,
+            header,
+            position: records_start,
+            records_start,
+            end_of_records: false,
+            buffer,
+            decompressed: vec![],
+        })
+    }
+
+    /// Header of the recording
+    pub fn header(&self) -> &FileHeader {
+        &self.header
+    }
+
+    /// Offset of the next record from the start of the file
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Read the next record, or None at the end of records
+    pub fn next_record(&mut self) -> Result<Option<RecordEntry<'_>>, ReadError> {
+        if self.end_of_records {
+            return Ok(None);
+        }
+
+        // End of file at a record boundary ends the records of an unfinished recording
+        let mut len = [0u8; size_of::<u32>()];
+        let read = read_up_to(&mut self.reader, &mut len)?;
+        if read == 0 || u32::from_le_bytes(len) == END_OF_RECORDS {
+            self.end_of_records = true;
+            return Ok(None);
+        }
+        if read < len.len() {
+            return Err(ReadError::Truncated);
+        }
+
+        let len = u32::from_le_bytes(len) as usize;
+        read_bytes(&mut self.reader, &mut self.buffer, len)?;
+        let data_size = match postcard::from_bytes(&self.buffer)? {
+            Record::Signal(_) | Record::DroppedCycles(_) => 0,
+            Record::DataDescription(description) => description.data_size,
+        };
+        // The data size is read from the file and may be corrupted
+        let record_size = (size_of::<u32>() + len)
+            .checked_add(data_size)
+            .ok_or(ReadError::InvalidDataSize(data_size))?;
+
+        // Append the data to the buffer and deserialize the record borrowing from it
+        append_bytes(&mut self.reader, &mut self.buffer, data_size)?;
+        self.position += record_size as u64;
+        let (record, mut data) = self.buffer.split_at(len);
+        let mut record: Record = postcard::from_bytes(record)?;
+
+        // Decompress the data of compressed topics
+        if let Record::DataDescription(description) = &mut record {
+            let entry = self.header.topic(description.topic);
+            if let Some(entry) = entry.filter(|e| e.compression != Compression::None) {
+                let max_size =
+                    usize::try_from(entry.schema.max_serialized_size).unwrap_or(usize::MAX);
+                self.decompressed = entry
+                    .compression
+                    .decompress(data, max_size)
+                    .ok_or(ReadError::Decompression)?
+                    .into_owned();
+                description.data_size = self.decompressed.len();
+                data = &self.decompressed;
+            }
+        }
+        Ok(Some(into_entry(record, data)))
+    }
+}
+
+impl<R: Read + Seek> RecordingReader<R> {
+    /// Read the index of cycles
+    ///
+    /// If the recording has not been finished, the index is rebuilt by scanning all records.
+    /// The position of the reader is not changed.
+    pub fn index(&mut self) -> Result<Vec<IndexEntry>, ReadError> {
+        let position = self.position;
+        let end_of_records = self.end_of_records;
+
+        let index = match self.read_index()? {
+            Some(index) => index,
+            None => self.scan_index()?,
+        };
+
+        self.reader.seek(SeekFrom::Start(position))?;
+        self.position = position;
+        self.end_of_records = end_of_records;
+        Ok(index)
+    }
+
+    /// Continue reading at the start of the given cycle
+    pub fn seek_cycle(&mut self, entry: &IndexEntry) -> Result<(), ReadError> {
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+        self.position = entry.offset;
+        self.end_of_records = false;
+        Ok(())
+    }
+
+    /// Read the index from the end of the file, or None if there is no trailer
+    fn read_index(&mut self) -> Result<Option<Vec<IndexEntry>>, ReadError> {
+        let file_size = self.reader.seek(SeekFrom::End(0))?;
+        if file_size < self.records_start + TRAILER_SIZE as u64 {
+            return Ok(None);
+        }
+
+        let mut trailer = [0u8; TRAILER_SIZE];
+        self.reader.seek(SeekFrom::End(-(TRAILER_SIZE as i64)))?;
+        self.reader.read_exact(&mut trailer)?;
+        let (offset, magic) = trailer.split_at(size_of::<u64>());
+        if magic != INDEX_MAGIC {
+            return Ok(None);
+        }
+
+        let offset = u64::from_le_bytes(offset.try_into().expect("slice has size of u64"));
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let len = read_u32(&mut self.reader)?;
+        read_bytes(&mut self.reader, &mut self.buffer, len as usize)?;
+        Ok(Some(postcard::from_bytes(&self.buffer)?))
+    }
+
+    /// Rebuild the index by scanning all records
+    fn scan_index(&mut self) -> Result<Vec<IndexEntry>, ReadError> {
+        self.reader.seek(SeekFrom::Start(self.records_start))?;
+        self.position = self.records_start;
+        self.end_of_records = false;
+
+        let mut index = vec![];
+        loop {
+            let offset = self.position;
+            match self.next_record() {
+                Ok(Some(RecordEntry::Signal(SignalRecord {
+                    signal: Signal::TaskChainStart(timestamp),
+                    ..
+                }))) => index.push(IndexEntry {
+                    cycle: index.len() as u64,
+                    offset,
+                    timestamp,
+                }),
+                Ok(Some(_)) => {}
+                // A killed recorder may leave a partially written record at the end
+                Ok(None) | Err(ReadError::Truncated) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(index)
+    }
+}
+
+fn into_entry<'r>(record: Record<'r>, data: &'r [u8]) -> RecordEntry<'r> {
+    match record {
+        Record::Signal(signal) => RecordEntry::Signal(signal),
+        Record::DataDescription(description) => RecordEntry::Data { description, data },
+        Record::DroppedCycles(dropped) => RecordEntry::DroppedCycles(dropped),
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, ReadError> {
+    let mut bytes = [0u8; size_of::<u32>()];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Replace the contents of the buffer with the given number of bytes from the reader
+fn read_bytes(reader: &mut impl Read, buffer: &mut Vec<u8>, len: usize) -> Result<(), ReadError> {
+    buffer.clear();
+    append_bytes(reader, buffer, len)
+}
+
+/// Append the given number of bytes from the reader to the buffer
+///
+/// The length is read from the file and may be corrupted, so the buffer is not resized to it
+/// up front, but grows with the bytes actually read. It thus never exceeds the remaining size
+/// of the file, and a length beyond the end of the file is reported as truncation.
+fn append_bytes(reader: &mut impl Read, buffer: &mut Vec<u8>, len: usize) -> Result<(), ReadError> {
+    let start = buffer.len();
+    reader.take(len as u64).read_to_end(buffer)?;
+    if buffer.len() - start < len {
+        return Err(ReadError::Truncated);
+    }
+    Ok(())
+}
+
+/// Fill the buffer as far as possible, returning the number of bytes read
+fn read_up_to(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, ReadError> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(ReadError::Io(e)),
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::recording::format::{RecordingWriter, TopicEntry, TypeSchema};
+    use crate::timestamp::{SyncInfo, Timestamp};
+    use core::time::Duration;
+    use std::io::Cursor;
+
+    fn header() -> FileHeader {
+        FileHeader {
+            app_name: "test".into(),
+            sync_info: SyncInfo::from(42u64),
+            topics: vec![TopicEntry {
+                topic: "topic".into(),
+                type_name: "u64".into(),
+                schema: TypeSchema::of::<u64>(),
+                compression: Compression::None,
+            }],
+        }
+    }
+
+    fn write_recording(finish: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut writer = RecordingWriter::new(&mut bytes, &header()).unwrap();
+        for cycle in 0..3 {
+            let timestamp = Timestamp(Duration::from_millis(cycle));
+            let signal = Record::Signal(SignalRecord {
+                timestamp,
+                signal: Signal::TaskChainStart(timestamp),
+            });
+            writer.start_cycle(timestamp);
+            writer.write_record(&signal, &[]).unwrap();
+            let description = Record::DataDescription(DataDescriptionRecord {
+                timestamp,
+                data_size: 1,
+                type_name: "u64",
+                topic: "topic",
+            });
+            writer.write_record(&description, &[cycle as u8]).unwrap();
+        }
+        if finish {
+            writer.finish().unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for finish in [true, false] {
+            let bytes = write_recording(finish);
+            let mut reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+            assert_eq!(reader.header(), &header());
+
+            let index = reader.index().unwrap();
+            assert_eq!(index.len(), 3);
+            assert_eq!(index[2].timestamp, Timestamp(Duration::from_millis(2)));
+
+            let mut records = 0;
+            while reader.next_record().unwrap().is_some() {
+                records += 1;
+            }
+            assert_eq!(records, 6);
+
+            reader.seek_cycle(&index[1]).unwrap();
+            assert!(matches!(
+                reader.next_record().unwrap(),
+                Some(RecordEntry::Signal(_))
+            ));
+            match reader.next_record().unwrap() {
+                Some(RecordEntry::Data { description, data }) => {
+                    assert_eq!(description.topic, "topic");
+                    assert_eq!(data, &[1]);
+                }
+                other => panic!("unexpected record {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_compressed_data() {
+        let mut header = header();
+        header.topics[0].compression = Compression::Deflate(6);
+        let value = 0x0102_0304_0506_0708u64;
+        let serialized = postcard::to_allocvec(&value).unwrap();
+        let compressed = header.topics[0].compression.compress(&serialized);
+
+        let mut bytes = Vec::new();
+        let mut writer = RecordingWriter::new(&mut bytes, &header).unwrap();
+        let description = Record::DataDescription(DataDescriptionRecord {
+            timestamp: Timestamp(Duration::ZERO),
+            data_size: compressed.len(),
+            type_name: "u64",
+            topic: "topic",
+        });
+        writer.write_record(&description, &compressed).unwrap();
+        let corrupted = Record::DataDescription(DataDescriptionRecord {
+            timestamp: Timestamp(Duration::ZERO),
+            data_size: 1,
+            type_name: "u64",
+            topic: "topic",
+        });
+        writer.write_record(&corrupted, &[0xff]).unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        match reader.next_record().unwrap() {
+            Some(RecordEntry::Data { description, data }) => {
+                assert_eq!(description.data_size, serialized.len());
+                assert_eq!(data, serialized.as_slice());
+            }
+            other => panic!("unexpected record {other:?}"),
+        }
+        assert!(matches!(
+            reader.next_record(),
+            Err(ReadError::Decompression)
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut bytes = write_recording(true);
+        bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&99u32.to_le_bytes());
+        assert!(matches!(
+            RecordingReader::new(Cursor::new(bytes)),
+            Err(ReadError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            RecordingReader::new(Cursor::new(b"garbage")),
+            Err(ReadError::NotARecording)
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_length() {
+        // A record length far beyond the end of the file
+        let mut bytes = write_recording(false);
+        let records_start = RecordingReader::new(Cursor::new(bytes.clone()))
+            .unwrap()
+            .position() as usize;
+        bytes[records_start..records_start + 4].copy_from_slice(&(u32::MAX - 1).to_le_bytes());
+        let mut reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        assert!(matches!(reader.next_record(), Err(ReadError::Truncated)));
+        assert!(reader.buffer.capacity() < 1024);
+    }
+
+    #[test]
+    fn test_corrupted_data_size() {
+        let mut bytes = Vec::new();
+        let mut writer = RecordingWriter::new(&mut bytes, &header()).unwrap();
+        let description = Record::DataDescription(DataDescriptionRecord {
+            timestamp: Timestamp(Duration::ZERO),
+            data_size: usize::MAX,
+            type_name: "u64",
+            topic: "topic",
+        });
+        writer.write_record(&description, &[]).unwrap();
+
+        let mut reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        let position = reader.position();
+        assert!(matches!(
+            reader.next_record(),
+            Err(ReadError::InvalidDataSize(usize::MAX))
+        ));
+        assert_eq!(reader.position(), position);
+    }
+
+    #[test]
+    fn test_truncated_data() {
+        let mut bytes = write_recording(false);
+        let records_end = bytes.len();
+        bytes.truncate(records_end - 1);
+
+        let mut reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        for _ in 0..5 {
+            reader.next_record().unwrap().unwrap();
+        }
+        // The position stays at the start of the record whose data is truncated
+        let position = reader.position();
+        assert!(matches!(reader.next_record(), Err(ReadError::Truncated)));
+        assert_eq!(reader.position(), position);
+    }
+}
@@ -5,21 +5,27 @@
 //! FEO data recorder. Records communication for debugging and development purposes
 
 use crate::ids::AgentId;
+use crate::recording::blackbox::{BlackBox, BlackBoxConfig, TriggerHandle};
+use crate::recording::decoupled::{DecoupledConfig, DecoupledWriter};
+use crate::recording::format::{Compression, FileHeader, RecordingWriter, TopicEntry};
 use crate::recording::registry::TypeRegistry;
+use crate::recording::rotation::{Rotation, SplitFiles};
 use crate::recording::transcoder::ComRecTranscoder;
 use crate::signalling::common::interface::ConnectRecorder;
-use crate::signalling::common::signals::Signal;
+use crate::signalling::common::signals::{Signal, TriggerReason};
 use crate::timestamp;
-use crate::timestamp::{timestamp, Timestamp};
+use crate::timestamp::{timestamp, SyncInfo, Timestamp};
+use crate::topicspec::TopicSpecification;
+#[cfg(feature = "runtime_tracing")]
+use crate::trace;
+use alloc::borrow::ToOwned as _;
 use alloc::boxed::Box;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::time::Duration;
-use feo_log::{debug, error, trace};
-use io::Write;
+use feo_log::{debug, error, info, trace, warn};
 use postcard::experimental::max_size::MaxSize;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::io::BufWriter;
 use std::{fs, io};
 
@@ -37,8 +43,26 @@ pub(crate) struct FileRecorder<'s> {
     /// Receive timeout used on poll
     receive_timeout: Duration,
 
-    // A file writer receiving the data
-    writer: BufWriter<fs::File>,
+    // Name of the recorded application
+    app_name: &'static str,
+
+    // The recording file, until the header has been written (not used in black-box mode)
+    file: Option<BufWriter<fs::File>>,
+
+    // The header written to the start of every split file
+    header: Option<FileHeader>,
+
+    // Split files of the recording, until handed to the background thread in decoupled mode
+    splits: Option<SplitFiles>,
+
+    // Decoupled mode configuration (not used in black-box mode)
+    decoupled: Option<DecoupledConfig>,
+
+    // The output receiving the data, once the header has been written or in black-box mode
+    output: Option<Output>,
+
+    // Handle to trigger the black box from outside of the task chain
+    trigger: TriggerHandle,
 
     // Which topics with what types to record
     rules: RecordingRules,
@@ -46,67 +70,113 @@ pub(crate) struct FileRecorder<'s> {
     // The type registry
     registry: &'s TypeRegistry,
 
-    // Transcoders reading and serializing com data
-    transcoders: Vec<Box<dyn ComRecTranscoder>>,
+    // Recorded topics with transcoders reading and serializing com data
+    topics: Vec<RecordedTopic>,
+
+    // Number of task chains started so far
+    cycles: u64,
 }
 
 impl<'s> FileRecorder<'s> {
     /// Create a new data recorder
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new<'t: 's>(
         id: AgentId,
         connector: Box<dyn ConnectRecorder>,
         receive_timeout: Duration,
+        app_name: &'static str,
         record_file: &'static str,
+        rotation: Rotation,
+        black_box: Option<BlackBoxConfig>,
+        decoupled: Option<DecoupledConfig>,
         rules: RecordingRules,
         registry: &'t TypeRegistry,
     ) -> io::Result<Self> {
-        // Create the recording file
-        let file = fs::File::create(record_file)?;
-        let writer = BufWriter::new(file);
+        let mut splits = SplitFiles::new(record_file, rotation);
+        let (file, output) = match black_box {
+            // Files are only created when the black box is triggered
+            Some(config) => {
+                let black_box = BlackBox::new(record_file, config);
+                (None, Some(Output::BlackBox(black_box)))
+            }
+            // Create the (first split) recording file. The header is written once the sync info is received.
+            None => (Some(BufWriter::new(splits.create_next()?)), None),
+        };
 
         Ok(Self {
             id,
             connector,
             receive_timeout,
-            writer,
+            app_name,
+            file,
+            header: None,
+            splits: Some(splits),
+            decoupled,
+            output,
+            trigger: TriggerHandle::default(),
             rules,
             registry,
-            transcoders: vec![],
+            topics: vec![],
+            cycles: 0,
         })
     }
 
+    /// Get a handle to trigger the black box from outside of the task chain
+    pub(crate) fn trigger_handle(&self) -> TriggerHandle {
+        self.trigger.clone()
+    }
+
     /// Run the recording
     pub(crate) fn run(&mut self) {
         // Create transcoders reading from the required topics
         debug!("Creating transcoders");
-        for (topic, type_name) in self.rules.iter() {
+        for (topic, rule) in self.rules.topics() {
+            let type_name = rule.type_name.unwrap_or_else(|| {
+                panic!("no type name for topic {topic}, expand the rules with topic specifications")
+            });
             let info = self
                 .registry
                 .info_name(type_name)
+                .or_else(|| self.registry.info_system_name(type_name))
                 .unwrap_or_else(|| panic!("type name {type_name} not in registry"));
             let transcoder_builder = &info.comrec_builder;
             let transcoder = transcoder_builder(topic);
-            debug!("Creating transcoder: {topic}, {type_name}");
-            self.transcoders.push(transcoder);
+            debug!("Creating transcoder: {topic}, {}, {rule:?}", info.type_name);
+            self.topics.push(RecordedTopic {
+                transcoder,
+                rule: *rule,
+                compression: rule.compression.unwrap_or(self.rules.compression),
+                last_sequence: None,
+            });
         }
 
         debug!("Starting main loop");
         let msg_buf_size = self
-            .transcoders
+            .topics
             .iter()
-            .map(|t| t.buffer_size())
+            .map(|t| t.transcoder.buffer_size())
             .max()
             .unwrap_or_default();
         let mut msg_buf = vec![0; msg_buf_size];
         loop {
+            // Handle external triggers
+            if self.trigger.take() {
+                let signal = Signal::RecordingTrigger((TriggerReason::External, timestamp()));
+                self.record_trigger(signal);
+            }
+
             // Receive the next signal from the primary process
             trace!("Waiting for next signal to record");
 
             let Ok(received) = self.connector.receive(self.receive_timeout) else {
                 error!("Failed to receive signal, trying to continue");
-                self.writer
-                    .flush()
-                    .unwrap_or_else(|_| error!("Failed to flush writer, trying to continue"));
+                // The primary process may be gone, so don't wait for the end of the task chain
+                self.write_pending_black_box();
+                if let Some(output) = self.output.as_mut() {
+                    output
+                        .flush()
+                        .unwrap_or_else(|_| error!("Failed to flush writer, trying to continue"));
+                }
                 continue;
             };
             let Some(signal) = received else {
@@ -117,6 +187,7 @@ impl<'s> FileRecorder<'s> {
             match signal {
                 Signal::StartupSync(sync_info) => {
                     timestamp::initialize_from(sync_info);
+                    self.write_header(sync_info);
                 }
                 // If received a step signal, or an end-of-taskchain signal,
                 // record the current latest change of com data, then record the signal.
@@ -128,9 +199,33 @@ impl<'s> FileRecorder<'s> {
                 Signal::TaskChainEnd(_) => {
                     self.record_com_data(&mut msg_buf);
                     self.record_signal(signal);
+                    #[cfg(feature = "runtime_tracing")]
+                    let _span = trace::finish_recorded_task_chain_span();
                     self.flush();
+                    self.write_black_box_if_due();
+                    self.queue_decoupled_cycle();
                     self.send_recorder_ready();
                 }
+                // Add the start of every task chain to the index of cycles,
+                // after starting a new split file if required
+                Signal::TaskChainStart(timestamp) => {
+                    self.cycles += 1;
+                    self.rotate_if_required();
+                    if let Some(output) = self.output.as_mut() {
+                        if let Some(splits) = self.splits.as_mut() {
+                            splits.cycle_started();
+                        }
+                        output.start_cycle(timestamp);
+                    }
+                    self.record_signal(signal);
+                }
+                Signal::RecordingTrigger((reason, _)) => {
+                    // Keep the trace of the overrun, if the flight recorder is enabled
+                    if reason == TriggerReason::Overrun {
+                        feo_tracing::request_dump();
+                    }
+                    self.record_trigger(signal);
+                }
 
                 // Otherwise, only record the signal
                 _ => {
@@ -140,18 +235,163 @@ impl<'s> FileRecorder<'s> {
         }
     }
 
+    /// Write the file header describing the recording
+    fn write_header(&mut self, sync_info: SyncInfo) {
+        if self.header.is_some() {
+            warn!("Received repeated startup sync, ignoring");
+            return;
+        }
+
+        let mut topics: Vec<TopicEntry> = self
+            .topics
+            .iter()
+            .map(|recorded| {
+                let type_name = recorded.transcoder.type_name();
+                let info = self
+                    .registry
+                    .info_name(type_name)
+                    .unwrap_or_else(|| panic!("type name {type_name} not in registry"));
+                TopicEntry {
+                    topic: recorded.transcoder.topic().to_owned(),
+                    type_name: type_name.to_owned(),
+                    schema: info.schema.clone(),
+                    compression: recorded.compression,
+                }
+            })
+            .collect();
+        topics.sort_by(|a, b| a.topic.cmp(&b.topic));
+
+        let header = FileHeader {
+            app_name: self.app_name.to_owned(),
+            sync_info,
+            topics,
+        };
+        if let Some(file) = self.file.take() {
+            let writer = RecordingWriter::new(file, &header)
+                .unwrap_or_else(|e| panic!("failed to write recording header: {e}"));
+            let output = match (self.decoupled, self.splits.take()) {
+                (Some(config), Some(splits)) => {
+                    let writer = DecoupledWriter::new(writer, splits, header.clone(), config);
+                    Output::Decoupled(writer)
+                }
+                (_, splits) => {
+                    self.splits = splits;
+                    Output::File(writer)
+                }
+            };
+            self.output = Some(output);
+        }
+        self.header = Some(header);
+    }
+
+    /// Finish the current split file and start the next one, if a rotation limit is reached
+    fn rotate_if_required(&mut self) {
+        let (Some(Output::File(writer)), Some(header), Some(splits)) = (
+            self.output.as_mut(),
+            self.header.as_ref(),
+            self.splits.as_mut(),
+        ) else {
+            return;
+        };
+        if let Err(e) = splits.rotate_if_required(writer, header) {
+            panic!("failed to rotate recording file: {e}");
+        }
+    }
+
+    /// Record the given trigger signal and trigger the black box, if any
+    ///
+    /// A failing activity stops the scheduler before the end of the task chain,
+    /// so the black box is written right away in this case.
+    fn record_trigger(&mut self, signal: Signal) {
+        self.record_signal(signal);
+        let (Some(Output::BlackBox(black_box)), Signal::RecordingTrigger((reason, _))) =
+            (self.output.as_mut(), signal)
+        else {
+            return;
+        };
+        black_box.trigger(reason);
+        if matches!(reason, TriggerReason::ActivityFailure(_)) {
+            self.write_pending_black_box();
+        }
+    }
+
+    /// Write the black box to a file, if a trigger is due
+    fn write_black_box_if_due(&mut self) {
+        let (Some(Output::BlackBox(black_box)), Some(header)) =
+            (self.output.as_mut(), self.header.as_ref())
+        else {
+            return;
+        };
+        match black_box.end_cycle(header) {
+            Ok(Some(path)) => info!("Wrote black box to {}", path.display()),
+            Ok(None) => (),
+            Err(e) => error!("Failed to write black box: {e}"),
+        }
+    }
+
+    /// Write the black box to a file right away, if a trigger is pending
+    fn write_pending_black_box(&mut self) {
+        let (Some(Output::BlackBox(black_box)), Some(header)) =
+            (self.output.as_mut(), self.header.as_ref())
+        else {
+            return;
+        };
+        match black_box.end_pending(header) {
+            Ok(Some(path)) => info!("Wrote black box to {}", path.display()),
+            Ok(None) => (),
+            Err(e) => error!("Failed to write black box: {e}"),
+        }
+    }
+
+    /// Queue the finished task chain for writing in decoupled mode
+    fn queue_decoupled_cycle(&mut self) {
+        if let Some(Output::Decoupled(writer)) = self.output.as_mut() {
+            if let Err(e) = writer.end_cycle() {
+                panic!("failed to write recording file: {e}");
+            }
+        }
+    }
+
     /// Flush the recording file
     fn flush(&mut self) {
-        if let Err(e) = self.writer.flush() {
-            panic!("failed to flush recording file: {e}");
+        if let Some(output) = self.output.as_mut() {
+            if let Err(e) = output.flush() {
+                panic!("failed to flush recording file: {e}");
+            }
         }
     }
 
-    // Record the latest changes of com data
+    // Record the latest changes of com data according to the recording rules
     fn record_com_data(&mut self, data_buffer: &mut [u8]) {
-        for transcoder in self.transcoders.iter() {
-            let data = transcoder.read_transcode(data_buffer);
-            if let Some(serialized_data) = data {
+        let (Some(_), Some(output)) = (self.header.as_ref(), self.output.as_mut()) else {
+            warn!("Received signal before startup sync, not recording com data");
+            return;
+        };
+        #[cfg(feature = "runtime_tracing")]
+        let _span = trace::record_com_data_span();
+        // Index of the current task chain
+        let cycle = self.cycles.saturating_sub(1);
+        for recorded in self.topics.iter_mut() {
+            let RecordingRule {
+                decimation,
+                on_change,
+                ..
+            } = recorded.rule;
+            // Position of the current task chain within the decimation period
+            let phase = cycle % u64::from(decimation.max(1));
+            if phase != 0 {
+                continue;
+            }
+            let skip_sequence = if on_change {
+                recorded.last_sequence
+            } else {
+                None
+            };
+            let transcoder = &recorded.transcoder;
+            let data = transcoder.read_transcode(data_buffer, skip_sequence);
+            if let Some((serialized_data, sequence)) = data {
+                recorded.last_sequence = sequence;
+                let stored_data = recorded.compression.compress(serialized_data);
                 // create serialized data description record
                 assert!(
                     transcoder.type_name().len() <= TOPIC_TYPENAME_MAX_SIZE,
@@ -164,24 +404,17 @@ impl<'s> FileRecorder<'s> {
                 let description = DataDescriptionRecord {
                     timestamp: timestamp(),
                     type_name: transcoder.type_name(),
-                    data_size: serialized_data.len(),
+                    data_size: stored_data.len(),
                     topic: transcoder.topic(),
                 };
                 let data_desc_record = Record::DataDescription(description);
-                let mut buf = [0u8; Record::POSTCARD_MAX_SIZE];
-                let serialized_header =
-                    postcard::to_slice(&data_desc_record, &mut buf).expect("serialization failed");
 
                 trace!("Writing data: {description:?}");
 
                 // Write description record and subsequent data block
                 // In case of failure, log an error message and continue
                 // (which may result in a corrupted file)
-                if let Err(e) = self
-                    .writer
-                    .write_all(serialized_header)
-                    .and_then(|_| self.writer.write_all(serialized_data))
-                {
+                if let Err(e) = output.write_record(&data_desc_record, &stored_data) {
                     error!("Failed to write data: {e:?}");
                 }
             }
@@ -190,14 +423,15 @@ impl<'s> FileRecorder<'s> {
 
     /// Record the given signal
     fn record_signal(&mut self, signal: Signal) {
+        let (Some(_), Some(output)) = (self.header.as_ref(), self.output.as_mut()) else {
+            warn!("Received signal {signal} before startup sync, not recording it");
+            return;
+        };
         let signal_record = Record::Signal(SignalRecord {
             signal,
             timestamp: timestamp(),
         });
-        let mut buf = [0u8; Record::POSTCARD_MAX_SIZE];
-        let serialized =
-            postcard::to_slice(&signal_record, &mut buf).expect("serialization failed");
-        if let Err(e) = self.writer.write_all(serialized) {
+        if let Err(e) = output.write_record(&signal_record, &[]) {
             error!("Failed to write signal {signal:?}: {e:?}");
         }
     }
@@ -213,15 +447,240 @@ impl<'s> FileRecorder<'s> {
 
 impl Drop for FileRecorder<'_> {
     fn drop(&mut self) {
-        // Try to flush pending data.
-        self.flush();
+        // Try to write a triggered black box, the index and flush pending data.
+        self.write_pending_black_box();
+        let result = match self.output.as_mut() {
+            Some(Output::File(writer)) => writer.finish(),
+            Some(Output::Decoupled(writer)) => writer.finish(),
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            panic!("failed to finish recording file: {e}");
+        }
+    }
+}
+
+/// Destination of recorded data
+enum Output {
+    /// Writer of the recording file or its current split file
+    File(RecordingWriter<BufWriter<fs::File>>),
+    /// In-memory black box, written to a file when triggered
+    BlackBox(BlackBox),
+    /// Queue to a background thread writing the recording file
+    Decoupled(DecoupledWriter),
+}
+
+impl Output {
+    /// Start a new task chain
+    fn start_cycle(&mut self, timestamp: Timestamp) {
+        match self {
+            Output::File(writer) => writer.start_cycle(timestamp),
+            Output::BlackBox(black_box) => black_box.start_cycle(timestamp),
+            Output::Decoupled(writer) => writer.start_cycle(timestamp),
+        }
+    }
+
+    /// Write the given record, followed by the given serialized data
+    fn write_record(&mut self, record: &Record, data: &[u8]) -> io::Result<()> {
+        match self {
+            Output::File(writer) => writer.write_record(record, data),
+            Output::BlackBox(black_box) => black_box.write_record(record, data),
+            Output::Decoupled(writer) => writer.write_record(record, data),
+        }
+    }
+
+    /// Flush the recording file, if written by the recorder itself
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::File(writer) => writer.flush(),
+            Output::BlackBox(_) | Output::Decoupled(_) => Ok(()),
+        }
+    }
+}
+
+/// A recorded topic with its transcoder and recording state
+struct RecordedTopic {
+    /// Transcoder reading and serializing the com data
+    transcoder: Box<dyn ComRecTranscoder>,
+    /// Rule applying to the topic
+    rule: RecordingRule,
+    /// Compression of the serialized data
+    compression: Compression,
+    /// Sequence number of the last recorded sample, if provided by the com backend
+    last_sequence: Option<u64>,
+}
+
+/// Rule about how to record a topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordingRule {
+    /// Type name from the type registry or system type name of the data on the topic
+    ///
+    /// May be omitted if the rules are expanded with topic specifications,
+    /// see [`RecordingRules::expand`].
+    pub type_name: Option<&'static str>,
+    /// Record the topic only in every n-th task chain, starting with the first one
+    pub decimation: u32,
+    /// Record samples only if they changed since they were last recorded
+    ///
+    /// Changes are detected by the sequence number of the sample.
+    /// If the com backend does not provide sequence numbers, every sample read is recorded.
+    pub on_change: bool,
+    /// Compression of the serialized data, overriding the compression of the rule set
+    pub compression: Option<Compression>,
+}
+
+impl RecordingRule {
+    /// Record the topic with the given type name in every task chain
+    pub const fn new(type_name: &'static str) -> Self {
+        Self {
+            type_name: Some(type_name),
+            decimation: 1,
+            on_change: false,
+            compression: None,
+        }
+    }
+
+    /// Record the topic only in every n-th task chain
+    pub const fn every(mut self, decimation: u32) -> Self {
+        self.decimation = decimation;
+        self
+    }
+
+    /// Record samples only if they changed since they were last recorded
+    pub const fn only_on_change(mut self) -> Self {
+        self.on_change = true;
+        self
+    }
+
+    /// Compress the serialized data with the given compression
+    pub const fn compressed(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+}
+
+impl Default for RecordingRule {
+    /// Record the topic in every task chain, taking its type from the topic specification
+    fn default() -> Self {
+        Self {
+            type_name: None,
+            decimation: 1,
+            on_change: false,
+            compression: None,
+        }
     }
 }
 
 /// Set of recording rules
 ///
-/// Maps every topic to be recorded to a corresponding type name from the type registry
-pub type RecordingRules = HashMap<&'static str, &'static str>;
+/// Maps the topics to be recorded to rules about how to record them.
+/// Topic patterns may contain `*` wildcards matching any sequence of characters,
+/// which must be resolved with [`RecordingRules::expand`].
+/// If several patterns match a topic, the rule added first applies.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingRules {
+    rules: Vec<(&'static str, RecordingRule)>,
+    /// Compression of topics whose rule does not specify one
+    compression: Compression,
+}
+
+impl RecordingRules {
+    /// Create an empty set of rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule for the given topic or topic pattern
+    pub fn add(&mut self, pattern: &'static str, rule: RecordingRule) -> &mut Self {
+        self.rules.push((pattern, rule));
+        self
+    }
+
+    /// Compress the data of all topics whose rule does not specify a compression
+    pub fn compress(&mut self, compression: Compression) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Get the rule applying to the given topic, if any
+    pub fn rule(&self, topic: &str) -> Option<&RecordingRule> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, topic))
+            .map(|(_, rule)| rule)
+    }
+
+    /// Check whether the given topic is to be recorded
+    pub fn contains(&self, topic: &str) -> bool {
+        self.rule(topic).is_some()
+    }
+
+    /// Resolve the rules for the given topic specifications
+    ///
+    /// Returns rules for all specified topics matching a rule,
+    /// with type names taken from the specifications where not given by the rule.
+    pub fn expand(&self, specs: &[TopicSpecification<'static>]) -> Self {
+        let rules = specs
+            .iter()
+            .filter_map(|spec| {
+                let rule = self.rule(spec.topic)?;
+                let rule = RecordingRule {
+                    type_name: rule.type_name.or(Some(spec.type_name)),
+                    ..*rule
+                };
+                Some((spec.topic, rule))
+            })
+            .collect();
+        Self {
+            rules,
+            compression: self.compression,
+        }
+    }
+
+    /// Iterate over the rules of concrete topics, skipping unresolved patterns
+    pub(crate) fn topics(&self) -> impl Iterator<Item = (&'static str, &RecordingRule)> {
+        self.rules.iter().filter_map(|(topic, rule)| {
+            if topic.contains('*') {
+                warn!("Ignoring unresolved recording rule pattern {topic}");
+                None
+            } else {
+                Some((*topic, rule))
+            }
+        })
+    }
+}
+
+impl<const N: usize> From<[(&'static str, &'static str); N]> for RecordingRules {
+    /// Record the given topics with the given type names in every task chain
+    fn from(topics: [(&'static str, &'static str); N]) -> Self {
+        let rules = topics
+            .into_iter()
+            .map(|(topic, type_name)| (topic, RecordingRule::new(type_name)))
+            .collect();
+        Self {
+            rules,
+            compression: Compression::None,
+        }
+    }
+}
+
+/// Check whether the topic matches the pattern, where `*` matches any sequence of characters
+fn matches_pattern(pattern: &str, topic: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == topic,
+        Some((prefix, rest)) => {
+            let Some(topic) = topic.strip_prefix(prefix) else {
+                return false;
+            };
+            // Try all possible lengths of the sequence matched by the wildcard
+            topic
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([topic.len()])
+                .any(|i| matches_pattern(rest, &topic[i..]))
+        }
+    }
+}
 
 /// Possible records in the recording file
 #[derive(Debug, Serialize, Deserialize, MaxSize)]
@@ -229,6 +688,7 @@ pub enum Record<'s> {
     Signal(SignalRecord),
     #[serde(borrow)]
     DataDescription(DataDescriptionRecord<'s>),
+    DroppedCycles(DroppedCyclesRecord),
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, MaxSize)]
@@ -239,6 +699,15 @@ pub struct SignalRecord {
     pub signal: Signal,
 }
 
+/// Task chains not recorded because the recorder could not keep up, see [`decoupled`](super::decoupled)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, MaxSize)]
+pub struct DroppedCyclesRecord {
+    /// The start time of the first dropped task chain
+    pub timestamp: Timestamp,
+    /// The number of consecutive task chains dropped
+    pub cycles: u64,
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct DataDescriptionRecord<'s> {
     // The monotonic time at the moment of recording as duration since the epoch
@@ -254,6 +723,7 @@ pub struct DataDescriptionRecord<'s> {
 }
 
 impl MaxSize for DataDescriptionRecord<'_> {
+    #[allow(clippy::identity_op)]
     const POSTCARD_MAX_SIZE: usize = Timestamp::POSTCARD_MAX_SIZE +
         usize::POSTCARD_MAX_SIZE + // data_size
         2*( // type_name, topic
@@ -280,4 +750,54 @@ mod test {
         let mut buf = [0u8; DataDescriptionRecord::POSTCARD_MAX_SIZE];
         postcard::to_slice(&record, &mut buf).expect("should fit");
     }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("a/b", "a/b"));
+        assert!(!matches_pattern("a/b", "a/bc"));
+        assert!(matches_pattern("a/*", "a/b/c"));
+        assert!(matches_pattern("*/c", "a/b/c"));
+        assert!(matches_pattern("a/*/c", "a/b/c"));
+        assert!(!matches_pattern("a/*/c", "a/b/d"));
+        assert!(matches_pattern("*", ""));
+    }
+
+    #[test]
+    fn test_expand_rules() {
+        let specs = [
+            TopicSpecification::new::<u32>("vehicle/camera/front", vec![]),
+            TopicSpecification::new::<u64>("vehicle/control/brakes", vec![]),
+            TopicSpecification::new::<u8>("diagnostics", vec![]),
+        ];
+        let mut rules = RecordingRules::new();
+        rules
+            .add("vehicle/camera/*", RecordingRule::default().every(5))
+            .add("vehicle/*", RecordingRule::new("Brakes").only_on_change());
+
+        let expanded = rules.expand(&specs);
+        let topics: Vec<_> = expanded.topics().map(|(t, r)| (t, *r)).collect();
+        assert_eq!(
+            topics,
+            [
+                (
+                    "vehicle/camera/front",
+                    RecordingRule {
+                        type_name: Some("u32"),
+                        decimation: 5,
+                        on_change: false,
+                        compression: None
+                    }
+                ),
+                (
+                    "vehicle/control/brakes",
+                    RecordingRule {
+                        type_name: Some("Brakes"),
+                        decimation: 1,
+                        on_change: true,
+                        compression: None
+                    }
+                ),
+            ]
+        );
+    }
 }
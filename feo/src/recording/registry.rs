@@ -3,10 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Type registry
-use crate::recording::transcoder::{ComRecTranscoderBuilder, RecordingTranscoder};
+use crate::recording::decoder::{decode, DecodeError, DecodeFn, Decoded};
+use crate::recording::format::TypeSchema;
+use crate::recording::inspector::{Inspector, TopicInspectorBuilder};
+use crate::recording::transcoder::{
+    ComRecTranscoderBuilder, RecComTranscoderBuilder, RecordingTranscoder, ReplayTranscoder,
+};
 use alloc::borrow::ToOwned as _;
 use alloc::boxed::Box;
-use feo_com::interface::ActivityInput;
+use feo_com::interface::{ActivityInput, ActivityOutput, ComBackend};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
 
@@ -39,6 +45,8 @@ impl TypeRegistry {
     ///
     /// The user may define a unique type name, otherwise the system type name will be used.
     /// Note that system type names may not be unique in which case the method will panic.
+    /// The input builder is used to read the type from com for recording and inspection.
+    /// Topics of types added with this method cannot be replayed, see [`Self::add_replayable`].
     ///
     /// # Panics
     ///
@@ -46,26 +54,44 @@ impl TypeRegistry {
     /// - a type with identical type id (i.e. the same type) has already been registered
     /// - the explicitly or implicitly provided type name is not unique
     pub fn add<
-        T: Serialize + postcard::experimental::max_size::MaxSize + core::fmt::Debug + 'static,
+        T: Serialize
+            + DeserializeOwned
+            + postcard::experimental::max_size::MaxSize
+            + core::fmt::Debug
+            + 'static,
     >(
         &mut self,
         type_name: Option<&'static str>,
         input_builder: impl Fn(&str) -> Box<dyn ActivityInput<T>> + Clone + Send + 'static,
     ) -> &mut Self {
-        let type_name = type_name.unwrap_or(core::any::type_name::<T>());
-        let decser_builder = {
-            let type_name = type_name.to_owned();
-            let input_builder = input_builder.clone();
-            Box::new(move |topic: &str| {
-                let topic = topic.to_owned();
-                let type_name = type_name.clone();
-                RecordingTranscoder::<T>::build(input_builder.clone(), topic, type_name)
-            }) as Box<dyn ComRecTranscoderBuilder>
-        };
-        let type_info = TypeInfo {
-            type_name,
-            comrec_builder: decser_builder,
-        };
+        let type_info = TypeInfo::new::<T>(type_name, input_builder, None);
+        self.add_helper(type_info)
+    }
+
+    /// Add the given type to the registry, allowing to replay its topics
+    ///
+    /// In addition to [`Self::add`], the output builder is used to write recorded data
+    /// to com for replay.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic under the same conditions as [`Self::add`].
+    pub fn add_replayable<
+        T: Serialize
+            + DeserializeOwned
+            + postcard::experimental::max_size::MaxSize
+            + core::fmt::Debug
+            + 'static,
+    >(
+        &mut self,
+        type_name: Option<&'static str>,
+        input_builder: impl Fn(&str) -> Box<dyn ActivityInput<T>> + Clone + Send + 'static,
+        output_builder: impl Fn(&str) -> Box<dyn ActivityOutput<T>> + Clone + Send + 'static,
+    ) -> &mut Self {
+        let reccom_builder = Box::new(move |topic: &str| {
+            ReplayTranscoder::<T>::build(output_builder.clone(), topic)
+        }) as Box<dyn RecComTranscoderBuilder>;
+        let type_info = TypeInfo::new::<T>(type_name, input_builder, Some(reccom_builder));
         self.add_helper(type_info)
     }
 
@@ -81,6 +107,21 @@ impl TypeRegistry {
     pub fn info_name(&self, type_name: &str) -> Option<&TypeInfo> {
         self.map.get(type_name)
     }
+
+    /// Decode recorded data through the registered type of the given name
+    pub fn decode(&self, type_name: &str, data: &[u8]) -> Result<Box<dyn Decoded>, DecodeError> {
+        let info = self
+            .info_name(type_name)
+            .ok_or_else(|| DecodeError::UnknownType(type_name.into()))?;
+        (info.decode_fn)(data).map_err(DecodeError::Deserialize)
+    }
+
+    /// Retrieve a [`TypeInfo`] for the given system type name, or None if not existent
+    pub fn info_system_name(&self, system_type_name: &str) -> Option<&TypeInfo> {
+        self.map
+            .values()
+            .find(|info| info.system_type_name == system_type_name)
+    }
 }
 
 impl Default for TypeRegistry {
@@ -97,8 +138,62 @@ pub struct TypeInfo {
     // Human-readable type name
     pub type_name: &'static str,
 
+    // System type name as provided by [`core::any::type_name`]
+    pub system_type_name: &'static str,
+
+    // Schema of the type as written to recording files
+    pub schema: TypeSchema,
+
+    // Function deserializing recorded data of the type
+    pub decode_fn: DecodeFn,
+
     // Corresponding [`ComRecTranscoderBuilder`] object
     pub comrec_builder: Box<dyn ComRecTranscoderBuilder>,
+
+    // Corresponding [`RecComTranscoderBuilder`] object, or None if the type cannot be replayed
+    pub reccom_builder: Option<Box<dyn RecComTranscoderBuilder>>,
+
+    // Corresponding [`TopicInspectorBuilder`] object
+    pub inspector_builder: Box<dyn TopicInspectorBuilder>,
+}
+
+impl TypeInfo {
+    /// Create the type information of the given type
+    fn new<
+        T: Serialize
+            + DeserializeOwned
+            + postcard::experimental::max_size::MaxSize
+            + core::fmt::Debug
+            + 'static,
+    >(
+        type_name: Option<&'static str>,
+        input_builder: impl Fn(&str) -> Box<dyn ActivityInput<T>> + Clone + Send + 'static,
+        reccom_builder: Option<Box<dyn RecComTranscoderBuilder>>,
+    ) -> Self {
+        let system_type_name = core::any::type_name::<T>();
+        let type_name = type_name.unwrap_or(system_type_name);
+        let decser_builder = {
+            let type_name = type_name.to_owned();
+            let input_builder = input_builder.clone();
+            Box::new(move |topic: &str| {
+                let topic = topic.to_owned();
+                let type_name = type_name.clone();
+                RecordingTranscoder::<T>::build(input_builder.clone(), topic, type_name)
+            }) as Box<dyn ComRecTranscoderBuilder>
+        };
+        let inspector_builder = Box::new(move |topic: &str, backend: ComBackend| {
+            Inspector::<T>::build(input_builder.clone(), topic, backend)
+        }) as Box<dyn TopicInspectorBuilder>;
+        Self {
+            type_name,
+            system_type_name,
+            schema: TypeSchema::of::<T>(),
+            decode_fn: decode::<T>,
+            comrec_builder: decser_builder,
+            reccom_builder,
+            inspector_builder,
+        }
+    }
 }
 
 impl core::fmt::Debug for TypeInfo {
@@ -110,6 +205,8 @@ impl core::fmt::Debug for TypeInfo {
     }
 }
 
+/// Add a type to the registry with [`TypeRegistry::add`], or with
+/// [`TypeRegistry::add_replayable`] if an output builder is given
 #[macro_export]
 macro_rules! register_type {
     ($registry:ident, $type:ty: $name:expr, $input:expr) => {
@@ -118,16 +215,24 @@ macro_rules! register_type {
     ($registry:ident, $type:ty, $input:expr) => {
         $registry.add::<$type>(None, $input)
     };
+    ($registry:ident, $type:ty: $name:expr, $input:expr, $output:expr) => {
+        $registry.add_replayable::<$type>(Some($name), $input, $output)
+    };
+    ($registry:ident, $type:ty, $input:expr, $output:expr) => {
+        $registry.add_replayable::<$type>(None, $input, $output)
+    };
 }
 
+/// Add several types to the registry, see [`register_type`]
 #[macro_export]
 macro_rules! register_types {
-    ($registry:ident; $($type:ty $(:$name:expr)?, $input_builder:expr);+ $(,)?) => {
+    ($registry:ident; $($type:ty $(:$name:expr)?, $input_builder:expr $(, $output_builder:expr)?);+ $(,)?) => {
         $(
             register_type!(
                 $registry,
                 $type $(:$name)?,
                 $input_builder
+                $(, $output_builder)?
             )
         );+
     };
@@ -149,17 +254,40 @@ fn test_type_registry() {
         }
     }
 
-    #[derive(Debug, serde::Serialize, postcard::experimental::max_size::MaxSize)]
+    #[derive(Debug)]
+    // Dummy output implementation for the test, never providing a buffer
+    struct DummyOutput;
+
+    impl<T: core::fmt::Debug> ActivityOutput<T> for DummyOutput {
+        fn write_uninit(
+            &mut self,
+        ) -> Result<feo_com::interface::OutputUninitGuard<T>, feo_com::interface::Error> {
+            Err(feo_com::interface::Error::NoEmptyBuffer)
+        }
+    }
+
+    #[derive(
+        Debug, serde::Serialize, serde::Deserialize, postcard::experimental::max_size::MaxSize,
+    )]
     struct TestType1 {}
 
-    #[derive(Debug, serde::Serialize, postcard::experimental::max_size::MaxSize)]
+    #[derive(
+        Debug, serde::Serialize, serde::Deserialize, postcard::experimental::max_size::MaxSize,
+    )]
     struct TestType2 {}
 
-    #[derive(Debug, serde::Serialize, postcard::experimental::max_size::MaxSize)]
+    #[derive(
+        Debug, serde::Serialize, serde::Deserialize, postcard::experimental::max_size::MaxSize,
+    )]
     struct TestType3 {}
 
     let mut registry = TypeRegistry::default();
-    register_types!(registry; TestType1, |_: &str| Box::new(DummyInput); TestType2, |_: &str| Box::new(DummyInput); TestType3: "my_test_type3_name", |_: &str| Box::new(DummyInput));
+    register_types!(
+        registry;
+        TestType1, |_: &str| Box::new(DummyInput), |_: &str| Box::new(DummyOutput);
+        TestType2, |_: &str| Box::new(DummyInput);
+        TestType3: "my_test_type3_name", |_: &str| Box::new(DummyInput), |_: &str| Box::new(DummyOutput)
+    );
 
     // test presence and data of entry for TestType1
     let type_name = core::any::type_name::<TestType1>();
@@ -167,11 +295,32 @@ fn test_type_registry() {
     assert!(registry.info_name(type_name).is_some());
     assert_eq!(registry.info_name(type_name).unwrap().type_name, type_name);
 
+    // test replayability of TestType1 and TestType2
+    assert!(registry
+        .info_name(type_name)
+        .unwrap()
+        .reccom_builder
+        .is_some());
+    let type_name = core::any::type_name::<TestType2>();
+    assert!(registry
+        .info_name(type_name)
+        .unwrap()
+        .reccom_builder
+        .is_none());
+
     // test presence and data of entry for TestType3
     let type_name = "my_test_type3_name";
     assert!(registry.map.contains_key(&type_name));
     assert!(registry.info_name(type_name).is_some());
     assert_eq!(registry.info_name(type_name).unwrap().type_name, type_name);
+    let system_type_name = core::any::type_name::<TestType3>();
+    assert_eq!(
+        registry
+            .info_system_name(system_type_name)
+            .unwrap()
+            .type_name,
+        type_name
+    );
 
     // test missing type Foo
     struct Foo {}
@@ -0,0 +1,472 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! FEO data replay. Re-runs activities against recorded communication
+
+use crate::error::Error;
+use crate::ids::{ActivityId, AgentId};
+use crate::recording::reader::{RecordEntry, RecordingReader};
+use crate::recording::recorder::SignalRecord;
+use crate::recording::registry::TypeRegistry;
+use crate::recording::transcoder::RecComTranscoder;
+use crate::signalling::common::interface::ConnectScheduler;
+use crate::signalling::common::signals::Signal;
+use crate::timestamp::{timestamp, Timestamp};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::time::Duration;
+use feo_log::{debug, error, info, trace, warn};
+use feo_time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::BufReader;
+use std::path::Path;
+use std::thread;
+
+/// The data replayer
+///
+/// The replayer takes the role of the scheduler: It publishes recorded com data
+/// on the replayed topics and triggers the replayed activities in the recorded order,
+/// skipping all other activities.
+pub(crate) struct Replayer {
+    /// Reader of the recording
+    reader: RecordingReader<BufReader<fs::File>>,
+
+    /// Transcoders writing recorded data to com, per replayed topic
+    transcoders: HashMap<&'static str, Box<dyn RecComTranscoder>>,
+
+    /// Activities to be re-run
+    activities: Vec<ActivityId>,
+
+    /// Timeout of receive function
+    receive_timeout: Duration,
+
+    /// Helper object connecting to activities in all connected agents
+    connector: Box<dyn ConnectScheduler>,
+
+    /// Agent IDs of expected recorders
+    recorder_ids: Vec<AgentId>,
+
+    /// Activities which have been stepped in the current task chain
+    triggered: HashSet<ActivityId>,
+
+    /// Activities which have signalled ready in the current task chain
+    ready: HashSet<ActivityId>,
+}
+
+impl Replayer {
+    /// Create a new replayer
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if a replayed topic is not contained in the recording,
+    /// if its type is not registered or registered without an output builder,
+    /// or if the registered type does not match the recorded schema.
+    pub(crate) fn new(
+        record_file: &Path,
+        topics: &[&'static str],
+        registry: &TypeRegistry,
+        activities: Vec<ActivityId>,
+        receive_timeout: Duration,
+        connector: Box<dyn ConnectScheduler>,
+        recorder_ids: Vec<AgentId>,
+    ) -> Result<Self, Error> {
+        let reader = RecordingReader::open(record_file)?;
+        let header = reader.header();
+        info!(
+            "Replaying recording of '{}' with {} topics",
+            header.app_name,
+            header.topics.len()
+        );
+
+        let transcoders = topics
+            .iter()
+            .map(|topic| {
+                let entry = header
+                    .topic(topic)
+                    .unwrap_or_else(|| panic!("topic {topic} not in recording"));
+                let info = registry
+                    .info_name(&entry.type_name)
+                    .unwrap_or_else(|| panic!("type name {} not in registry", entry.type_name));
+                assert!(
+                    info.schema.fingerprint == entry.schema.fingerprint,
+                    "type {} of topic {topic} does not match the recorded schema, \
+                     possibly recorded by a different build",
+                    entry.type_name
+                );
+                let reccom_builder = info.reccom_builder.as_ref().unwrap_or_else(|| {
+                    panic!(
+                        "type {} of topic {topic} is not replayable",
+                        entry.type_name
+                    )
+                });
+                debug!("Creating replay transcoder: {topic}, {}", entry.type_name);
+                (*topic, reccom_builder(topic))
+            })
+            .collect();
+
+        Ok(Self {
+            reader,
+            transcoders,
+            activities,
+            receive_timeout,
+            connector,
+            recorder_ids,
+            triggered: HashSet::new(),
+            ready: HashSet::new(),
+        })
+    }
+
+    /// Synchronize all remote agents and recorders
+    pub(crate) fn sync_remotes(&mut self) -> Result<(), Error> {
+        self.connector.sync_time()?;
+        info!("Time synchronization of remote agents done");
+        Ok(())
+    }
+
+    /// Run startup of the replayed activities and replay the recording until its end
+    pub(crate) fn run(&mut self) -> Result<(), Error> {
+        let mut activity_ids = self.activities.clone();
+        activity_ids.sort();
+        for id in activity_ids.iter() {
+            debug!("Triggering startup for activity {id}");
+            self.trigger_activity(*id, &Signal::Startup((*id, timestamp())))?;
+        }
+        for id in activity_ids.iter() {
+            self.wait_ready(*id)?;
+        }
+
+        // Recorded start time of the first replayed task chain and local time of its replay
+        let mut start: Option<(Timestamp, Instant)> = None;
+        let mut cycles = 0usize;
+
+        while let Some(record) = self.reader.next_record()? {
+            let signal = match record {
+                RecordEntry::Data { description, data } => {
+                    // Skip data recorded before the first task chain
+                    if start.is_none() {
+                        continue;
+                    }
+                    if let Some(transcoder) = self.transcoders.get_mut(description.topic) {
+                        trace!("Replaying data on topic {}", description.topic);
+                        if !transcoder.transcode_write(data) {
+                            error!("Failed to replay data on topic {}", description.topic);
+                        }
+                    }
+                    continue;
+                }
+                RecordEntry::Signal(SignalRecord { signal, .. }) => signal,
+                RecordEntry::DroppedCycles(dropped) => {
+                    warn!(
+                        "Recording lacks {} task chains, replaying without them",
+                        dropped.cycles
+                    );
+                    continue;
+                }
+            };
+
+            match signal {
+                Signal::TaskChainStart(recorded) => {
+                    // Keep the recorded distance between the starts of task chains
+                    match start {
+                        None => start = Some((recorded, Instant::now())),
+                        Some((recorded_start, local_start)) => {
+                            let offset = recorded.0.saturating_sub(recorded_start.0);
+                            let time_left = offset.saturating_sub(local_start.elapsed());
+                            thread::sleep(time_left);
+                        }
+                    }
+                    debug!("Starting task chain");
+                    self.triggered.clear();
+                    self.ready.clear();
+                    self.send_to_recorders(&Signal::TaskChainStart(timestamp()))?;
+                }
+                _ if start.is_none() => {}
+                Signal::Step((id, cycle, _)) if self.activities.contains(&id) => {
+                    debug!("Triggering step for activity {id}");
+                    self.trigger_activity(id, &Signal::Step((id, cycle, timestamp())))?;
+                    self.triggered.insert(id);
+                }
+                Signal::Ready((id, _)) if self.triggered.contains(&id) => {
+                    self.wait_ready(id)?;
+                }
+                Signal::TaskChainEnd(_) => {
+                    let triggered: Vec<_> = self.triggered.iter().copied().collect();
+                    for id in triggered {
+                        self.wait_ready(id)?;
+                    }
+                    self.send_to_recorders(&Signal::TaskChainEnd(timestamp()))?;
+                    self.wait_recorders_ready()?;
+                    cycles += 1;
+                }
+                _ => {}
+            }
+        }
+
+        info!("Replay finished after {cycles} task chains");
+        Ok(())
+    }
+
+    /// Trigger activity by forwarding the signal to the activity and all recorders
+    fn trigger_activity(&mut self, id: ActivityId, signal: &Signal) -> Result<(), Error> {
+        self.connector.send_to_activity(id, signal)?;
+        self.send_to_recorders(signal)
+    }
+
+    /// Send the given signal to all recorders
+    fn send_to_recorders(&mut self, signal: &Signal) -> Result<(), Error> {
+        for id in self.recorder_ids.iter() {
+            self.connector.send_to_recorder(*id, signal)?;
+        }
+        Ok(())
+    }
+
+    /// Wait until the given activity has signalled ready
+    fn wait_ready(&mut self, activity_id: ActivityId) -> Result<(), Error> {
+        while !self.ready.contains(&activity_id) {
+            match self.connector.receive(self.receive_timeout)? {
+                None => continue,
+                Some(signal @ Signal::Ready((id, _))) => {
+                    self.send_to_recorders(&signal)?;
+                    self.ready.insert(id);
+                }
+                Some(signal @ Signal::RecordingTrigger(_)) => {
+                    self.send_to_recorders(&signal)?;
+                }
+                Some(other) => {
+                    error!("Received unexpected signal {other:?} while waiting for ready signal");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait until all recorders have signalled ready
+    fn wait_recorders_ready(&mut self) -> Result<(), Error> {
+        let mut pending: HashSet<AgentId> = self.recorder_ids.iter().copied().collect();
+        while !pending.is_empty() {
+            match self.connector.receive(self.receive_timeout)? {
+                None => continue,
+                Some(Signal::RecorderReady((id, _))) => {
+                    if !pending.remove(&id) {
+                        error!("Received unexpected id {id} in recorder ready signal");
+                    }
+                }
+                Some(other) => {
+                    error!("Received unexpected signal {other} while waiting for recorder ready signal");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::recording::format::{
+        Compression, FileHeader, RecordingWriter, TopicEntry, TypeSchema,
+    };
+    use crate::recording::recorder::{DataDescriptionRecord, Record};
+    use crate::recording::test_util::TestDir;
+    use crate::timestamp::SyncInfo;
+    use alloc::collections::VecDeque;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use feo_com::interface::{ActivityInput, ActivityOutput, InputGuard, OutputUninitGuard};
+    use std::sync::Mutex;
+
+    const ACTIVITY: ActivityId = ActivityId::new(1);
+    const SKIPPED_ACTIVITY: ActivityId = ActivityId::new(2);
+    const RECORDER: AgentId = AgentId::new(900);
+
+    // Connector answering each trigger with a ready signal and logging all sent signals
+    struct MockConnector {
+        sent: Arc<Mutex<Vec<String>>>,
+        pending: VecDeque<Signal>,
+    }
+
+    impl ConnectScheduler for MockConnector {
+        fn connect_remotes(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn sync_time(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn receive(&mut self, _timeout: Duration) -> Result<Option<Signal>, Error> {
+            let signal = self.pending.pop_front().expect("no pending signal");
+            Ok(Some(signal))
+        }
+
+        fn send_to_activity(&mut self, id: ActivityId, signal: &Signal) -> Result<(), Error> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push(format!("activity {id}: {}", kind(signal)));
+            self.pending.push_back(Signal::Ready((id, timestamp())));
+            Ok(())
+        }
+
+        fn send_to_recorder(&mut self, id: AgentId, signal: &Signal) -> Result<(), Error> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push(format!("recorder {id}: {}", kind(signal)));
+            if matches!(signal, Signal::TaskChainEnd(_)) {
+                self.pending
+                    .push_back(Signal::RecorderReady((id, timestamp())));
+            }
+            Ok(())
+        }
+
+        fn send_to_remote_agents(&mut self, _signal: &Signal) -> Result<(), Error> {
+            unimplemented!("not sent by the replayer")
+        }
+    }
+
+    // Signal without its timestamp
+    fn kind(signal: &Signal) -> String {
+        match signal {
+            Signal::Startup((id, _)) => format!("startup {id}"),
+            Signal::Step((id, _, _)) => format!("step {id}"),
+            Signal::Ready((id, _)) => format!("ready {id}"),
+            Signal::TaskChainStart(_) => "start".into(),
+            Signal::TaskChainEnd(_) => "end".into(),
+            other => format!("{other}"),
+        }
+    }
+
+    #[derive(Debug)]
+    // Input never providing a buffer
+    struct DummyInput;
+
+    impl ActivityInput<u64> for DummyInput {
+        fn read(&self) -> Result<InputGuard<u64>, feo_com::interface::Error> {
+            Err(feo_com::interface::Error::NoEmptyBuffer)
+        }
+    }
+
+    #[derive(Debug)]
+    // Output counting the replayed values, never providing a buffer
+    struct CountingOutput(Arc<AtomicUsize>);
+
+    impl ActivityOutput<u64> for CountingOutput {
+        fn write_uninit(&mut self) -> Result<OutputUninitGuard<u64>, feo_com::interface::Error> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Err(feo_com::interface::Error::NoEmptyBuffer)
+        }
+    }
+
+    fn write_recording(path: &Path) {
+        let header = FileHeader {
+            app_name: "test".into(),
+            sync_info: SyncInfo::from(42u64),
+            topics: vec![TopicEntry {
+                topic: "topic".into(),
+                type_name: "u64".into(),
+                schema: TypeSchema::of::<u64>(),
+                compression: Compression::None,
+            }],
+        };
+        let mut bytes = Vec::new();
+        let mut writer = RecordingWriter::new(&mut bytes, &header).unwrap();
+        let write_signal = |writer: &mut RecordingWriter<_>, signal| {
+            let record = Record::Signal(SignalRecord {
+                timestamp: Timestamp(Duration::ZERO),
+                signal,
+            });
+            writer.write_record(&record, &[]).unwrap();
+        };
+        let write_data = |writer: &mut RecordingWriter<_>, value: u64| {
+            let data = postcard::to_allocvec(&value).unwrap();
+            let record = Record::DataDescription(DataDescriptionRecord {
+                timestamp: Timestamp(Duration::ZERO),
+                data_size: data.len(),
+                type_name: "u64",
+                topic: "topic",
+            });
+            writer.write_record(&record, &data).unwrap();
+        };
+
+        // Data and signals before the first task chain
+        let t = Timestamp(Duration::ZERO);
+        write_data(&mut writer, 1);
+        write_signal(&mut writer, Signal::Step((ACTIVITY, 0, t)));
+        write_signal(&mut writer, Signal::Ready((ACTIVITY, t)));
+
+        for cycle in 0..2 {
+            let t = Timestamp(Duration::from_millis(cycle));
+            writer.start_cycle(t);
+            write_signal(&mut writer, Signal::TaskChainStart(t));
+            write_data(&mut writer, cycle + 2);
+            write_signal(&mut writer, Signal::Step((SKIPPED_ACTIVITY, cycle, t)));
+            write_signal(&mut writer, Signal::Step((ACTIVITY, cycle, t)));
+            write_signal(&mut writer, Signal::Ready((SKIPPED_ACTIVITY, t)));
+            write_signal(&mut writer, Signal::Ready((ACTIVITY, t)));
+            write_signal(&mut writer, Signal::TaskChainEnd(t));
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_replay() {
+        crate::timestamp::initialize();
+        let dir = TestDir::new("replay");
+        let path = dir.join("rec.bin");
+        write_recording(&path);
+
+        let replayed = Arc::new(AtomicUsize::new(0));
+        let output = replayed.clone();
+        let mut registry = TypeRegistry::new();
+        registry.add_replayable::<u64>(
+            Some("u64"),
+            |_: &str| Box::new(DummyInput),
+            move |_: &str| Box::new(CountingOutput(output.clone())),
+        );
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let connector = MockConnector {
+            sent: sent.clone(),
+            pending: VecDeque::new(),
+        };
+        let mut replayer = Replayer::new(
+            &path,
+            &["topic"],
+            &registry,
+            vec![ACTIVITY],
+            Duration::from_millis(10),
+            Box::new(connector),
+            vec![RECORDER],
+        )
+        .unwrap();
+        replayer.run().unwrap();
+
+        // The data recorded before the first task chain is skipped
+        assert_eq!(replayed.load(Ordering::Relaxed), 2);
+
+        // Only the listed activity is stepped, recorders get the task chain signals in order
+        let cycle = [
+            "recorder Agt-900: start",
+            "activity A1: step A1",
+            "recorder Agt-900: step A1",
+            "recorder Agt-900: ready A1",
+            "recorder Agt-900: end",
+        ];
+        let mut expected = vec![
+            "activity A1: startup A1",
+            "recorder Agt-900: startup A1",
+            "recorder Agt-900: ready A1",
+        ];
+        expected.extend(cycle);
+        expected.extend(cycle);
+        assert_eq!(*sent.lock().unwrap(), expected);
+    }
+}
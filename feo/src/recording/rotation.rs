@@ -0,0 +1,251 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rotation of recording files
+//!
+//! With rotation enabled, a recording is split into numbered files,
+//! e.g. `rec.0000.bin`, `rec.0001.bin`, ... for the recording file `rec.bin`.
+//! Splits only happen at the start of a task chain, and every split file
+//! is a complete recording with its own header and index.
+
+use crate::recording::format::{FileHeader, RecordingWriter};
+use alloc::collections::VecDeque;
+use alloc::format;
+use core::time::Duration;
+use feo_log::{error, info};
+use feo_time::Instant;
+use std::fs;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Configuration of the rotation of recording files
+///
+/// A new split file is started as soon as one of the configured limits is reached.
+/// Rotation is disabled if no limit is configured.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rotation {
+    /// Maximum size of a split file in bytes (may be exceeded by the last task chain)
+    pub max_size: Option<u64>,
+    /// Maximum recorded duration of a split file
+    pub max_duration: Option<Duration>,
+    /// Maximum number of task chains in a split file
+    pub max_cycles: Option<u64>,
+    /// Maximum number of finished split files to keep, deleting the oldest ones
+    ///
+    /// The split file currently written is not counted.
+    pub max_files: Option<usize>,
+    /// Maximum total size of finished split files to keep in bytes, deleting the oldest ones
+    ///
+    /// The split file currently written is not counted.
+    pub max_total_size: Option<u64>,
+}
+
+impl Rotation {
+    /// Check whether rotation is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.max_size.is_some() || self.max_duration.is_some() || self.max_cycles.is_some()
+    }
+}
+
+/// Numbered split files of a recording
+pub(crate) struct SplitFiles {
+    /// Path of the recording as configured
+    path: PathBuf,
+    /// Rotation configuration
+    rotation: Rotation,
+    /// Number of the next split file
+    next: usize,
+    /// Finished split files with their sizes, oldest first
+    finished: VecDeque<(PathBuf, u64)>,
+    /// Path of the current split file
+    current: PathBuf,
+    /// Start time of the current split file
+    current_start: Instant,
+    /// Number of task chains in the current split file
+    current_cycles: u64,
+}
+
+impl SplitFiles {
+    /// Create split files for the given recording path
+    pub(crate) fn new(path: impl Into<PathBuf>, rotation: Rotation) -> Self {
+        let path = path.into();
+        Self {
+            current: path.clone(),
+            path,
+            rotation,
+            next: 0,
+            finished: VecDeque::new(),
+            current_start: Instant::now(),
+            current_cycles: 0,
+        }
+    }
+
+    /// Create the next split file, or the recording file itself if rotation is disabled
+    pub(crate) fn create_next(&mut self) -> io::Result<fs::File> {
+        self.current = if self.rotation.is_enabled() {
+            split_path(&self.path, self.next)
+        } else {
+            self.path.clone()
+        };
+        self.next += 1;
+        self.current_start = Instant::now();
+        self.current_cycles = 0;
+
+        info!("Recording to {}", self.current.display());
+        fs::File::create(&self.current)
+    }
+
+    /// Count the start of a task chain in the current split file
+    pub(crate) fn cycle_started(&mut self) {
+        self.current_cycles += 1;
+    }
+
+    /// Check whether the current split file with the given size shall be finished
+    /// before starting the next task chain
+    pub(crate) fn should_rotate(&self, current_size: u64) -> bool {
+        let Rotation {
+            max_size,
+            max_duration,
+            max_cycles,
+            ..
+        } = self.rotation;
+
+        // Every split file contains at least one task chain
+        self.current_cycles > 0
+            && (max_size.is_some_and(|max| current_size >= max)
+                || max_duration.is_some_and(|max| self.current_start.elapsed() >= max)
+                || max_cycles.is_some_and(|max| self.current_cycles >= max))
+    }
+
+    /// Register the current split file as finished with the given size
+    /// and delete the oldest split files exceeding the retention limits
+    pub(crate) fn finish_current(&mut self, size: u64) {
+        self.finished.push_back((self.current.clone(), size));
+
+        loop {
+            // Both limits apply to the finished split files only
+            let total_size: u64 = self.finished.iter().map(|(_, size)| size).sum();
+            let too_many = self
+                .rotation
+                .max_files
+                .is_some_and(|max| self.finished.len() > max);
+            let too_large = self
+                .rotation
+                .max_total_size
+                .is_some_and(|max| total_size > max);
+            if !(too_many || too_large) {
+                break;
+            }
+            let Some((oldest, _)) = self.finished.pop_front() else {
+                break;
+            };
+            info!("Deleting old split file {}", oldest.display());
+            if let Err(e) = fs::remove_file(&oldest) {
+                error!("Failed to delete split file {}: {e}", oldest.display());
+            }
+        }
+    }
+
+    /// Finish the current split file written by the given writer and continue with the next one,
+    /// if a rotation limit is reached
+    pub(crate) fn rotate_if_required(
+        &mut self,
+        writer: &mut RecordingWriter<BufWriter<fs::File>>,
+        header: &FileHeader,
+    ) -> io::Result<()> {
+        if !self.should_rotate(writer.position()) {
+            return Ok(());
+        }
+
+        writer.finish()?;
+        self.finish_current(writer.position());
+        let file = self.create_next()?;
+        *writer = RecordingWriter::new(BufWriter::new(file), header)?;
+        Ok(())
+    }
+}
+
+/// Path of the split file with the given number, e.g. `rec.0001.bin` for `rec.bin`
+pub fn split_path(path: &Path, split: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}.{split:04}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{split:04}"),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::recording::test_util::TestDir;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(
+            split_path(Path::new("./rec.bin"), 1),
+            PathBuf::from("./rec.0001.bin")
+        );
+        assert_eq!(
+            split_path(Path::new("/tmp/rec"), 12),
+            PathBuf::from("/tmp/rec.0012")
+        );
+    }
+
+    #[test]
+    fn test_rotation_and_retention() {
+        let dir = TestDir::new("rotation");
+        let rotation = Rotation {
+            max_cycles: Some(2),
+            max_files: Some(2),
+            ..Default::default()
+        };
+        let mut splits = SplitFiles::new(dir.join("rec.bin"), rotation);
+
+        splits.create_next().unwrap();
+        for _ in 0..3 {
+            assert!(!splits.should_rotate(0));
+            splits.cycle_started();
+            splits.cycle_started();
+            assert!(splits.should_rotate(0));
+            splits.finish_current(0);
+            splits.create_next().unwrap();
+        }
+
+        let mut files: Vec<_> = fs::read_dir(&*dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        // Two finished split files and the current one
+        assert_eq!(files, ["rec.0001.bin", "rec.0002.bin", "rec.0003.bin"]);
+    }
+
+    #[test]
+    fn test_total_size_retention() {
+        let dir = TestDir::new("rotation");
+        let rotation = Rotation {
+            max_cycles: Some(1),
+            max_total_size: Some(250),
+            ..Default::default()
+        };
+        let mut splits = SplitFiles::new(dir.join("rec.bin"), rotation);
+
+        splits.create_next().unwrap();
+        for _ in 0..3 {
+            splits.cycle_started();
+            splits.finish_current(100);
+            splits.create_next().unwrap();
+        }
+
+        let mut files: Vec<_> = fs::read_dir(&*dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        // Two finished split files of 200 bytes in total and the current one
+        assert_eq!(files, ["rec.0001.bin", "rec.0002.bin", "rec.0003.bin"]);
+    }
+}
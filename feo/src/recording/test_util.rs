@@ -0,0 +1,42 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for the tests of the recording modules
+
+use alloc::format;
+use core::ops::Deref;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Temporary directory of a test, removed when dropped
+pub(crate) struct TestDir(PathBuf);
+
+impl TestDir {
+    /// Create an empty directory, unique to the calling test even if tests run in parallel
+    pub(crate) fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let next = NEXT.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("feo_{name}_test_{}_{next}", process::id()));
+        // Remove leftovers of a previous process with the same pid
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -7,7 +7,9 @@
 use alloc::boxed::Box;
 use alloc::string::String;
 use core::ops::Deref as _;
-use feo_com::interface::ActivityInput;
+use feo_com::interface::{ActivityInput, ActivityOutput};
+use feo_log::error;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 /// Transcode data of the given type from com layer representation to recording serialization
@@ -35,13 +37,23 @@ impl<T: Serialize + postcard::experimental::max_size::MaxSize + core::fmt::Debug
     }
 
     /// Read com layer data and serialize them for recording
-    pub fn read_and_serialize<'a>(&self, buf: &'a mut [u8]) -> Option<&'a mut [u8]> {
+    ///
+    /// Data with the given sequence number are skipped, i.e. not serialized.
+    pub fn read_and_serialize<'a>(
+        &self,
+        buf: &'a mut [u8],
+        skip_sequence: Option<u64>,
+    ) -> Option<(&'a mut [u8], Option<u64>)> {
         let input = self.input.read();
         if let Ok(value) = input {
+            let sequence = value.sequence();
+            if sequence.is_some() && sequence == skip_sequence {
+                return None;
+            }
             let value = value.deref();
             feo_log::info!("Serializing {:?}", value);
             let written = postcard::to_slice(value, buf).expect("serialization failed");
-            return Some(written);
+            return Some((written, sequence));
         }
         None
     }
@@ -50,7 +62,14 @@ impl<T: Serialize + postcard::experimental::max_size::MaxSize + core::fmt::Debug
 /// Trait implementing reading and transcoding of com data for recording
 pub trait ComRecTranscoder {
     /// Read com layer data and serialize them for recording
-    fn read_transcode<'a>(&self, buf: &'a mut [u8]) -> Option<&'a mut [u8]>;
+    ///
+    /// Data with the sequence number `skip_sequence` are skipped.
+    /// Returns the serialized data and their sequence number, if provided by the com backend.
+    fn read_transcode<'a>(
+        &self,
+        buf: &'a mut [u8],
+        skip_sequence: Option<u64>,
+    ) -> Option<(&'a mut [u8], Option<u64>)>;
 
     /// Maximum buffer size required for serialization
     fn buffer_size(&self) -> usize;
@@ -69,8 +88,12 @@ impl<T: Serialize + postcard::experimental::max_size::MaxSize + core::fmt::Debug
     fn buffer_size(&self) -> usize {
         T::POSTCARD_MAX_SIZE
     }
-    fn read_transcode<'a>(&self, buf: &'a mut [u8]) -> Option<&'a mut [u8]> {
-        self.read_and_serialize(buf)
+    fn read_transcode<'a>(
+        &self,
+        buf: &'a mut [u8],
+        skip_sequence: Option<u64>,
+    ) -> Option<(&'a mut [u8], Option<u64>)> {
+        self.read_and_serialize(buf, skip_sequence)
     }
 
     fn topic(&self) -> &str {
@@ -93,3 +116,54 @@ pub trait ComRecTranscoderBuilder: Fn(&'static str) -> Box<dyn ComRecTranscoder>
 ///
 /// In particular, this will apply to the [`build`] method of [`RecordingTranscoder`]
 impl<T: Fn(&'static str) -> Box<dyn ComRecTranscoder>> ComRecTranscoderBuilder for T {}
+
+/// Transcode data of the given type from recording serialization to com layer representation
+pub(crate) struct ReplayTranscoder<T: DeserializeOwned + 'static + core::fmt::Debug> {
+    output: Box<dyn ActivityOutput<T>>,
+}
+
+impl<T: DeserializeOwned + core::fmt::Debug> ReplayTranscoder<T> {
+    /// Create a transcoder writing to the given com layer topic
+    pub fn build(
+        output_builder: impl Fn(&str) -> Box<dyn ActivityOutput<T>> + Send,
+        topic: &str,
+    ) -> Box<dyn RecComTranscoder> {
+        let output = output_builder(topic);
+        Box::new(ReplayTranscoder::<T> { output })
+    }
+}
+
+/// Trait implementing deserialization of recorded data and writing to com
+pub trait RecComTranscoder {
+    /// Deserialize recorded data and write them to com, returning false on failure
+    fn transcode_write(&mut self, data: &[u8]) -> bool;
+}
+
+/// Implement the deserialization-and-writing trait for all [`ReplayTranscoder`] types
+impl<T: DeserializeOwned + core::fmt::Debug> RecComTranscoder for ReplayTranscoder<T> {
+    fn transcode_write(&mut self, data: &[u8]) -> bool {
+        let value: T = match postcard::from_bytes(data) {
+            Ok(value) => value,
+            Err(e) => {
+                error!("Failed to deserialize recorded data: {e}");
+                return false;
+            }
+        };
+        match self.output.write_uninit() {
+            Ok(buffer) => buffer.write_payload(value).send().is_ok(),
+            Err(e) => {
+                error!("Failed to get output buffer: {e:?}");
+                false
+            }
+        }
+    }
+}
+
+/// Builder trait for a [`RecComTranscoder`] object
+///
+/// A builder is a function taking a com layer topic and creating a [`RecComTranscoder`] object
+/// for that topic
+pub trait RecComTranscoderBuilder: Fn(&str) -> Box<dyn RecComTranscoder> {}
+
+/// Implement the builder trait for any function matching the [`RecComTranscoderBuilder`] builder trait.
+impl<T: Fn(&str) -> Box<dyn RecComTranscoder>> RecComTranscoderBuilder for T {}
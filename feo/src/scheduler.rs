@@ -7,8 +7,10 @@
 use crate::error::Error;
 use crate::ids::{ActivityId, AgentId};
 use crate::signalling::common::interface::ConnectScheduler;
-use crate::signalling::common::signals::Signal;
+use crate::signalling::common::signals::{Signal, TriggerReason};
 use crate::timestamp::timestamp;
+#[cfg(feature = "runtime_tracing")]
+use crate::trace;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use feo_log::{debug, error, info, trace};
@@ -37,6 +39,9 @@ pub(crate) struct Scheduler {
     recorder_ids: Vec<AgentId>,
     /// Map from recorder agent ID to ready state
     recorders_ready: HashMap<AgentId, bool>,
+
+    /// Number of the current task chain
+    cycle: u64,
 }
 
 impl Scheduler {
@@ -70,6 +75,7 @@ impl Scheduler {
             activity_states,
             recorder_ids,
             recorders_ready,
+            cycle: 0,
         }
     }
 
@@ -84,9 +90,6 @@ impl Scheduler {
     ///
     /// Shutdown is not implemented, as it is not yet defined in the architecture
     pub(crate) fn run(&mut self) {
-        #[cfg(feature = "loop_duration_meter")]
-        let mut meter = loop_duration_meter::LoopDurationMeter::<1000>::default();
-
         // Sort activity ids
         let mut activity_ids: Vec<_> = self.activity_states.keys().collect();
         activity_ids.sort();
@@ -108,6 +111,8 @@ impl Scheduler {
         // Loop the FEO task chain
         loop {
             let task_chain_start = Instant::now();
+            #[cfg(feature = "runtime_tracing")]
+            let task_chain_span = trace::task_chain_span(self.cycle);
 
             // Record start of task chain on registered recorders
             self.record_task_chain_start().unwrap();
@@ -132,15 +137,21 @@ impl Scheduler {
             // => wait until all recorders have signalled to be ready
             trace!("Flushing recorders");
             let start_flush = Instant::now();
+            #[cfg(feature = "runtime_tracing")]
+            let flush_span = trace::flush_recorders_span();
             self.record_task_chain_end().unwrap();
             self.wait_recorders_ready().unwrap();
+            #[cfg(feature = "runtime_tracing")]
+            drop(flush_span);
             let flush_duration = start_flush.elapsed();
             trace!("Flushing recorders took {flush_duration:?}");
 
             let task_chain_duration = task_chain_start.elapsed();
+            #[cfg(feature = "runtime_tracing")]
+            drop(task_chain_span);
 
             #[cfg(feature = "loop_duration_meter")]
-            meter.track(&task_chain_duration);
+            feo_tracing::counter!("loop_duration_us", task_chain_duration.as_micros() as u64);
 
             let time_left = self.cycle_time.saturating_sub(task_chain_duration);
             if time_left.is_zero() {
@@ -148,12 +159,23 @@ impl Scheduler {
                     "Finished task chain after {task_chain_duration:?}. Expected to be less than {:?}",
                     self.cycle_time
                 );
+                if let Err(e) = self.trigger_recorders(TriggerReason::Overrun) {
+                    error!("Failed to trigger recorders after overrun, continuing: {e}");
+                }
+                // Keep the trace of the overrun in all agents, if the flight recorder is enabled
+                feo_tracing::request_dump();
+                let signal = Signal::RecordingTrigger((TriggerReason::Overrun, timestamp()));
+                if let Err(e) = self.connector.send_to_remote_agents(&signal) {
+                    error!("Failed to notify remote agents of overrun, continuing: {e}");
+                }
             } else {
                 debug!(
                     "Finished task chain after {task_chain_duration:?}. Sleeping for {time_left:?}"
                 );
                 thread::sleep(time_left);
             }
+
+            self.cycle += 1;
         }
     }
 
@@ -174,8 +196,10 @@ impl Scheduler {
                 .filter(|(id, _)| dependencies.contains(id))
                 .all(|(_, state)| state.ready);
             if is_ready {
-                Self::step_activity(act_id, &self.recorder_ids, &mut self.connector)
+                Self::step_activity(act_id, self.cycle, &self.recorder_ids, &mut self.connector)
                     .expect("failed to step activity");
+                #[cfg(feature = "runtime_tracing")]
+                trace::step_triggered(*act_id, self.cycle);
                 self.activity_states.get_mut(act_id).unwrap().triggered = true;
             }
         }
@@ -195,11 +219,12 @@ impl Scheduler {
     /// Send step signal to the given activity
     fn step_activity(
         id: &ActivityId,
+        cycle: u64,
         recorder_ids: &[AgentId],
         connector: &mut Box<dyn ConnectScheduler>,
     ) -> Result<(), Error> {
         debug!("Triggering step for activity {}", id);
-        let signal = Signal::Step((*id, timestamp()));
+        let signal = Signal::Step((*id, cycle, timestamp()));
         Self::trigger_activity(id, &signal, recorder_ids, connector)
     }
 
@@ -242,6 +267,11 @@ impl Scheduler {
                     }
                     break id;
                 }
+                Some(signal @ Signal::RecordingTrigger(_)) => {
+                    for recorder_id in self.recorder_ids.iter() {
+                        self.connector.send_to_recorder(*recorder_id, &signal)?;
+                    }
+                }
                 Some(other) => {
                     error!("Received unexpected signal {other:?} while waiting for ready signal");
                 }
@@ -249,7 +279,14 @@ impl Scheduler {
         };
 
         // Set corresponding ready flag
-        self.activity_states.get_mut(&activity_id).unwrap().ready = true;
+        let state = self.activity_states.get_mut(&activity_id).unwrap();
+        state.ready = true;
+
+        // Only steps are triggered, startups are not
+        #[cfg(feature = "runtime_tracing")]
+        if state.triggered {
+            trace::step_ready(activity_id, self.cycle);
+        }
         Ok(())
     }
 
@@ -276,6 +313,15 @@ impl Scheduler {
         Ok(())
     }
 
+    fn trigger_recorders(&mut self, reason: TriggerReason) -> Result<(), Error> {
+        trace!("Triggering recorders: {reason}");
+        let signal = &Signal::RecordingTrigger((reason, timestamp()));
+        for id in self.recorder_ids.iter() {
+            self.connector.send_to_recorder(*id, signal)?;
+        }
+        Ok(())
+    }
+
     fn wait_recorders_ready(&mut self) -> Result<(), Error> {
         // If there are no recorders registered, return immediately
         if self.recorder_ids.is_empty() {
@@ -317,30 +363,3 @@ struct ActivityState {
     /// Whether the activity has finished its previously triggered operation
     ready: bool,
 }
-
-#[cfg(feature = "loop_duration_meter")]
-mod loop_duration_meter {
-    use std::println;
-
-    /// Meter to track and periodically print average durations
-    #[derive(Default)]
-    pub(super) struct LoopDurationMeter<const NUM_STEPS: usize> {
-        duration_micros: usize,
-        num_steps: usize,
-    }
-
-    impl<const NUM_STEPS: usize> LoopDurationMeter<NUM_STEPS> {
-        pub fn track(&mut self, duration: &feo_time::Duration) {
-            self.duration_micros +=
-                duration.subsec_micros() as usize + 1000000 * duration.as_secs() as usize;
-            self.num_steps += 1;
-
-            if self.num_steps == NUM_STEPS {
-                let avg_micros = self.duration_micros / NUM_STEPS;
-                println!("Loop duration {avg_micros}µs (average of {NUM_STEPS} steps)");
-                self.duration_micros = 0;
-                self.num_steps = 0;
-            }
-        }
-    }
-}
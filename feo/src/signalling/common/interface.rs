@@ -25,6 +25,12 @@ pub(crate) trait ConnectScheduler {
 
     /// Send `signal` to the recorder with `recorder_id`
     fn send_to_recorder(&mut self, recorder_id: AgentId, signal: &Signal) -> Result<(), Error>;
+
+    /// Send `signal` to all agents running activities in other processes than the scheduler
+    ///
+    /// Each of these agents receives the signal at least once, either by one of its workers
+    /// or by the agent itself. Workers in the process of the scheduler may receive it as well.
+    fn send_to_remote_agents(&mut self, signal: &Signal) -> Result<(), Error>;
 }
 
 /// Trait for the connector of a worker
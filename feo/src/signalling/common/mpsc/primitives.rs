@@ -5,6 +5,8 @@
 //! Primitive building blocks of mpsc channel signalling implementation
 
 use crate::error::Error;
+#[cfg(feature = "runtime_tracing")]
+use crate::trace;
 use core::fmt;
 use core::time::Duration;
 use std::sync::mpsc;
@@ -30,7 +32,11 @@ impl<T: fmt::Debug + 'static> Receiver<T> {
 
     pub fn receive(&mut self, timeout: Duration) -> Result<Option<T>, Error> {
         match self.receiver.recv_timeout(timeout) {
-            Ok(v) => Ok(Some(v)),
+            Ok(v) => {
+                #[cfg(feature = "runtime_tracing")]
+                trace::signal_received(&v);
+                Ok(Some(v))
+            }
             Err(err) => match err {
                 RecvTimeoutError::Timeout => Ok(None),
                 _ => Err(Error::Channel("channel closed")),
@@ -50,6 +56,8 @@ impl<T: fmt::Debug + 'static> Sender<T> {
     }
 
     pub fn send(&mut self, t: T) -> Result<(), Error> {
+        #[cfg(feature = "runtime_tracing")]
+        let _span = trace::send_signal_span(&t);
         self.sender
             .send(t)
             .map_err(|_| Error::Channel("channel closed"))?;
@@ -25,8 +25,9 @@ pub enum Signal {
     // Signal sent by the scheduler on the primary agent to trigger an activity's shutdown method
     Shutdown((ActivityId, Timestamp)),
 
-    // Signal sent by the scheduler on the primary agent to trigger an activity's step method
-    Step((ActivityId, Timestamp)),
+    // Signal sent by the scheduler on the primary agent to trigger an activity's step method,
+    // carrying the number of the current task chain
+    Step((ActivityId, u64, Timestamp)),
 
     // Signal sent to indicate that a previously triggered activity method has finished
     Ready((ActivityId, Timestamp)),
@@ -39,6 +40,71 @@ pub enum Signal {
 
     // Signal sent to indicate that a recorder operation has finished
     RecorderReady((AgentId, Timestamp)),
+
+    // Signal sent to the recorders to trigger writing their black-box buffers
+    RecordingTrigger((TriggerReason, Timestamp)),
+}
+
+/// Reason of a recording trigger
+#[cfg_attr(feature = "recording", derive(Serialize, Deserialize, MaxSize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TriggerReason {
+    /// Requested by an activity
+    ActivityRequest(ActivityId),
+    /// A task chain exceeded the cycle time
+    Overrun,
+    /// An activity method panicked
+    ActivityFailure(ActivityId),
+    /// Triggered from outside of the task chain, e.g. by the user
+    External,
+}
+
+// Encoding of trigger reasons into a single u64 for the socket protocol:
+// the reason in the upper 8 bits, the activity ID (if any) in the lower 56 bits
+const TRIGGER_REASON_SHIFT: u32 = 56;
+const TRIGGER_ID_MASK: u64 = (1 << TRIGGER_REASON_SHIFT) - 1;
+
+impl From<&TriggerReason> for u64 {
+    fn from(reason: &TriggerReason) -> u64 {
+        let (tag, id) = match reason {
+            TriggerReason::ActivityRequest(id) => (1, u64::from(id)),
+            TriggerReason::Overrun => (2, 0),
+            TriggerReason::ActivityFailure(id) => (3, u64::from(id)),
+            TriggerReason::External => (4, 0),
+        };
+        debug_assert!(
+            id <= TRIGGER_ID_MASK,
+            "activity id too large for trigger encoding"
+        );
+        (tag << TRIGGER_REASON_SHIFT) | (id & TRIGGER_ID_MASK)
+    }
+}
+
+impl TryFrom<u64> for TriggerReason {
+    type Error = u64;
+
+    /// Decode a trigger reason, returning the value as error if its reason tag is unknown
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        let id = ActivityId::from(value & TRIGGER_ID_MASK);
+        match value >> TRIGGER_REASON_SHIFT {
+            1 => Ok(TriggerReason::ActivityRequest(id)),
+            2 => Ok(TriggerReason::Overrun),
+            3 => Ok(TriggerReason::ActivityFailure(id)),
+            4 => Ok(TriggerReason::External),
+            _ => Err(value),
+        }
+    }
+}
+
+impl Display for TriggerReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TriggerReason::ActivityRequest(id) => write!(f, "ActivityRequest({id})"),
+            TriggerReason::Overrun => write!(f, "Overrun"),
+            TriggerReason::ActivityFailure(id) => write!(f, "ActivityFailure({id})"),
+            TriggerReason::External => write!(f, "External"),
+        }
+    }
 }
 
 impl Display for Signal {
@@ -47,11 +113,14 @@ impl Display for Signal {
             Signal::StartupSync(t) => write!(f, "StartupSync({t:?})"),
             Signal::Startup((id, t)) => write!(f, "Startup({id}, {t:?})"),
             Signal::Shutdown((id, t)) => write!(f, "Shutdown({id}, {t:?})"),
-            Signal::Step((id, t)) => write!(f, "Step({id}, {t:?})"),
+            Signal::Step((id, cycle, t)) => write!(f, "Step({id}, {cycle}, {t:?})"),
             Signal::Ready((id, t)) => write!(f, "Ready({id}, {t:?})"),
             Signal::TaskChainStart(t) => write!(f, "TaskChainStart({t:?})"),
             Signal::TaskChainEnd(t) => write!(f, "TaskChainEnd({t:?})"),
             Signal::RecorderReady((id, t)) => write!(f, "RecorderReady({id}, {t:?})"),
+            Signal::RecordingTrigger((reason, t)) => {
+                write!(f, "RecordingTrigger({reason}, {t:?})")
+            }
         }
     }
 }
@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::signalling::common::socket::EncodeDecode;
+#[cfg(feature = "runtime_tracing")]
+use crate::trace;
 use core::marker::PhantomData;
 use feo_log::trace;
 use mio::net::{TcpStream, UnixStream};
@@ -74,6 +76,16 @@ where
 {
     /// Try to read from this connection
     pub(crate) fn read(&mut self) -> io::Result<Option<M>> {
+        let msg = self.read_message()?;
+        #[cfg(feature = "runtime_tracing")]
+        if let Some(msg) = msg.as_ref() {
+            trace::signal_received(msg);
+        }
+        Ok(msg)
+    }
+
+    /// Try to read a message from the buffer or, if there is none, from the stream
+    fn read_message(&mut self) -> io::Result<Option<M>> {
         if self.buffer_readable {
             if let Some(msg) = self.parse_from_buffer() {
                 return Ok(Some(msg));
@@ -95,6 +107,9 @@ where
 
     /// Send a [Message] through the stream
     pub(crate) fn send(&mut self, msg: &M) -> io::Result<()> {
+        #[cfg(feature = "runtime_tracing")]
+        let _span = trace::send_signal_span(msg);
+
         // Encode message to buffer.
         // Buffered writes reduce the signalling overhead by 33% through less fragmentation.
         let mut writer = Cursor::new(&mut self.send_buffer[..]);
@@ -5,8 +5,9 @@
 //! Socket signalling building blocks
 
 use crate::ids::{ActivityId, AgentId, ChannelId, RelayId, WorkerId};
-use crate::signalling::common::signals::Signal;
+use crate::signalling::common::signals::{Signal, TriggerReason};
 use crate::timestamp::{SyncInfo, Timestamp};
+use core::fmt;
 use std::io::{self, Write};
 use std::os::fd::AsRawFd;
 
@@ -17,7 +18,7 @@ pub(crate) mod server;
 /// Trait providing encoding and decoding methods
 ///
 /// This is used as a bound on the [connection::Connection] primitive.
-pub(crate) trait EncodeDecode: Sized {
+pub(crate) trait EncodeDecode: Sized + fmt::Debug {
     /// Encode type to the writer
     fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>;
 
@@ -137,6 +138,32 @@ macro_rules! decode_data {
             let connector_signal = $variant(connector_signal);
         )+
 
+        Some((connector_signal, consumed_bytes))
+    }};
+    // Variant with a wrapped triple
+    ($src:expr; $( $variant:expr ),+; $from1:ty => $to1:ty; $from2:ty => $to2:ty; $from3:ty => $to3:ty) => {{
+        // Extract data for all three values
+        const LENGTH1: usize = core::mem::size_of::<$from1>();
+        const LENGTH2: usize = core::mem::size_of::<$from2>();
+        const LENGTH3: usize = core::mem::size_of::<$from3>();
+        const END2: usize = LENGTH1 + LENGTH2;
+        const END3: usize = END2 + LENGTH3;
+        let data: [u8; END3] = (&$src[..END3]).try_into().unwrap();
+
+        // Extract values
+        let value1: $to1 = <$from1>::from_le_bytes(data[0..LENGTH1].try_into().unwrap()).into();
+        let value2: $to2 = <$from2>::from_le_bytes(data[LENGTH1..END2].try_into().unwrap()).into();
+        let value3: $to3 = <$from3>::from_le_bytes(data[END2..END3].try_into().unwrap()).into();
+
+        // Calculate the number of consumed bytes
+        let consumed_bytes = 2 + END3;
+
+        // Recursively wrap values into enum layers
+        let connector_signal = (value1, value2, value3);
+        $(
+            let connector_signal = $variant(connector_signal);
+        )+
+
         Some((connector_signal, consumed_bytes))
     }};
 }
@@ -167,13 +194,16 @@ impl EncodeDecode for ProtocolSignal {
             ProtocolSignal::Core(Signal::RecorderReady((agent_id, timestamp))) => {
                 encode_data!(w; SignalTag::CoreRecorderReady; agent_id => u64, timestamp => u128);
             }
+            ProtocolSignal::Core(Signal::RecordingTrigger((reason, timestamp))) => {
+                encode_data!(w; SignalTag::CoreRecordingTrigger; reason => u64, timestamp => u128);
+            }
 
             // Activity-related
             ProtocolSignal::Core(Signal::Startup((activity_id, timestamp))) => {
                 encode_data!(w; SignalTag::CoreStartup; activity_id => u64, timestamp => u128);
             }
-            ProtocolSignal::Core(Signal::Step((activity_id, timestamp))) => {
-                encode_data!(w; SignalTag::CoreStep; activity_id => u64, timestamp => u128);
+            ProtocolSignal::Core(Signal::Step((activity_id, cycle, timestamp))) => {
+                encode_data!(w; SignalTag::CoreStep; activity_id => u64, *cycle => u64, timestamp => u128);
             }
             ProtocolSignal::Core(Signal::Shutdown((activity_id, timestamp))) => {
                 encode_data!(w; SignalTag::CoreShutdown; activity_id => u64, timestamp => u128);
@@ -249,13 +279,26 @@ impl EncodeDecode for ProtocolSignal {
             CoreRecorderReady => {
                 decode_data!(src; Signal::RecorderReady, ProtocolSignal::Core; u64 => AgentId; u128 => Timestamp)
             }
+            CoreRecordingTrigger => {
+                // Decoded without `decode_data!`, since not every value is a valid trigger reason
+                const LENGTH1: usize = core::mem::size_of::<u64>();
+                const LENGTH2: usize = core::mem::size_of::<u128>();
+                let reason = u64::from_le_bytes(src[..LENGTH1].try_into().unwrap());
+                let Ok(reason) = TriggerReason::try_from(reason) else {
+                    panic!("failed to parse unknown trigger reason {reason:#x}");
+                };
+                let timestamp =
+                    u128::from_le_bytes(src[LENGTH1..(LENGTH1 + LENGTH2)].try_into().unwrap());
+                let signal = Signal::RecordingTrigger((reason, timestamp.into()));
+                Some((ProtocolSignal::Core(signal), 2 + LENGTH1 + LENGTH2))
+            }
 
             // Activity-related
             CoreStartup => {
                 decode_data!(src; Signal::Startup, ProtocolSignal::Core; u64 => ActivityId; u128 => Timestamp)
             }
             CoreStep => {
-                decode_data!(src; Signal::Step, ProtocolSignal::Core; u64 => ActivityId; u128 => Timestamp)
+                decode_data!(src; Signal::Step, ProtocolSignal::Core; u64 => ActivityId; u64 => u64; u128 => Timestamp)
             }
             CoreShutdown => {
                 decode_data!(src; Signal::Shutdown, ProtocolSignal::Core; u64 => ActivityId; u128 => Timestamp)
@@ -294,6 +337,7 @@ pub(crate) enum SignalTag {
     CoreTaskChainStart = 11,
     CoreTaskChainEnd = 12,
     CoreRecorderReady = 13,
+    CoreRecordingTrigger = 14,
     CoreStartup = 21,
     CoreStep = 22,
     CoreShutdown = 23,
@@ -317,6 +361,7 @@ impl TryFrom<u8> for SignalTag {
             v if v == CoreTaskChainStart as u8 => Ok(CoreTaskChainStart),
             v if v == CoreTaskChainEnd as u8 => Ok(CoreTaskChainEnd),
             v if v == CoreRecorderReady as u8 => Ok(CoreRecorderReady),
+            v if v == CoreRecordingTrigger as u8 => Ok(CoreRecordingTrigger),
             v if v == CoreStartup as u8 => Ok(CoreStartup),
             v if v == CoreStep as u8 => Ok(CoreStep),
             v if v == CoreShutdown as u8 => Ok(CoreShutdown),
@@ -341,8 +386,10 @@ fn connector_signal_roundtrips() {
         (ProtocolSignal::Core(Signal::TaskChainStart(timestamp)), 18),
         (ProtocolSignal::Core(Signal::TaskChainEnd(timestamp)), 18),
         (ProtocolSignal::Core(Signal::RecorderReady((AgentId::from(123), timestamp))), 26),
+        (ProtocolSignal::Core(Signal::RecordingTrigger((TriggerReason::Overrun, timestamp))), 26),
+        (ProtocolSignal::Core(Signal::RecordingTrigger((TriggerReason::ActivityFailure(ActivityId::from(123)), timestamp))), 26),
         (ProtocolSignal::Core(Signal::Startup((ActivityId::from(123), timestamp))), 26),
-        (ProtocolSignal::Core(Signal::Step((ActivityId::from(123), timestamp))), 26),
+        (ProtocolSignal::Core(Signal::Step((ActivityId::from(123), 7, timestamp))), 34),
         (ProtocolSignal::Core(Signal::Shutdown((ActivityId::from(123), timestamp))), 26),
         (ProtocolSignal::Core(Signal::Ready((ActivityId::from(123), timestamp))), 26),
         (ProtocolSignal::ActivityHello(ActivityId::from(123)), 10),
@@ -360,3 +407,19 @@ fn connector_signal_roundtrips() {
         assert_eq!(consumed, consumed_bytes);
     }
 }
+
+#[test]
+#[should_panic(expected = "unknown trigger reason")]
+fn unknown_trigger_reason_is_rejected() {
+    let timestamp = Timestamp(feo_time::Duration::from_secs(1));
+    let signal = ProtocolSignal::Core(Signal::RecordingTrigger((
+        TriggerReason::External,
+        timestamp,
+    )));
+    let mut buffer = [0; 128];
+    signal.encode(&mut &mut buffer[..]).unwrap();
+
+    // Replace the reason tag in the most significant byte of the encoded reason
+    buffer[2 + size_of::<u64>() - 1] = 0xff;
+    ProtocolSignal::try_decode(&buffer);
+}
@@ -161,4 +161,9 @@ impl ConnectScheduler for SchedulerConnector {
     fn send_to_recorder(&mut self, _recorder_id: AgentId, _signal: &Signal) -> Result<(), Error> {
         unimplemented!("Recording not supported with mpsc channels");
     }
+
+    /// All activities run in the process of the scheduler
+    fn send_to_remote_agents(&mut self, _signal: &Signal) -> Result<(), Error> {
+        Ok(())
+    }
 }
@@ -172,4 +172,14 @@ where
             .send(token, &ProtocolSignal::Core(*signal))
             .map_err(|e| Error::Io((e, "failed to send")))
     }
+
+    fn send_to_remote_agents(&mut self, signal: &Signal) -> Result<(), Error> {
+        // The agents of the activities are unknown, so the signal is sent to all activities
+        for token in self.activity_id_token_map.values() {
+            self.server
+                .send(token, &ProtocolSignal::Core(*signal))
+                .map_err(|e| Error::Io((e, "failed to send")))?;
+        }
+        Ok(())
+    }
 }
@@ -6,7 +6,7 @@
 
 use crate::error::Error;
 use crate::ids::{ActivityId, AgentId, ChannelId, WorkerId};
-use crate::signalling::common::signals::Signal;
+use crate::signalling::common::signals::{Signal, TriggerReason};
 use crate::signalling::relayed::interface::{
     Builder, IsChannel, ProtocolMultiRecv, ProtocolMultiSend, ProtocolRecv, ProtocolSend,
 };
@@ -262,8 +262,13 @@ impl<Inter: IsChannel, Intra: IsChannel> SecondaryReceiveRelay<Inter, Intra> {
             // Check signal type and extract activity ID
             let act_id = match core_signal {
                 Signal::Startup((act_id, _)) => act_id,
-                Signal::Step((act_id, _)) => act_id,
+                Signal::Step((act_id, _, _)) => act_id,
                 Signal::Shutdown((act_id, _)) => act_id,
+                Signal::RecordingTrigger((TriggerReason::Overrun, _)) => {
+                    // Keep the trace of the overrun, if the flight recorder is enabled
+                    feo_tracing::request_dump();
+                    continue;
+                }
                 other => {
                     error!("Received unexpected signal {other:?}");
                     continue;
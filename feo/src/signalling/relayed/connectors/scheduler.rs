@@ -133,4 +133,18 @@ impl<Inter: IsChannel, Intra: IsChannel> ConnectScheduler for SchedulerConnector
     fn send_to_recorder(&mut self, recorder_id: AgentId, signal: &Signal) -> Result<(), Error> {
         self.send_to_agent(recorder_id, (*signal).into())
     }
+
+    fn send_to_remote_agents(&mut self, signal: &Signal) -> Result<(), Error> {
+        // Agents of the workers not running in this process
+        let agents: HashSet<AgentId> = self
+            .worker_agent_map
+            .iter()
+            .filter(|(worker_id, _)| !self.local_workers.contains(worker_id))
+            .map(|(_, agent_id)| *agent_id)
+            .collect();
+        for agent_id in agents {
+            self.send_to_agent(agent_id, (*signal).into())?;
+        }
+        Ok(())
+    }
 }
@@ -28,6 +28,8 @@ pub enum Direction {
 pub struct TopicSpecification<'a> {
     /// The topic
     pub topic: Topic<'a>,
+    /// System type name of the data on the topic as provided by [`core::any::type_name`]
+    pub type_name: &'static str,
     /// Peers with [ActivityId] and communication [Direction] for this topic
     pub peers: Vec<(ActivityId, Direction)>,
     /// Function to initialize this topic with the number of writers and readers as arguments
@@ -44,6 +46,7 @@ impl<'a> TopicSpecification<'a> {
         let init_secondary_fn = Box::new(init_topic_secondary::<T>);
         Self {
             topic,
+            type_name: core::any::type_name::<T>(),
             peers,
             init_primary_fn,
             init_secondary_fn,
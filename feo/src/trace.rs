@@ -0,0 +1,106 @@
+// Copyright 2025 Accenture.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracing of the runtime via feo-tracing
+//!
+//! The runtime emits spans of task chains, activity steps and recorder work,
+//! as well as events of the signals sent and received by the connectors and relays.
+//!
+//! Triggering a step of an activity in the scheduler, executing the step in a worker and
+//! receiving the ready signal of the activity in the scheduler are linked by a flow.
+//! The flow id is derived from the activity id and the number of the task chain carried
+//! by the step signal, so that scheduler and workers arrive at the same id independent
+//! of the process they run in.
+
+use crate::ids::{ActivityId, AgentId, WorkerId};
+use core::fmt::Debug;
+use core::sync::atomic::{AtomicU64, Ordering};
+use feo_tracing::tracing::span::EnteredSpan;
+use feo_tracing::{event, span, Level};
+
+/// Number of bits of the flow id taken by the activity id
+const ACTIVITY_BITS: u32 = 20;
+
+/// Id of the agent running in this process
+static AGENT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Set the id of the agent running in this process, attached to the spans of activity steps
+pub(crate) fn set_agent_id(agent_id: AgentId) {
+    AGENT_ID.store(u64::from(agent_id), Ordering::Relaxed);
+}
+
+/// Derive the id of the flow of the given activity's step in the given task chain
+fn flow_id(activity_id: ActivityId, cycle: u64) -> u64 {
+    let activity = u64::from(activity_id) & ((1 << ACTIVITY_BITS) - 1);
+    // Avoid the id 0
+    (cycle + 1) << ACTIVITY_BITS | activity
+}
+
+/// Enter the span of a task chain in the scheduler, until the scheduler sleeps
+pub(crate) fn task_chain_span(cycle: u64) -> EnteredSpan {
+    span!(Level::INFO, "task chain", cycle).entered()
+}
+
+/// Enter the span of the scheduler waiting for the recorders at the end of a task chain
+pub(crate) fn flush_recorders_span() -> EnteredSpan {
+    span!(Level::DEBUG, "flush recorders").entered()
+}
+
+/// Emit the start of the flow of an activity step triggered by the scheduler
+pub(crate) fn step_triggered(activity_id: ActivityId, cycle: u64) {
+    event!(
+        name: "trigger step",
+        Level::INFO,
+        activity = u64::from(activity_id),
+        cycle,
+        flow = flow_id(activity_id, cycle)
+    );
+}
+
+/// Enter the span of an activity step executed by a worker, continuing the flow
+pub(crate) fn step_span(activity_id: ActivityId, worker_id: WorkerId, cycle: u64) -> EnteredSpan {
+    span!(
+        Level::INFO,
+        "step",
+        activity = u64::from(activity_id),
+        worker = u64::from(worker_id),
+        agent = AGENT_ID.load(Ordering::Relaxed),
+        cycle,
+        flow = flow_id(activity_id, cycle)
+    )
+    .entered()
+}
+
+/// Emit the end of the flow of an activity step whose ready signal arrived at the scheduler
+pub(crate) fn step_ready(activity_id: ActivityId, cycle: u64) {
+    event!(
+        name: "step ready",
+        Level::INFO,
+        activity = u64::from(activity_id),
+        cycle,
+        terminating_flow = flow_id(activity_id, cycle)
+    );
+}
+
+/// Enter the span of sending a signal through a channel or socket
+pub(crate) fn send_signal_span(signal: &impl Debug) -> EnteredSpan {
+    span!(Level::TRACE, "send signal", signal = ?signal).entered()
+}
+
+/// Emit the reception of a signal from a channel or socket
+pub(crate) fn signal_received(signal: &impl Debug) {
+    event!(name: "receive signal", Level::TRACE, signal = ?signal);
+}
+
+/// Enter the span of a recorder reading and recording com data
+#[cfg(feature = "recording")]
+pub(crate) fn record_com_data_span() -> EnteredSpan {
+    span!(Level::DEBUG, "record com data").entered()
+}
+
+/// Enter the span of a recorder finishing a task chain, e.g. flushing the recording file
+#[cfg(feature = "recording")]
+pub(crate) fn finish_recorded_task_chain_span() -> EnteredSpan {
+    span!(Level::DEBUG, "finish recorded task chain").entered()
+}
@@ -4,17 +4,21 @@
 
 //! Worker thread running FEO activities
 
-use crate::activity::{Activity, ActivityBuilder};
+use crate::activity::{take_recording_trigger_request, Activity, ActivityBuilder};
 use crate::error::Error;
 use crate::ids::{ActivityId, WorkerId};
 use crate::signalling::common::interface::ConnectWorker;
-use crate::signalling::common::signals::Signal;
+use crate::signalling::common::signals::{Signal, TriggerReason};
 use crate::timestamp;
+#[cfg(feature = "runtime_tracing")]
+use crate::trace;
 use alloc::boxed::Box;
+use core::panic::AssertUnwindSafe;
 use core::time::Duration;
-use feo_log::debug;
+use feo_log::{debug, error};
 use feo_time::Instant;
 use std::collections::HashMap;
+use std::panic;
 
 /// Worker
 ///
@@ -66,12 +70,16 @@ impl<T: ConnectWorker> Worker<T> {
             };
 
             match signal {
-                Signal::Startup((id, _)) | Signal::Step((id, _)) | Signal::Shutdown((id, _)) => {
+                Signal::Startup((id, _)) | Signal::Step((id, _, _)) | Signal::Shutdown((id, _)) => {
                     self.handle_activity_signal(&id, &signal)?;
                 }
                 Signal::StartupSync(sync_info) => {
                     timestamp::initialize_from(sync_info);
                 }
+                Signal::RecordingTrigger((TriggerReason::Overrun, _)) => {
+                    // Keep the trace of the overrun, if the flight recorder is enabled
+                    feo_tracing::request_dump();
+                }
                 other => return Err(Error::UnexpectedSignal(other)),
             }
         }
@@ -86,21 +94,26 @@ impl<T: ConnectWorker> Worker<T> {
 
         match signal {
             Signal::Startup((activity_id, _)) => {
-                activity.startup();
+                Self::run_method(&mut self.connector, *activity_id, || activity.startup())?;
                 let elapsed = start.elapsed();
                 debug!("Ran startup of activity {id:?} in {elapsed:?}");
                 self.connector
                     .send_to_scheduler(&Signal::Ready((*activity_id, timestamp::timestamp())))
             }
-            Signal::Step((activity_id, _)) => {
-                activity.step();
+            Signal::Step((activity_id, cycle, _)) => {
+                // The number of the task chain is only used to attribute traces
+                #[cfg(feature = "runtime_tracing")]
+                let _span = trace::step_span(*activity_id, self.id, *cycle);
+                #[cfg(not(feature = "runtime_tracing"))]
+                let _ = cycle;
+                Self::run_method(&mut self.connector, *activity_id, || activity.step())?;
                 let elapsed = start.elapsed();
                 debug!("Stepped activity {id:?} in {elapsed:?}");
                 self.connector
                     .send_to_scheduler(&Signal::Ready((*activity_id, timestamp::timestamp())))
             }
             Signal::Shutdown((activity_id, _)) => {
-                activity.startup();
+                Self::run_method(&mut self.connector, *activity_id, || activity.startup())?;
                 let elapsed = start.elapsed();
                 debug!("Ran shutdown of activity {id:?} in {elapsed:?}");
                 self.connector
@@ -109,4 +122,34 @@ impl<T: ConnectWorker> Worker<T> {
             other => Err(Error::UnexpectedSignal(*other)),
         }
     }
+
+    /// Run an activity method and send recording triggers requested by it to the scheduler
+    ///
+    /// If the method panics, a recording trigger is sent before resuming the panic.
+    fn run_method(
+        connector: &mut T,
+        activity_id: ActivityId,
+        method: impl FnOnce(),
+    ) -> Result<(), Error> {
+        let result = panic::catch_unwind(AssertUnwindSafe(method));
+
+        let reason = match result {
+            Err(_) => Some(TriggerReason::ActivityFailure(activity_id)),
+            Ok(()) if take_recording_trigger_request() => {
+                Some(TriggerReason::ActivityRequest(activity_id))
+            }
+            Ok(()) => None,
+        };
+        if let Some(reason) = reason {
+            let signal = Signal::RecordingTrigger((reason, timestamp::timestamp()));
+            if let Err(e) = connector.send_to_scheduler(&signal) {
+                error!("Failed to send recording trigger of activity {activity_id}: {e:?}");
+            }
+        }
+
+        match result {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(()) => Ok(()),
+        }
+    }
 }